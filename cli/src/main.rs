@@ -1,10 +1,14 @@
 use std::env::current_dir;
+use std::path::PathBuf;
 
 use anyhow::Result;
 use clap::Parser;
+#[cfg(feature = "jrsonnet")]
+use reytan::JrsonnetFormatPicker;
 use reytan::{
-    AnyExtraction, CoreClient, ExtractLevel, Extractable, Extraction, FormatSelection,
-    MediaFormatURL,
+    AnyExtraction, CoreClient, ExtractLevel, Extractable, Extraction, ExtractorArgs,
+    ExtractorPreference, FormatSelection, FormatSorter, LiveStatus, MediaFormatURL,
+    NativeFormatPicker, SubtitleExt, SubtitleSelection, WaitForStream, DEFAULT_SELECTOR,
 };
 use url::Url;
 
@@ -14,8 +18,83 @@ struct Args {
     #[arg()]
     url: String,
 
-    #[arg(long)]
+    /// Format selector, like `bv[height<=1080]+ba/b` (defaults to `bv+ba/b`, only over HTTP)
+    #[arg(short, long)]
+    format: Option<String>,
+
+    /// Jsonnet format picker code, instead of the format selector
+    #[arg(long, conflicts_with = "format")]
     jsonnet_format: Option<String>,
+
+    /// Directory with the files imported by the Jsonnet format picker (can be used multiple times)
+    #[arg(long, requires = "jsonnet_format")]
+    jsonnet_path: Vec<PathBuf>,
+
+    /// External variable for the Jsonnet format picker, as `name=value` (can be used multiple times)
+    #[arg(long, requires = "jsonnet_format")]
+    jsonnet_ext_str: Vec<String>,
+
+    /// External variable for the Jsonnet format picker, as `name=code` (can be used multiple times)
+    #[arg(long, requires = "jsonnet_format")]
+    jsonnet_ext_code: Vec<String>,
+
+    /// Order of the formats from the best, like `res<=1080,+codec:vp9,br`
+    /// (defaults to `proto,track,res,fps,codec,br,channels`)
+    #[arg(short = 'S', long)]
+    format_sort: Option<String>,
+
+    /// Which list entries to process, like `[duration<=3600][date>=20220101]`
+    /// (Jsonnet code returning the entry indexes with `--jsonnet-format`)
+    #[arg(long)]
+    match_filter: Option<String>,
+
+    /// Fetch the next pages of the list until there are that many entries
+    /// (all of them by default, only the first page of the endless lists like mixes)
+    #[arg(long)]
+    max_entries: Option<usize>,
+
+    /// Download just the video, if the URL points at both a video and a playlist
+    #[arg(long)]
+    no_playlist: bool,
+
+    /// Extractor-specific options, like `youtube:player_client=android,web;merge_formats=false`
+    /// (can be used multiple times)
+    #[arg(long)]
+    extractor_args: Vec<String>,
+
+    /// Wait for a scheduled live stream or premiere to start, instead of failing
+    #[arg(long)]
+    wait_for_stream: bool,
+
+    /// Show which formats would be picked and why, without downloading them
+    #[arg(long)]
+    dry_run: bool,
+
+    /// Download the subtitles in the languages, like `en,de`
+    /// (picked by the code with `--jsonnet-format`)
+    #[arg(long, value_delimiter = ',', conflicts_with = "jsonnet_format")]
+    sub_langs: Vec<String>,
+
+    /// Convert the subtitles to the format: srt, vtt, ass or ttml
+    #[arg(long)]
+    sub_format: Option<String>,
+}
+
+fn parse_subtitle_format(name: &str) -> Result<SubtitleExt> {
+    Ok(match name {
+        "srt" => SubtitleExt::SRT,
+        "vtt" => SubtitleExt::VTT,
+        "ass" => SubtitleExt::ASS,
+        "ttml" => SubtitleExt::TTML,
+        _ => anyhow::bail!("unsupported subtitle format: {}", name),
+    })
+}
+
+/// `name=value`
+#[cfg(feature = "jrsonnet")]
+fn parse_ext_var(var: &str) -> Result<(&str, &str)> {
+    var.split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("expected name=value, got {:?}", var))
 }
 
 struct Reyt<'a> {
@@ -24,45 +103,178 @@ struct Reyt<'a> {
 }
 
 impl<'a> Reyt<'a> {
-    fn new() -> Self {
-        Self {
-            args: Args::parse(),
-            client: CoreClient::new(),
+    fn new() -> Result<Self> {
+        let args = Args::parse();
+        let mut extractor_args = ExtractorArgs::new();
+        for arg in &args.extractor_args {
+            extractor_args.parse_and_add(arg)?;
+        }
+        let mut client = CoreClient::new().with_extractor_args(extractor_args);
+        let sorter = match &args.format_sort {
+            Some(sort) => FormatSorter::parse(sort)?,
+            None => FormatSorter::default(),
+        };
+        if args.jsonnet_format.is_some() {
+            #[cfg(feature = "jrsonnet")]
+            {
+                let mut picker = JrsonnetFormatPicker::new().with_sorter(sorter);
+                for path in &args.jsonnet_path {
+                    picker = picker.with_library_path(path.clone());
+                }
+                for var in &args.jsonnet_ext_str {
+                    let (name, value) = parse_ext_var(var)?;
+                    picker = picker.with_ext_str(name, value);
+                }
+                for var in &args.jsonnet_ext_code {
+                    let (name, code) = parse_ext_var(var)?;
+                    picker = picker.with_ext_code(name, code);
+                }
+                client = client.with_format_picker(Box::new(picker));
+            }
+            #[cfg(not(feature = "jrsonnet"))]
+            anyhow::bail!("--jsonnet-format needs reyt built with the jrsonnet feature");
+        } else {
+            let subtitles = args
+                .sub_langs
+                .iter()
+                .map(|lang| SubtitleSelection::Lang(lang.clone()))
+                .collect();
+            client = client.with_format_picker(Box::new(
+                NativeFormatPicker::new()
+                    .with_sorter(sorter)
+                    .with_subtitles(subtitles),
+            ));
+        }
+        if let Some(sub_format) = &args.sub_format {
+            client = client.with_subtitle_format(parse_subtitle_format(sub_format)?);
         }
+        Ok(Self { args, client })
     }
 
     async fn main(&self) -> Result<()> {
         println!("extracting {}", &self.args.url);
+        let url = Url::parse(&self.args.url)?;
+        let wanted = Extractable {
+            metadata: ExtractLevel::Extended,
+            playback: ExtractLevel::Extended,
+            // only the Jsonnet code and `--sub-langs` pick the subtitles
+            subtitles: if self.args.jsonnet_format.is_some() || !self.args.sub_langs.is_empty() {
+                ExtractLevel::Basic
+            } else {
+                ExtractLevel::None
+            },
+            ..Default::default()
+        };
         let extraction = self
             .client
-            .extract_url(
-                &Url::parse(&self.args.url)?,
-                &Extractable {
-                    metadata: ExtractLevel::Extended,
-                    playback: ExtractLevel::Extended,
+            .extract_url_preferring(
+                &url,
+                &wanted,
+                if self.args.no_playlist {
+                    ExtractorPreference::Recording
+                } else {
+                    ExtractorPreference::List
                 },
             )
             .await?
             .unwrap();
 
         match extraction {
+            AnyExtraction::Recording(e)
+                if self.args.wait_for_stream
+                    && e.metadata.live_status == Some(LiveStatus::IsUpcoming) =>
+            {
+                match e.metadata.scheduled_start_time {
+                    Some(start) => println!("waiting for the stream, scheduled for {}", start),
+                    None => println!("waiting for the stream"),
+                }
+                let e = self
+                    .client
+                    .wait_for_stream(&url, &wanted, &WaitForStream::default())
+                    .await?;
+                self.handle_extraction(&e).await?;
+            }
             AnyExtraction::Recording(e) => {
                 self.handle_extraction(&e).await?;
             }
-            AnyExtraction::List(_) => todo!(),
+            AnyExtraction::List(mut list) => {
+                println!("list: {}\ntitle: {}", list.id, list.title);
+                self.client
+                    .extract_more_entries(&url, &mut list, self.args.max_entries)
+                    .await?;
+                let filter = self.args.match_filter.as_deref().unwrap_or_default();
+                let entries = self.client.pick_entries(filter, &list).await?;
+                println!(
+                    "picked {} of {} entries",
+                    entries.len(),
+                    list.entry_details().len()
+                );
+                for entry in entries {
+                    // one failing entry (e.g. a private video) doesn't stop the rest
+                    if let Err(error) = self.handle_entry(entry, &wanted).await {
+                        println!("error: {:?}", error);
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
+    async fn handle_entry(&self, entry: &AnyExtraction, wanted: &Extractable) -> Result<()> {
+        let (id, title, url) = match entry {
+            AnyExtraction::Recording(e) => (&e.metadata.id, &e.metadata.title, &e.metadata.url),
+            AnyExtraction::List(l) => {
+                println!("skipping the nested list: [{}] {}", l.id, l.title);
+                return Ok(());
+            }
+        };
+        let url = match url {
+            Some(url) => url,
+            None => {
+                println!("skipping [{}] {}: no URL to extract it from", id, title);
+                return Ok(());
+            }
+        };
+        println!("extracting entry {}", url);
+        match self
+            .client
+            .extract_url_preferring(url, wanted, ExtractorPreference::Recording)
+            .await?
+        {
+            Some(AnyExtraction::Recording(e)) => self.handle_extraction(&e).await,
+            _ => anyhow::bail!("not a recording: {}", url),
+        }
+    }
+
     async fn handle_extraction(&self, e: &Extraction) -> Result<()> {
         // println!("{:#?}", e.metadata);
         println!("id: {}\ntitle: {}", e.metadata.id, e.metadata.title);
-        let download_selection = if let Some(selector) = &self.args.jsonnet_format {
-            self.client.pick_formats(selector, e).await?
-        } else {
-            todo!()
-        };
+        let selector = self
+            .args
+            .jsonnet_format
+            .as_deref()
+            .or(self.args.format.as_deref())
+            .unwrap_or(DEFAULT_SELECTOR);
+        if self.args.dry_run {
+            let explanation = self.client.explain_formats(selector, e).await?;
+            println!("picked formats:");
+            let formats = explanation.selection.formats.unwrap_or_default();
+            for (i, (format, reason)) in formats.iter().zip(&explanation.reasons).enumerate() {
+                let (kind, id) = match format {
+                    FormatSelection::Full(id) => ("full", id),
+                    FormatSelection::ExtractVideo(id) => ("extract video", id),
+                    FormatSelection::ExtractAudio(id) => ("extract audio", id),
+                };
+                println!("{i}: {kind}: [{id}] {reason}");
+            }
+            for subtitle in explanation.selection.subtitles.unwrap_or_default() {
+                println!("subtitles: {:?}", subtitle);
+            }
+            println!("formats from the best: {}", explanation.ranking.join(", "));
+            return Ok(());
+        }
+        let download_selection = self.client.pick_formats(selector, e).await?;
         // println!("{:#?}", download_selection);
         println!("download selection:");
         for (i, format) in download_selection.formats.iter().enumerate() {
@@ -90,6 +302,14 @@ impl<'a> Reyt<'a> {
                 }
             }
         }
+        for subtitle in &download_selection.subtitles {
+            println!(
+                "subtitles: [{}] {:?} {}",
+                subtitle.details.lang,
+                subtitle.details.ext,
+                subtitle.url.as_str()
+            );
+        }
         println!("performing downloads");
         self.client
             .download_from_list(
@@ -110,5 +330,5 @@ fn printable_format_url(mfu: &MediaFormatURL) -> String {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    Reyt::new().main().await
+    Reyt::new()?.main().await
 }
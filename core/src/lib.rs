@@ -1,15 +1,19 @@
 use std::env::current_dir;
 use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
 
 use once_cell::sync::Lazy;
 use reytan_download::Downloader;
-use reytan_extractor_api::anyhow::Result;
+use reytan_extractor_api::anyhow::{anyhow, bail, Result};
 use reytan_extractor_api::url::Url;
 pub use reytan_extractor_api::*;
-use reytan_format_picker_api::FormatPicker;
-pub use reytan_format_picker_api::{DownloadList, FormatSelection};
+pub use reytan_format_picker_api::{
+    DownloadList, FormatPicker, FormatSelection, FormatSorter, PickExplanation, SubtitleSelection,
+    UnmatchedSelection, DEFAULT_SORT,
+};
 #[cfg(feature = "jrsonnet")]
-use reytan_format_picker_jrsonnet::JrsonnetFormatPicker;
+pub use reytan_format_picker_jrsonnet::JrsonnetFormatPicker;
+pub use reytan_format_picker_native::{NativeFormatPicker, DEFAULT_SELECTOR};
 
 pub static DEFAULT_EXTRACTOR_LIST: Lazy<Vec<&AnyExtractor>> = Lazy::new(|| {
     let l = vec![].into_iter();
@@ -26,6 +30,50 @@ pub static DEFAULT_EXTRACTOR_LIST: Lazy<Vec<&AnyExtractor>> = Lazy::new(|| {
     l.collect()
 });
 
+/// Which kind of extractor to use, if the URL points at both a recording and a list
+/// (e.g. a YouTube video opened from a playlist)
+#[derive(Default, PartialEq, Clone, Copy, Debug)]
+pub enum ExtractorPreference {
+    List,
+    #[default]
+    Recording,
+}
+
+/// Polling settings for [`CoreClient::wait_for_stream`]
+#[derive(Clone, Debug)]
+pub struct WaitForStream {
+    /// How often to check if the stream has started
+    pub poll_interval: Duration,
+    /// Give up after waiting this long
+    pub timeout: Option<Duration>,
+    /// Give up after this many failed checks in a row
+    pub max_failed_checks: u32,
+}
+
+impl Default for WaitForStream {
+    fn default() -> Self {
+        WaitForStream {
+            poll_interval: Duration::from_secs(60),
+            timeout: None,
+            max_failed_checks: 5,
+        }
+    }
+}
+
+/// Sleeps until the scheduled start if it's further away than the interval,
+/// otherwise (not known, or overdue) just for the interval
+fn next_poll_delay(
+    metadata: &MediaMetadata,
+    now: DateTime<Utc>,
+    poll_interval: Duration,
+) -> Duration {
+    metadata
+        .scheduled_start_time
+        .and_then(|start| (start - now).to_std().ok())
+        .filter(|until_start| *until_start > poll_interval)
+        .unwrap_or(poll_interval)
+}
+
 pub struct CoreClient<'a> {
     extractors: Vec<&'a AnyExtractor>,
     context: ExtractionContext,
@@ -38,26 +86,124 @@ impl<'a> CoreClient<'a> {
         CoreClient {
             extractors: DEFAULT_EXTRACTOR_LIST.to_vec(),
             context: ExtractionContext::new().unwrap(),
-            #[cfg(feature = "jrsonnet")]
-            format_picker: Box::new(JrsonnetFormatPicker::new()),
+            format_picker: Box::new(NativeFormatPicker::new()),
             downloader: Downloader::new(),
         }
     }
 
+    /// Sets the extractor-specific options, see [`ExtractorArgs`]
+    pub fn with_extractor_args(mut self, extractor_args: ExtractorArgs) -> Self {
+        self.context.extractor_args = extractor_args;
+        self
+    }
+
+    /// Replaces the default [`NativeFormatPicker`], e.g. with the Jsonnet one
+    pub fn with_format_picker(mut self, format_picker: Box<dyn FormatPicker>) -> Self {
+        self.format_picker = format_picker;
+        self
+    }
+
+    /// Converts the downloaded subtitles to the format (SRT, VTT, ASS or TTML)
+    pub fn with_subtitle_format(mut self, ext: SubtitleExt) -> Self {
+        self.downloader = self.downloader.with_subtitle_format(ext);
+        self
+    }
+
+    /// Prefers the recording if the URL points at both a recording and a list,
+    /// see [`CoreClient::extract_url_preferring`] to get the list
     pub async fn extract_url(
         &self,
         url: &Url,
         wanted: &Extractable,
     ) -> Result<Option<AnyExtraction>> {
-        for extractor in &self.extractors {
-            if extractor.match_extractor(url) {
-                return extractor
-                    .extract_info(&self.context, url, wanted)
-                    .await
-                    .map(Option::Some);
+        self.extract_url_preferring(url, wanted, ExtractorPreference::default())
+            .await
+    }
+
+    pub async fn extract_url_preferring(
+        &self,
+        url: &Url,
+        wanted: &Extractable,
+        preference: ExtractorPreference,
+    ) -> Result<Option<AnyExtraction>> {
+        let matching: Vec<&&AnyExtractor> = self
+            .extractors
+            .iter()
+            .filter(|e| e.match_extractor(url))
+            .collect();
+        let extractor = matching
+            .iter()
+            .find(|e| match (e, preference) {
+                (AnyExtractor::List(_), ExtractorPreference::List)
+                | (AnyExtractor::Recording(_), ExtractorPreference::Recording) => true,
+                _ => false,
+            })
+            .or(matching.first());
+        match extractor {
+            Some(extractor) => extractor
+                .extract_info(&self.context, url, wanted)
+                .await
+                .map(Option::Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Extracts the recording, waiting until it is no longer upcoming
+    /// (a scheduled live stream or premiere). Checks again at the scheduled start,
+    /// then every `poll_interval` until it starts. Once it's known to be upcoming,
+    /// the failed checks are logged and retried, up to `max_failed_checks` in a row
+    pub async fn wait_for_stream(
+        &self,
+        url: &Url,
+        wanted: &Extractable,
+        options: &WaitForStream,
+    ) -> Result<Extraction> {
+        let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+        // the first check failing is not a hiccup (private, deleted, blocked), so not retried
+        let mut failed_checks = None;
+        loop {
+            let (mut delay, error) = match self
+                .extract_url_preferring(url, wanted, ExtractorPreference::Recording)
+                .await
+            {
+                Ok(Some(AnyExtraction::Recording(extraction))) => {
+                    if extraction.metadata.live_status != Some(LiveStatus::IsUpcoming) {
+                        return Ok(extraction);
+                    }
+                    failed_checks = Some(0);
+                    let delay = next_poll_delay(
+                        &extraction.metadata,
+                        DateTime::<Utc>::from(SystemTime::now()),
+                        options.poll_interval,
+                    );
+                    (delay, None)
+                }
+                Ok(Some(AnyExtraction::List(_))) => bail!("not a recording: {}", url),
+                Ok(None) => bail!("no extractor matching {}", url),
+                // one failed check (network, rate limiting) shouldn't end a wait of hours
+                Err(e) => match failed_checks.as_mut() {
+                    Some(failed) if *failed + 1 < options.max_failed_checks => {
+                        *failed += 1;
+                        log::warn!("checking {} failed, retrying: {:?}", url, e);
+                        (options.poll_interval, Some(e))
+                    }
+                    _ => return Err(e),
+                },
+            };
+            if let Some(deadline) = deadline {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    let message = format!("{} has not started in time", url);
+                    return Err(match error {
+                        Some(e) => e.context(message),
+                        None => anyhow!(message),
+                    });
+                }
+                // one last check at the deadline
+                delay = delay.min(remaining);
             }
+            tokio::time::sleep(delay).await;
         }
-        Ok(None)
     }
 
     pub async fn pick_formats(
@@ -74,6 +220,94 @@ impl<'a> CoreClient<'a> {
         )
     }
 
+    /// What [`Self::pick_formats`] would pick and why, without resolving it for the download
+    pub async fn explain_formats(
+        &self,
+        selector: &str,
+        extraction: &Extraction,
+    ) -> Result<PickExplanation> {
+        let explanation = self
+            .format_picker
+            .explain_formats(selector, extraction)
+            .await?;
+        // the same checks as for the download
+        DownloadList::from(&explanation.selection, extraction)?;
+        Ok(explanation)
+    }
+
+    /// Fetches the next pages of the list's entries, following the continuations
+    /// until there are no more, or there are `max_entries` (or a few more, the pages are kept whole).
+    /// The endless lists (like YouTube mixes) are only followed with `max_entries` set.
+    /// `url` is the one the list was extracted from
+    pub async fn extract_more_entries(
+        &self,
+        url: &Url,
+        list: &mut ListExtraction,
+        max_entries: Option<usize>,
+    ) -> Result<()> {
+        if list.is_endless && max_entries.is_none() {
+            return Ok(());
+        }
+        let extractor = self
+            .extractors
+            .iter()
+            .find_map(|e| match e {
+                AnyExtractor::List(le) if le.match_extractor(url) => Some(le),
+                _ => None,
+            })
+            .ok_or_else(|| anyhow!("no list extractor matching {}", url))?;
+        let entries = match &mut list.entries {
+            Some(Ok(entries)) => entries,
+            _ => return Ok(()),
+        };
+        while let Some(continuation) = &list.continuation {
+            if max_entries.is_some_and(|max| entries.len() >= max) {
+                break;
+            }
+            let next = extractor
+                .extract_list_continuation(&self.context, &list.id, continuation)
+                .await?;
+            let more = match next.entries {
+                Some(Ok(more)) => more,
+                Some(Err(error)) => bail!("couldn't get more entries of {}: {}", list.id, error),
+                None => vec![],
+            };
+            list.continuation = next.continuation;
+            // not to loop forever on the empty pages
+            if more.is_empty() {
+                break;
+            }
+            entries.extend(more);
+        }
+        Ok(())
+    }
+
+    /// The list entries to process, picked by their metadata with `filter`
+    /// (in the format picker's language, an empty one keeps every entry).
+    /// Nothing gets extracted for the entries before. Only the entries fetched so far
+    /// are picked from, see [`Self::extract_more_entries`] for the next pages
+    pub async fn pick_entries<'b>(
+        &self,
+        filter: &str,
+        list: &'b ListExtraction,
+    ) -> Result<Vec<&'b AnyExtraction>> {
+        let entries = match &list.entries {
+            Some(Ok(entries)) => entries,
+            Some(Err(error)) => bail!("couldn't get the entries of {}: {}", list.id, error),
+            None => return Ok(vec![]),
+        };
+        self.format_picker
+            .pick_entries(filter, list)
+            .await?
+            .into_iter()
+            .map(|index| {
+                entries
+                    .get(index)
+                    .ok_or_else(|| anyhow!("no entry with the index {} in {}", index, list.id))
+            })
+            .collect()
+    }
+
     pub async fn download(&self, url: &Url, wanted: &Extractable, selector: &str) -> Result<()> {
         let extraction = self
             .extract_url(url, wanted)
@@ -111,3 +345,116 @@ impl<'a> CoreClient<'a> {
             .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use reytan_extractor_api::anyhow::{bail, Result};
+    use reytan_extractor_api::chrono::TimeZone;
+    use reytan_extractor_api::url::Url;
+    use reytan_extractor_api::{
+        async_trait, AnyExtractor, Extractable, Extraction, ExtractionContext, LiveStatus,
+        MediaMetadata, RecordingExtractor, URLMatcher, Utc,
+    };
+
+    use super::{next_poll_delay, CoreClient, WaitForStream};
+
+    /// Gives the live statuses in order, `None` failing the extraction
+    struct ScriptedRE(Arc<Mutex<Vec<Option<LiveStatus>>>>);
+
+    impl URLMatcher for ScriptedRE {
+        fn match_extractor(&self, _url: &Url) -> bool {
+            true
+        }
+    }
+
+    #[async_trait]
+    impl RecordingExtractor for ScriptedRE {
+        async fn extract_recording(
+            &self,
+            _ctx: &ExtractionContext,
+            _url: &Url,
+            _wanted: &Extractable,
+        ) -> Result<Extraction> {
+            match self.0.lock().unwrap().remove(0) {
+                Some(live_status) => Ok(Extraction {
+                    metadata: MediaMetadata {
+                        live_status: Some(live_status),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }),
+                None => bail!("connection reset"),
+            }
+        }
+    }
+
+    /// Whether the wait succeeded, and how many checks were left unused
+    async fn wait_for_stream(script: Vec<Option<LiveStatus>>) -> (bool, usize) {
+        let script = Arc::new(Mutex::new(script));
+        let extractor = AnyExtractor::Recording(Box::new(ScriptedRE(script.clone())));
+        let client = CoreClient {
+            extractors: vec![&extractor],
+            ..CoreClient::new()
+        };
+        let options = WaitForStream {
+            poll_interval: Duration::ZERO,
+            ..Default::default()
+        };
+        let result = client
+            .wait_for_stream(
+                &Url::parse("https://example.com/stream").unwrap(),
+                &Extractable::default(),
+                &options,
+            )
+            .await;
+        let left = script.lock().unwrap().len();
+        (result.is_ok(), left)
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_stream() {
+        use LiveStatus::{IsLive, IsUpcoming};
+
+        assert_eq!(
+            wait_for_stream(vec![
+                Some(IsUpcoming),
+                None,
+                Some(IsUpcoming),
+                None,
+                Some(IsLive)
+            ])
+            .await,
+            (true, 0)
+        );
+        // private, deleted, etc. - not waiting for it
+        assert_eq!(wait_for_stream(vec![None, Some(IsLive)]).await, (false, 1));
+        // failing for good after it was upcoming
+        let mut script = vec![Some(IsUpcoming)];
+        script.extend(vec![None; 5]);
+        script.push(Some(IsLive));
+        assert_eq!(wait_for_stream(script).await, (false, 1));
+    }
+
+    #[test]
+    fn test_next_poll_delay() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 10, 12, 0, 0).unwrap();
+        let interval = Duration::from_secs(60);
+        let mut metadata = MediaMetadata::default();
+        assert_eq!(next_poll_delay(&metadata, now, interval), interval);
+
+        metadata.scheduled_start_time = Some(Utc.with_ymd_and_hms(2023, 3, 10, 16, 0, 0).unwrap());
+        assert_eq!(
+            next_poll_delay(&metadata, now, interval),
+            Duration::from_secs(4 * 60 * 60)
+        );
+
+        // starting soon, or late already
+        metadata.scheduled_start_time = Some(Utc.with_ymd_and_hms(2023, 3, 10, 12, 0, 30).unwrap());
+        assert_eq!(next_poll_delay(&metadata, now, interval), interval);
+        metadata.scheduled_start_time = Some(Utc.with_ymd_and_hms(2023, 3, 10, 11, 0, 0).unwrap());
+        assert_eq!(next_poll_delay(&metadata, now, interval), interval);
+    }
+}
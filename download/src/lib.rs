@@ -1,19 +1,79 @@
 use reytan_download_http::HTTPDownloader;
-use reytan_download_types::anyhow::Result;
-use reytan_download_types::{DownloadList, ExtractionContext, FormatSelection, MediaFormatURL};
-use std::path::Path;
+use reytan_download_types::anyhow::{anyhow, Error, Result};
+use reytan_download_types::ratmom::Request;
+use reytan_download_types::{
+    DownloadList, ExtractionContext, FormatProtocol, FormatSelection, MediaFormatURL, SubtitleExt,
+    SubtitlePointerURL,
+};
+use std::path::{Path, PathBuf};
 
 pub struct Downloader {
     http: HTTPDownloader,
+    /// Format to save the subtitles in, `None` keeps the one they are served in
+    subtitle_ext: Option<SubtitleExt>,
+}
+
+/// `{stem}.{lang}.{ext}` next to the media, with `.auto` before the extension
+/// for the machine-generated and machine-translated ones (not to overwrite the human-made ones)
+fn subtitle_path(
+    output: &Path,
+    subtitle: &SubtitlePointerURL,
+    ext: &SubtitleExt,
+) -> Result<PathBuf> {
+    let details = &subtitle.details;
+    let is_auto =
+        details.is_machine_generated == Some(true) || details.is_machine_translated == Some(true);
+    let mut file_name = output
+        .file_stem()
+        .ok_or_else(|| anyhow!("no file name in the output path {:?}", output))?
+        .to_os_string();
+    file_name.push(format!(
+        ".{}{}.{}",
+        details.lang,
+        if is_auto { ".auto" } else { "" },
+        reytan_subtitles::file_extension(ext),
+    ));
+    Ok(output.with_file_name(file_name))
+}
+
+fn unsupported_protocol(format: &MediaFormatURL) -> Error {
+    anyhow!(
+        "unsupported protocol: {:?} (only the HTTP formats can be downloaded for now)",
+        format.protocol()
+    )
+}
+
+/// Fails on the first format that can't be downloaded, not to leave the others half-done
+fn check_protocols(download_list: &DownloadList) -> Result<()> {
+    for format_sel in &download_list.formats {
+        match format_sel {
+            &FormatSelection::Full(format)
+            | &FormatSelection::ExtractVideo(format)
+            | &FormatSelection::ExtractAudio(format) => {
+                if format.url.protocol() != FormatProtocol::HTTP {
+                    return Err(unsupported_protocol(&format.url)
+                        .context(format!("format {}", format.details.id)));
+                }
+            }
+        }
+    }
+    Ok(())
 }
 
 impl Downloader {
     pub fn new() -> Self {
         Self {
             http: HTTPDownloader::new(),
+            subtitle_ext: None,
         }
     }
 
+    /// Converts the downloaded subtitles to the format, if they can be read
+    pub fn with_subtitle_format(mut self, ext: SubtitleExt) -> Self {
+        self.subtitle_ext = Some(ext);
+        self
+    }
+
     pub async fn download_from_list<'a, P>(
         &self,
         ctx: &ExtractionContext,
@@ -23,6 +83,7 @@ impl Downloader {
     where
         P: AsRef<Path>,
     {
+        check_protocols(download_list)?;
         let output = output_.as_ref();
         let filenames_and_formats =
             download_list
@@ -53,6 +114,21 @@ impl Downloader {
             }
         }
 
+        for subtitle in &download_list.subtitles {
+            // the formats that can't be read are saved as they are
+            let ext = match &self.subtitle_ext {
+                Some(ext) if reytan_subtitles::can_parse(&subtitle.details.ext) => ext,
+                _ => &subtitle.details.ext,
+            };
+            self.download_subtitle(
+                ctx,
+                subtitle,
+                Some(ext),
+                subtitle_path(output, subtitle, ext)?,
+            )
+            .await?;
+        }
+
         Ok(())
     }
 
@@ -70,7 +146,128 @@ impl Downloader {
                 self.http.download_format(ctx, url, options, output).await?;
                 Ok(())
             }
-            _ => todo!(),
+            MediaFormatURL::HLS(..) | MediaFormatURL::DASH(..) => Err(unsupported_protocol(format)),
         }
     }
+
+    /// Saves the subtitles, converting them if `ext` is not the format they are served in
+    pub async fn download_subtitle<P>(
+        &self,
+        ctx: &ExtractionContext,
+        subtitle: &SubtitlePointerURL,
+        ext: Option<&SubtitleExt>,
+        output: P,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let body = ctx
+            .get_body("subtitles", Request::get(subtitle.url.as_str()).body(())?)
+            .await?;
+        let body = match ext {
+            Some(ext) if ext != &subtitle.details.ext => {
+                reytan_subtitles::convert(&body, &subtitle.details.ext, ext)?
+            }
+            _ => body,
+        };
+        std::fs::write(output, body)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use reytan_download_types::{
+        DASHDownloadOptions, DownloadList, FormatBreed, FormatProtocol, FormatSelection,
+        HTTPDownloadOptions, MediaFormatDetails, MediaFormatEstablished, MediaFormatURL,
+        SubtitleDetails, SubtitleExt, SubtitlePointerURL, Url,
+    };
+
+    use super::{check_protocols, subtitle_path};
+
+    #[test]
+    fn test_subtitle_path() {
+        let mut subtitle = SubtitlePointerURL {
+            details: SubtitleDetails {
+                lang: "en".to_string(),
+                is_original_lang: Some(true),
+                is_machine_generated: Some(false),
+                is_machine_translated: None,
+                ext: SubtitleExt::NonStandard("srv3".to_string()),
+            },
+            url: Url::parse("https://example.com/subtitles").unwrap(),
+        };
+        let output = Path::new("/tmp/dQw4w9WgXcQ.mp4");
+        assert_eq!(
+            subtitle_path(output, &subtitle, &SubtitleExt::SRT).unwrap(),
+            PathBuf::from("/tmp/dQw4w9WgXcQ.en.srt")
+        );
+        subtitle.details.is_machine_generated = Some(true);
+        assert_eq!(
+            subtitle_path(output, &subtitle, &subtitle.details.ext).unwrap(),
+            PathBuf::from("/tmp/dQw4w9WgXcQ.en.auto.srv3")
+        );
+        assert!(subtitle_path(Path::new("/"), &subtitle, &SubtitleExt::SRT).is_err());
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+
+            let output = Path::new(OsStr::from_bytes(b"/tmp/\xff.mp4"));
+            assert_eq!(
+                subtitle_path(output, &subtitle, &SubtitleExt::SRT).unwrap(),
+                Path::new(OsStr::from_bytes(b"/tmp/\xff.en.auto.srt"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_protocols() {
+        let format = |id: &str, url: MediaFormatURL| MediaFormatEstablished {
+            details: MediaFormatDetails {
+                id: id.to_string(),
+                breed: FormatBreed::Video,
+                video_details: None,
+                audio_details: None,
+                source: None,
+                bitrate: None,
+                protocol: url.protocol(),
+            },
+            url,
+        };
+        let http = format(
+            "137",
+            MediaFormatURL::HTTP(
+                Url::parse("https://example.com/video").unwrap(),
+                HTTPDownloadOptions::default(),
+            ),
+        );
+        let dash = format(
+            "137-dash",
+            MediaFormatURL::DASH(
+                Url::parse("https://example.com/manifest.mpd").unwrap(),
+                DASHDownloadOptions::default(),
+            ),
+        );
+        assert_eq!(dash.details.protocol, FormatProtocol::DASH);
+        assert!(check_protocols(&DownloadList {
+            formats: vec![FormatSelection::Full(&http)],
+            subtitles: vec![],
+        })
+        .is_ok());
+        let error = check_protocols(&DownloadList {
+            formats: vec![
+                FormatSelection::ExtractVideo(&http),
+                FormatSelection::ExtractAudio(&dash),
+            ],
+            subtitles: vec![],
+        })
+        .unwrap_err();
+        assert_eq!(
+            format!("{:#}", error),
+            "format 137-dash: unsupported protocol: DASH (only the HTTP formats can be downloaded for now)"
+        );
+    }
 }
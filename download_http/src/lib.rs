@@ -63,6 +63,10 @@ impl HTTPDownloader {
             cmd.arg("--header");
             cmd.arg(format!("user-agent: {}", ua));
         }
+        for (name, value) in &options.connection.headers {
+            cmd.arg("--header");
+            cmd.arg(format!("{}: {}", name, value));
+        }
 
         cmd.arg("--");
         cmd.arg(url.as_str());
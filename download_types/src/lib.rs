@@ -1,6 +1,7 @@
 pub use reytan_extractor_api::{
-    anyhow, ratmom, uri, DASHDownloadOptions, ExtractionContext, HLSDownloadOptions,
-    HTTPConnectionOptions, HTTPDownloadOptions, HTTPImpersonationTarget, MediaFormatEstablished,
-    MediaFormatURL, Url,
+    anyhow, ratmom, uri, DASHDownloadOptions, ExtractionContext, FormatBreed, FormatProtocol,
+    HLSDownloadOptions, HTTPConnectionOptions, HTTPDownloadOptions, HTTPImpersonationTarget,
+    MediaFormatDetails, MediaFormatEstablished, MediaFormatURL, SubtitleDetails, SubtitleExt,
+    SubtitlePointerURL, Url,
 };
 pub use reytan_format_picker_api::{DownloadList, FormatSelection};
@@ -0,0 +1,97 @@
+use anyhow::Result;
+
+/// A few values kept for reuse, the least recently used one is dropped over the limit.
+/// For the values that are expensive to set up and can't be sent between threads
+/// (like JS engines), kept in a `thread_local!`
+pub struct Lru<K, V> {
+    limit: usize,
+    /// The most recently used last
+    entries: Vec<(K, V)>,
+}
+
+impl<K: PartialEq + Clone, V> Lru<K, V> {
+    /// Keeps at least one value, even with a `limit` of 0
+    pub const fn new(limit: usize) -> Self {
+        Lru {
+            limit: if limit == 0 { 1 } else { limit },
+            entries: Vec::new(),
+        }
+    }
+
+    /// Runs `f` with the value for `key`, set up with `init` if not kept yet
+    pub fn with<T>(
+        &mut self,
+        key: &K,
+        init: impl FnOnce() -> Result<V>,
+        f: impl FnOnce(&mut V) -> Result<T>,
+    ) -> Result<T> {
+        let entry = match self.entries.iter().position(|(k, _)| k == key) {
+            Some(position) => self.entries.remove(position),
+            None => (key.clone(), init()?),
+        };
+        if self.entries.len() >= self.limit {
+            self.entries.remove(0);
+        }
+        self.entries.push(entry);
+        let (_, value) = self.entries.last_mut().unwrap();
+        f(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Lru;
+
+    #[test]
+    fn test_lru() {
+        let mut lru = Lru::new(2);
+        let mut inits = vec![];
+        let mut get = |lru: &mut Lru<&str, String>, key: &'static str| {
+            lru.with(
+                &key,
+                || {
+                    inits.push(key);
+                    Ok(key.to_uppercase())
+                },
+                |value| Ok(value.clone()),
+            )
+            .unwrap()
+        };
+        assert_eq!(get(&mut lru, "a"), "A");
+        assert_eq!(get(&mut lru, "b"), "B");
+        assert_eq!(get(&mut lru, "a"), "A");
+        // drops "b", used less recently than "a"
+        assert_eq!(get(&mut lru, "c"), "C");
+        assert_eq!(get(&mut lru, "a"), "A");
+        assert_eq!(get(&mut lru, "b"), "B");
+        assert_eq!(lru.len(), 2);
+        assert_eq!(inits, vec!["a", "b", "c", "b"]);
+
+        // not kept if the setup fails
+        assert!(lru
+            .with(&"d", || anyhow::bail!("failed"), |value| Ok(value.clone()))
+            .is_err());
+        assert_eq!(lru.len(), 2);
+    }
+
+    #[test]
+    fn test_lru_zero_limit() {
+        let mut lru = Lru::new(0);
+        for key in ["a", "b", "a"] {
+            assert_eq!(
+                lru.with(&key, || Ok(key.to_uppercase()), |value| Ok(value.clone()))
+                    .unwrap(),
+                key.to_uppercase()
+            );
+            assert_eq!(lru.len(), 1);
+        }
+    }
+}
@@ -1,3 +1,4 @@
 pub mod api;
 pub mod local;
+pub mod lru;
 pub mod stub;
@@ -9,12 +9,15 @@ use crate::cache::api::{CacheAPI, CacheImplementation, MapAPI};
 use crate::cache::local::LocalCache;
 #[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
 use crate::cache::stub::StubCache;
+use crate::ExtractorArgs;
 
 #[derive(Clone)]
 pub struct ExtractionContext {
     pub http: HttpClient,
     pub locales: Vec<String>,
     pub cache: CacheAPI,
+    /// Extractor-specific options set by the user
+    pub extractor_args: ExtractorArgs,
 }
 
 impl ExtractionContext {
@@ -37,6 +40,7 @@ impl ExtractionContext {
             cache: CacheAPI::new(CacheImplementation::Stub(StubCache::new())),
             #[cfg(any(target_os = "linux", target_os = "windows", target_os = "macos"))]
             cache: CacheAPI::new(CacheImplementation::Local(LocalCache::new())),
+            extractor_args: ExtractorArgs::default(),
         })
     }
 
@@ -45,6 +49,7 @@ impl ExtractionContext {
             http: build_http(&locales)?,
             locales,
             cache: CacheAPI::new(CacheImplementation::Local(LocalCache::new())),
+            extractor_args: ExtractorArgs::default(),
         })
     }
 
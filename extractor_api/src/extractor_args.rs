@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use anyhow::{anyhow, bail, Error, Result};
+
+/// Extractor-specific options set by the user, in the form of
+/// `extractor:key=value1,value2;key2=value` (e.g. `youtube:player_client=android,web`).
+/// Meant for working around service-specific issues, without having a dedicated API for everything.
+#[derive(Default, PartialEq, Clone, Debug)]
+pub struct ExtractorArgs {
+    args: HashMap<String, HashMap<String, Vec<String>>>,
+}
+
+impl ExtractorArgs {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Values of the `key` option for the `extractor`, if set
+    pub fn get(&self, extractor: &str, key: &str) -> Option<&[String]> {
+        self.args
+            .get(extractor)
+            .and_then(|ea| ea.get(key))
+            .map(Vec::as_slice)
+    }
+
+    /// Gets a yes/no option (`true`/`false`, `1`/`0`, `yes`/`no`)
+    pub fn get_bool(&self, extractor: &str, key: &str) -> Result<Option<bool>> {
+        match self.get(extractor, key) {
+            None => Ok(None),
+            Some([value]) => match value.as_str() {
+                "true" | "1" | "yes" => Ok(Some(true)),
+                "false" | "0" | "no" => Ok(Some(false)),
+                _ => bail!("{}:{} is not a yes/no value: {}", extractor, key, value),
+            },
+            Some(values) => bail!(
+                "{}:{} expects a single value, got {}",
+                extractor,
+                key,
+                values.len()
+            ),
+        }
+    }
+
+    pub fn set(&mut self, extractor: &str, key: &str, values: Vec<String>) {
+        self.args
+            .entry(extractor.to_string())
+            .or_default()
+            .insert(key.to_string(), values);
+    }
+
+    /// Parses `extractor:key=value1,value2;key2=value`, adding the options to the existing ones
+    /// (the repeated keys are replaced)
+    pub fn parse_and_add(&mut self, arg: &str) -> Result<()> {
+        let (extractor, options) = arg
+            .split_once(':')
+            .ok_or_else(|| anyhow!("no extractor name in the extractor args: {}", arg))?;
+        let extractor = extractor.trim().to_lowercase();
+        if extractor.is_empty() {
+            bail!("no extractor name in the extractor args: {}", arg);
+        }
+        for option in options.split(';').filter(|o| !o.trim().is_empty()) {
+            let (key, values) = option.split_once('=').unwrap_or((option, ""));
+            let key = key.trim().to_lowercase();
+            if key.is_empty() {
+                bail!("no option name in the extractor args: {}", arg);
+            }
+            let values = values
+                .split(',')
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+                .collect();
+            self.set(&extractor, &key, values);
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ExtractorArgs {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut args = ExtractorArgs::new();
+        args.parse_and_add(s)?;
+        Ok(args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExtractorArgs;
+
+    #[test]
+    fn test_parse_extractor_args() {
+        let mut args: ExtractorArgs = "youtube:player_client=android,web;merge_formats=no"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            args.get("youtube", "player_client"),
+            Some(&["android".to_string(), "web".to_string()][..])
+        );
+        assert_eq!(
+            args.get_bool("youtube", "merge_formats").unwrap(),
+            Some(false)
+        );
+        assert_eq!(args.get("youtube", "player_skip_client"), None);
+        assert_eq!(args.get("soundcloud", "player_client"), None);
+
+        args.parse_and_add("YouTube:player_client=ios;player_skip_client=web")
+            .unwrap();
+        assert_eq!(
+            args.get("youtube", "player_client"),
+            Some(&["ios".to_string()][..])
+        );
+        assert_eq!(
+            args.get("youtube", "player_skip_client"),
+            Some(&["web".to_string()][..])
+        );
+        assert_eq!(
+            args.get_bool("youtube", "merge_formats").unwrap(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_parse_extractor_args_invalid() {
+        assert!("player_client=android".parse::<ExtractorArgs>().is_err());
+        assert!(":player_client=android".parse::<ExtractorArgs>().is_err());
+        assert!("youtube:=android".parse::<ExtractorArgs>().is_err());
+        let args: ExtractorArgs = "youtube:merge_formats=maybe".parse().unwrap();
+        assert!(args.get_bool("youtube", "merge_formats").is_err());
+    }
+}
@@ -2,10 +2,13 @@
 extern crate smart_default;
 
 mod context;
+mod extractor_args;
 
 pub use context::{build_http, ExtractionContext};
+pub use extractor_args::ExtractorArgs;
 
 pub mod cache;
+pub mod parse;
 
 pub use anyhow;
 pub use async_trait::async_trait;
@@ -37,6 +40,13 @@ pub trait URLMatcher {
 
 #[async_trait]
 pub trait RecordingExtractor: URLMatcher + Sync + Send {
+    /// Extracts a single recording from the service.
+    ///
+    /// Only the aspects requested in `wanted` should be extracted - anything at
+    /// [`ExtractLevel::None`] must not cause additional requests or processing,
+    /// and is left empty in the returned [`Extraction`].
+    /// Extractors are allowed to return more than asked for, if it comes at no additional cost
+    /// (e.g. metadata that is a part of the same response as playback).
     async fn extract_recording(
         &self,
         ctx: &ExtractionContext,
@@ -51,17 +61,37 @@ pub trait RecordingExtractor: URLMatcher + Sync + Send {
 /// * rate limiting
 /// * region locks
 /// * age gate
-#[derive(Default)]
+///
+/// Every aspect defaults to [`ExtractLevel::None`], so set only what you need.
+/// Comments and related items are not extracted by any of the current extractors yet.
+#[derive(Default, Clone, Debug)]
 pub struct Extractable {
+    /// Title, description, dates, counters, etc.
     pub metadata: ExtractLevel,
+    /// Media formats (`Extraction.established_formats`)
     pub playback: ExtractLevel,
-}
-
-#[derive(Serialize, SmartDefault, PartialEq, Debug)]
+    /// Subtitle tracks (`Extraction.established_subtitles`).
+    /// `Basic` asks for the formats commonly supported by players,
+    /// `Extended` for every format provided by the service.
+    pub subtitles: ExtractLevel,
+    /// Thumbnails and other cover images (`MediaMetadata.thumbnails`)
+    pub thumbnails: ExtractLevel,
+    /// Chapters or other titled sections of the recording (`MediaMetadata.chapters`)
+    pub chapters: ExtractLevel,
+    /// Comments posted under the recording
+    pub comments: ExtractLevel,
+    /// Related items suggested by the service (recommendations, "up next")
+    pub related: ExtractLevel,
+}
+
+#[derive(Serialize, SmartDefault, PartialEq, Clone, Copy, Debug)]
 pub enum ExtractLevel {
+    /// Do not extract, do not make any requests for it
     #[default]
     None,
+    /// Extract what is available at a low cost
     Basic,
+    /// Extract everything, even if it takes additional requests or workarounds
     Extended,
 }
 
@@ -90,6 +120,9 @@ impl Extraction {
 #[derive(Serialize, Default, PartialEq, Clone, Debug)]
 pub struct MediaMetadata {
     pub id: String,
+    /// The page of the recording on the service.
+    /// Set on the list entries, for them to be extracted on their own
+    pub url: Option<Url>,
     pub title: String,
     pub description: Option<String>,
     pub duration: Option<Duration>,
@@ -99,6 +132,74 @@ pub struct MediaMetadata {
     pub created_time: Option<DateTime<Utc>>,
     pub published_time: Option<DateTime<Utc>>,
     pub modified_time: Option<DateTime<Utc>>,
+    /// The part of the recording that the URL was pointing at (e.g. YouTube clips)
+    pub clip: Option<ClipSection>,
+    /// Name of the channel/user that has uploaded the recording
+    pub uploader: Option<String>,
+    /// Service-specific ID of the uploader
+    pub uploader_id: Option<String>,
+    /// Position in the list, if extracted as a list entry (1-based)
+    pub list_index: Option<u32>,
+    pub thumbnails: Vec<Thumbnail>,
+    pub chapters: Vec<Chapter>,
+    /// Whether the recording can be played, as told by the service without a player request.
+    /// Mostly set on list entries, where deleted and private items are still listed.
+    pub availability: Option<Availability>,
+    /// Set if the recording is a music track
+    pub track: Option<TrackMetadata>,
+    /// When the upcoming live stream or premiere is planned to start
+    pub scheduled_start_time: Option<DateTime<Utc>>,
+    /// Set on the live streams (upcoming, ongoing or just ended)
+    pub live_details: Option<LiveDetails>,
+}
+
+#[derive(Serialize, Default, PartialEq, Clone, Debug)]
+pub struct LiveDetails {
+    /// Whether it's possible to seek back in the ongoing stream
+    pub dvr_enabled: bool,
+    pub latency_class: Option<LatencyClass>,
+}
+
+/// The delay between the streamer and the viewers, as set up by the streamer
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub enum LatencyClass {
+    Normal,
+    Low,
+    UltraLow,
+}
+
+#[derive(Serialize, Default, PartialEq, Clone, Debug)]
+pub struct TrackMetadata {
+    pub artists: Vec<String>,
+    pub album: Option<String>,
+    pub album_artists: Vec<String>,
+    /// Position on the album (1-based)
+    pub track_number: Option<u32>,
+    pub release_year: Option<i32>,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub enum Availability {
+    Playable,
+    /// Exists, but can't be played (region lock, members-only, etc.)
+    Unplayable,
+    /// Visible only to the uploader
+    Private,
+    /// Removed by the uploader or the service
+    Deleted,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct ClipSection {
+    pub start: Duration,
+    pub end: Duration,
+}
+
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct Chapter {
+    pub start: Duration,
+    pub end: Duration,
+    pub title: String,
 }
 
 #[derive(Serialize, PartialEq, Clone, Debug)]
@@ -109,6 +210,10 @@ pub enum LiveStatus {
     IsLive,
     /// A recording of a live stream
     WasLive,
+    /// A live stream or premiere that is scheduled, but has not started yet
+    IsUpcoming,
+    /// A live stream that has just ended, the recording is not processed yet
+    PostLive,
 }
 
 #[derive(Serialize, Debug)]
@@ -123,6 +228,23 @@ pub struct MediaFormatDetails {
     pub breed: FormatBreed,
     pub video_details: Option<VideoDetails>,
     pub audio_details: Option<AudioDetails>,
+    /// Where the format was obtained from, if the service has more than one way
+    /// (e.g. YouTube innertube client name)
+    pub source: Option<String>,
+    /// bits per second, the peak or the average one (whichever is given)
+    pub bitrate: Option<u64>,
+    /// How it's downloaded, the same as the [`MediaFormatURL`] variant
+    #[serde(default)]
+    pub protocol: FormatProtocol,
+}
+
+/// Download protocol
+#[derive(Serialize, Deserialize, SmartDefault, PartialEq, Clone, Copy, Debug)]
+pub enum FormatProtocol {
+    #[default]
+    HTTP,
+    HLS,
+    DASH,
 }
 
 #[derive(Serialize, PartialEq, Clone, Debug)]
@@ -132,6 +254,16 @@ pub enum MediaFormatURL {
     DASH(Url, DASHDownloadOptions),
 }
 
+impl MediaFormatURL {
+    pub fn protocol(&self) -> FormatProtocol {
+        match self {
+            MediaFormatURL::HTTP(..) => FormatProtocol::HTTP,
+            MediaFormatURL::HLS(..) => FormatProtocol::HLS,
+            MediaFormatURL::DASH(..) => FormatProtocol::DASH,
+        }
+    }
+}
+
 #[derive(Serialize, PartialEq, Clone, Debug, SmartDefault)]
 /// options common to any protocols made on top of HTTP
 pub struct HTTPConnectionOptions {
@@ -139,6 +271,8 @@ pub struct HTTPConnectionOptions {
     pub ff_target: Option<HTTPImpersonationTarget>,
     /// used if not running any impersonate or target not specified
     pub user_agent: Option<String>,
+    /// other headers required by the server, as (name, value)
+    pub headers: Vec<(String, String)>,
 }
 
 #[derive(Serialize, PartialEq, Clone, Debug)]
@@ -163,6 +297,8 @@ pub struct HLSDownloadOptions {
 #[derive(Serialize, PartialEq, Clone, Debug, SmartDefault)]
 pub struct DASHDownloadOptions {
     pub connection: HTTPConnectionOptions,
+    /// the manifest lists multiple formats, this is the one to download
+    pub representation_id: Option<String>,
 }
 
 #[async_trait]
@@ -183,11 +319,41 @@ pub enum FormatBreed {
 pub struct VideoDetails {
     pub width: Option<u32>,
     pub height: Option<u32>,
+    pub fps: Option<u32>,
+    /// as in the RFC 6381 `codecs` parameter (e.g. `avc1.640028`, `vp9`)
+    pub codec: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, SmartDefault, PartialEq, Clone, Debug)]
 pub struct AudioDetails {
     pub channels: Option<u8>,
+    /// as in the RFC 6381 `codecs` parameter (e.g. `mp4a.40.2`, `opus`)
+    pub codec: Option<String>,
+    /// language of the audio, as given by the service (e.g. `en`, `pt-BR`)
+    pub language: Option<String>,
+    /// set if the recording comes with more than one audio track (e.g. original and dubs)
+    pub track: Option<AudioTrack>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub struct AudioTrack {
+    /// the same for all the formats of the track
+    pub id: String,
+    /// human-readable name, as given by the service
+    pub name: Option<String>,
+    pub role: Option<AudioTrackRole>,
+    /// whether the service plays this track by default
+    pub is_default: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
+pub enum AudioTrackRole {
+    /// The audio the recording was made with
+    Original,
+    /// Translated voice-over, by a human or not
+    Dubbed,
+    /// Audio description for the visually impaired
+    Descriptive,
 }
 
 #[derive(SmartDefault, PartialEq, Clone, Debug)]
@@ -245,12 +411,40 @@ pub enum AnyExtraction {
     List(ListExtraction),
 }
 
+/// Image representing a list or a recording, such as a cover art, avatar or banner.
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct Thumbnail {
+    pub url: Url,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// The account (channel, user, artist) that has made the list.
+#[derive(Serialize, PartialEq, Clone, Debug)]
+pub struct ListOwner {
+    /// Service-specific ID of the account
+    pub id: Option<String>,
+    pub name: String,
+}
+
 /// What the list extractor spits out at you.
 #[derive(Default)]
 pub struct ListExtraction {
     pub id: String,
     pub breed: ListBreed,
     pub title: String,
+    pub description: Option<String>,
+    /// For channels, this is the channel itself
+    pub owner: Option<ListOwner>,
+    /// Total count of the items, as reported by the service
+    /// (may differ from the actually returned entries, e.g. with unavailable videos)
+    pub item_count: Option<u64>,
+    /// When the list (or the list of items) was last updated
+    pub modified_time: Option<DateTime<Utc>>,
+    /// Cover art, or the avatar for channels
+    pub thumbnails: Vec<Thumbnail>,
+    /// Channel banners
+    pub banners: Vec<Thumbnail>,
     pub is_endless: bool,
     pub entries: Option<Result<Vec<AnyExtraction>>>,
     /// Gets returned if there are more items (like a next page).
@@ -258,6 +452,41 @@ pub struct ListExtraction {
     pub continuation: Option<String>,
 }
 
+impl ListExtraction {
+    /// What's known about the entries without extracting them, in the same order
+    pub fn entry_details(&self) -> Vec<EntryDetails<'_>> {
+        let entries = match &self.entries {
+            Some(Ok(entries)) => entries,
+            _ => return vec![],
+        };
+        entries
+            .iter()
+            .map(|entry| match entry {
+                AnyExtraction::Recording(recording) => EntryDetails::Recording(&recording.metadata),
+                AnyExtraction::List(list) => EntryDetails::List {
+                    id: &list.id,
+                    title: &list.title,
+                    item_count: list.item_count,
+                },
+            })
+            .collect()
+    }
+}
+
+/// A list entry, for picking the ones to process.
+/// Serialized with `kind` being `recording` or `list`, as in the native picker's entry filters
+#[derive(Serialize, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum EntryDetails<'a> {
+    Recording(&'a MediaMetadata),
+    /// A nested list (album volume, channel playlist)
+    List {
+        id: &'a str,
+        title: &'a str,
+        item_count: Option<u64>,
+    },
+}
+
 /// What the list extractor spits out at you (again, if you want more)
 #[derive(Default)]
 pub struct ListContinuation {
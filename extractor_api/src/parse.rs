@@ -0,0 +1,353 @@
+//! Parsing the dates and durations, in the formats used by the services
+
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Months, NaiveDate, NaiveDateTime, TimeZone, Utc};
+
+/// Date-only formats, tried in order
+static DATE_FORMATS: &[&str] = &[
+    "%Y-%m-%d",
+    "%Y%m%d",
+    "%b %d, %Y",
+    "%B %d, %Y",
+    "%d %b %Y",
+    "%d %B %Y",
+];
+
+/// Parses a timestamp: RFC 3339/ISO 8601 (`2023-03-05T12:30:00-08:00`),
+/// or RFC 2822 (`Sun, 05 Mar 2023 20:30:00 GMT`).
+/// Timestamps without the time zone are assumed to be UTC
+pub fn parse_datetime(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_rfc2822(text))
+        .map(DateTime::<Utc>::from)
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+                .map(|dt| Utc.from_utc_datetime(&dt))
+        })
+}
+
+/// Parses a date (`2023-03-05`, `20230305`, `Mar 5, 2023`, `5 March 2023`) into its midnight UTC.
+/// Full timestamps are accepted too, see [parse_datetime]
+pub fn parse_date(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+        .map(|dt| Utc.from_utc_datetime(&dt))
+        .or_else(|| parse_datetime(text))
+}
+
+/// Current time, to parse the relative dates against
+pub fn now() -> DateTime<Utc> {
+    DateTime::<Utc>::from(SystemTime::now())
+}
+
+/// Parses the relative dates, like "3 weeks ago", "Streamed 2 hours ago", "today" or "yesterday"
+/// (English only), the first one found in the text. "today" and "yesterday" give the midnight
+pub fn parse_relative_date(text: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|w| {
+            w.trim_matches(|c: char| !c.is_alphanumeric())
+                .to_lowercase()
+        })
+        .collect();
+    let midnight = |date: NaiveDate| Some(Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0)?));
+    for (i, word) in words.iter().enumerate() {
+        match word.as_str() {
+            "today" => return midnight(now.date_naive()),
+            "yesterday" => return midnight(now.date_naive().pred_opt()?),
+            "ago" if i >= 2 => {
+                let count = match words[i - 2].as_str() {
+                    "a" | "an" => 1,
+                    n => match n.parse::<u32>() {
+                        Ok(n) => n,
+                        Err(_) => continue,
+                    },
+                };
+                // the counts from the page can go past the dates chrono can represent
+                let seconds = |unit: u64| {
+                    let ago = Duration::from_secs(u64::from(count).checked_mul(unit)?);
+                    now.checked_sub_signed(chrono::Duration::from_std(ago).ok()?)
+                };
+                let date = match words[i - 1].trim_end_matches('s') {
+                    "second" | "sec" => seconds(1),
+                    "minute" | "min" => seconds(60),
+                    "hour" => seconds(60 * 60),
+                    "day" => seconds(24 * 60 * 60),
+                    "week" => seconds(7 * 24 * 60 * 60),
+                    // not a fixed amount of days
+                    "month" => now.checked_sub_months(Months::new(count)),
+                    "year" => now.checked_sub_months(Months::new(count.checked_mul(12)?)),
+                    _ => continue,
+                };
+                return date;
+            }
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Parses the durations, as displayed (`4:13`, `1:02:03`) or ISO 8601 (`PT1H2M3S`)
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if let Some(iso) = text.strip_prefix('P') {
+        return parse_iso8601_duration(iso);
+    }
+    if text.is_empty() {
+        return None;
+    }
+    text.split(':')
+        .try_fold(0u64, |acc, part| {
+            acc.checked_mul(60)?.checked_add(part.parse::<u64>().ok()?)
+        })
+        .map(Duration::from_secs)
+}
+
+/// Parses the clock durations strictly, only `M:SS` or `H:MM:SS`,
+/// like the timestamps written in the descriptions (`4:13`, `1:02:03`)
+pub fn parse_clock_duration(text: &str) -> Option<Duration> {
+    let parts = text
+        .split(':')
+        .map(|p| match p.bytes().all(|b| b.is_ascii_digit()) {
+            true => p.parse::<u64>().ok(),
+            false => None,
+        })
+        .collect::<Option<Vec<_>>>()?;
+    if !(2..=3).contains(&parts.len()) || parts[1..].iter().any(|p| *p >= 60) {
+        return None;
+    }
+    parts
+        .iter()
+        .try_fold(0u64, |t, p| t.checked_mul(60)?.checked_add(*p))
+        .map(Duration::from_secs)
+}
+
+/// The part after `P`, only the days and time (years and months have no fixed length)
+fn parse_iso8601_duration(text: &str) -> Option<Duration> {
+    let (days, time) = text.split_once('T').unwrap_or((text, ""));
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    let date_units: &[(char, f64)] = &[('W', 7.0 * 86400.0), ('D', 86400.0)];
+    let time_units: &[(char, f64)] = &[('H', 3600.0), ('M', 60.0), ('S', 1.0)];
+    for (part, units) in [(days, date_units), (time, time_units)] {
+        for c in part.chars() {
+            if c.is_ascii_digit() || c == '.' {
+                number.push(c);
+            } else {
+                let (_, multiplier) = units.iter().find(|(unit, _)| *unit == c)?;
+                seconds += number.parse::<f64>().ok()? * multiplier;
+                number.clear();
+            }
+        }
+        if !number.is_empty() {
+            return None;
+        }
+    }
+    if days.is_empty() && time.is_empty() {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    use super::{
+        parse_clock_duration, parse_date, parse_datetime, parse_duration, parse_relative_date,
+    };
+
+    #[test]
+    fn test_parse_datetime() {
+        let expected = Utc.with_ymd_and_hms(2023, 3, 5, 20, 30, 0).single();
+        for text in [
+            "2023-03-05T20:30:00Z",
+            "2023-03-05T20:30:00+00:00",
+            "2023-03-05T12:30:00-08:00",
+            "2023-03-05T20:30:00.000Z",
+            "2023-03-05T20:30:00",
+            "2023-03-05 20:30:00",
+            // bandcamp
+            "05 Mar 2023 20:30:00 GMT",
+            "Sun, 05 Mar 2023 20:30:00 GMT",
+            "Sun, 05 Mar 2023 21:30:00 +0100",
+            " 2023-03-05T20:30:00Z\n",
+        ] {
+            assert_eq!(parse_datetime(text), expected, "{:?}", text);
+        }
+        assert_eq!(
+            parse_datetime("2023-03-05T20:30:00.250Z").map(|d| d.timestamp_subsec_millis()),
+            Some(250)
+        );
+        for text in [
+            "",
+            "2023-03-05",
+            "yesterday",
+            "2023-13-05T20:30:00Z",
+            "4:13",
+        ] {
+            assert_eq!(parse_datetime(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let expected = Utc.with_ymd_and_hms(2023, 3, 5, 0, 0, 0).single();
+        for text in [
+            "2023-03-05",
+            "20230305",
+            "Mar 5, 2023",
+            "March 5, 2023",
+            "5 Mar 2023",
+            "05 March 2023",
+            "2023-03-05T00:00:00Z",
+            "2023-03-04T16:00:00-08:00",
+        ] {
+            assert_eq!(parse_date(text), expected, "{:?}", text);
+        }
+        // time is kept if given
+        assert_eq!(
+            parse_date("2023-03-05T12:30:00Z"),
+            Utc.with_ymd_and_hms(2023, 3, 5, 12, 30, 0).single()
+        );
+        for text in ["", "2023-02-30", "Mar 5", "3 weeks ago", "1,234 views"] {
+            assert_eq!(parse_date(text), None, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_relative_date() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 31, 12, 0, 0).unwrap();
+        for (text, expected) in [
+            ("3 weeks ago", Utc.with_ymd_and_hms(2023, 3, 10, 12, 0, 0)),
+            ("1 week ago", Utc.with_ymd_and_hms(2023, 3, 24, 12, 0, 0)),
+            ("2 days ago", Utc.with_ymd_and_hms(2023, 3, 29, 12, 0, 0)),
+            (
+                "Streamed 5 hours ago",
+                Utc.with_ymd_and_hms(2023, 3, 31, 7, 0, 0),
+            ),
+            ("an hour ago", Utc.with_ymd_and_hms(2023, 3, 31, 11, 0, 0)),
+            (
+                "30 minutes ago",
+                Utc.with_ymd_and_hms(2023, 3, 31, 11, 30, 0),
+            ),
+            (
+                "45 seconds ago",
+                Utc.with_ymd_and_hms(2023, 3, 31, 11, 59, 15),
+            ),
+            // no 31st of February
+            ("1 month ago", Utc.with_ymd_and_hms(2023, 2, 28, 12, 0, 0)),
+            ("2 years ago", Utc.with_ymd_and_hms(2021, 3, 31, 12, 0, 0)),
+            (
+                "1,234 views • 3 years ago",
+                Utc.with_ymd_and_hms(2020, 3, 31, 12, 0, 0),
+            ),
+            (
+                "Premiered 3 weeks ago",
+                Utc.with_ymd_and_hms(2023, 3, 10, 12, 0, 0),
+            ),
+            ("Updated today", Utc.with_ymd_and_hms(2023, 3, 31, 0, 0, 0)),
+            (
+                "Updated yesterday",
+                Utc.with_ymd_and_hms(2023, 3, 30, 0, 0, 0),
+            ),
+        ] {
+            assert_eq!(
+                parse_relative_date(text, now),
+                expected.single(),
+                "{:?}",
+                text
+            );
+        }
+        for text in [
+            "",
+            "ago",
+            "1,234 views",
+            "3 fortnights ago",
+            "Mar 5, 2023",
+            // past the dates that can be represented
+            "99999999 days ago",
+            "4294967295 weeks ago",
+            "4294967295 years ago",
+        ] {
+            assert_eq!(parse_relative_date(text, now), None, "{:?}", text);
+        }
+        assert_eq!(
+            parse_relative_date("Updated today", now).map(|d| d.date_naive()),
+            NaiveDate::from_ymd_opt(2023, 3, 31)
+        );
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        for (text, expected) in [
+            ("4:13", Some(253)),
+            ("1:02:03", Some(3723)),
+            ("0:05", Some(5)),
+            ("42", Some(42)),
+            (" 4:13 ", Some(253)),
+            ("PT1H2M3S", Some(3723)),
+            ("PT4M13S", Some(253)),
+            ("P1DT1S", Some(86401)),
+            ("P1W", Some(604800)),
+            ("LIVE", None),
+            ("", None),
+            ("4:", None),
+            ("-4:13", None),
+            ("P", None),
+            ("PT", None),
+            ("PT1X", None),
+            ("P1Y", None),
+        ] {
+            assert_eq!(
+                parse_duration(text),
+                expected.map(Duration::from_secs),
+                "{:?}",
+                text
+            );
+        }
+        assert_eq!(parse_duration("PT1.5S"), Some(Duration::from_millis(1500)));
+        // too long, from the page
+        assert_eq!(parse_duration(&format!("{}99", "99:".repeat(20))), None);
+        assert_eq!(parse_duration("9999999999999999:00:00"), None);
+        assert_eq!(parse_duration(&format!("PT{}S", "9".repeat(400))), None);
+        assert_eq!(parse_duration(&format!("P{}W", "9".repeat(20))), None);
+    }
+
+    #[test]
+    fn test_parse_clock_duration() {
+        for (text, expected) in [
+            ("4:13", Some(253)),
+            ("0:00", Some(0)),
+            ("1:02:03", Some(3723)),
+            ("123:00", Some(7380)),
+            ("42", None),
+            ("1:60", None),
+            ("1:60:00", None),
+            ("1:2:3:4", None),
+            ("4:", None),
+            ("+4:13", None),
+            (" 4:13", None),
+            ("", None),
+            // too long, from the description
+            ("99999999999999999:59:59", None),
+        ] {
+            assert_eq!(
+                parse_clock_duration(text),
+                expected.map(Duration::from_secs),
+                "{:?}",
+                text
+            );
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use nipper::Document;
 use reytan_extractor_api::anyhow::Result;
+use reytan_extractor_api::parse::parse_datetime;
 use reytan_extractor_api::{
     async_trait, header, uri, AnyExtraction, Extraction, ExtractionContext, ListBreed,
-    ListContinuation, ListExtraction, ListExtractor, MediaMetadata, NewExtractor, Request,
-    URLMatcher, Url,
+    ListContinuation, ListExtraction, ListExtractor, ListOwner, MediaMetadata, NewExtractor,
+    Request, Thumbnail, URLMatcher, Url,
 };
 
 use super::common::{_is_bandcamp, _path_is};
@@ -60,6 +61,26 @@ impl ListExtractor for BandcampAlbumLE {
             id: tralbum.url,
             breed: ListBreed::Album,
             title: tralbum.current.title,
+            description: tralbum.current.about,
+            owner: tralbum.artist.map(|name| ListOwner {
+                id: tralbum.current.band_id.map(|id| id.to_string()),
+                name,
+            }),
+            item_count: Some(tralbum.trackinfo.len() as u64),
+            modified_time: tralbum.current.mod_date.as_deref().and_then(parse_datetime),
+            thumbnails: tralbum
+                .art_id
+                .into_iter()
+                // size 10 is the original upload
+                .flat_map(|art_id| {
+                    Url::parse(&format!("https://f4.bcbits.com/img/a{}_10.jpg", art_id))
+                })
+                .map(|url| Thumbnail {
+                    url,
+                    width: None,
+                    height: None,
+                })
+                .collect(),
             is_endless: false,
             entries: {
                 Some(Ok(tralbum
@@ -68,6 +89,7 @@ impl ListExtractor for BandcampAlbumLE {
                     .map(|ti| {
                         AnyExtraction::Recording(Extraction {
                             metadata: MediaMetadata {
+                                url: url.join(&ti.title_link).ok(),
                                 id: ti.title_link,
                                 title: ti.title,
                                 ..Default::default()
@@ -121,6 +143,9 @@ mod tests {
             .await
             .expect("extraction");
         assert_eq!(album.title, "Public Void");
+        assert_eq!(album.owner.unwrap().name, "Penelope Scott");
+        assert!(album.item_count.unwrap() > 0);
+        assert_eq!(album.thumbnails.len(), 1);
         assert_eq!(album.breed, ListBreed::Album);
         assert_eq!(album.continuation, None);
         assert_eq!(album.is_endless, false);
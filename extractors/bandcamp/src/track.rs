@@ -2,10 +2,11 @@ use std::time::Duration;
 
 use nipper::Document;
 use reytan_extractor_api::anyhow::Result;
+use reytan_extractor_api::parse::parse_datetime;
 use reytan_extractor_api::{
-    async_trait, chrono, header, uri, AudioDetails, Extractable, Extraction, ExtractionContext,
-    FormatBreed, HTTPDownloadOptions, MediaFormatDetails, MediaFormatEstablished, MediaFormatURL,
-    MediaMetadata, NewExtractor, RecordingExtractor, Request, URLMatcher, Url, Utc,
+    async_trait, header, uri, AudioDetails, Extractable, Extraction, ExtractionContext,
+    FormatBreed, FormatProtocol, HTTPDownloadOptions, MediaFormatDetails, MediaFormatEstablished,
+    MediaFormatURL, MediaMetadata, NewExtractor, RecordingExtractor, Request, URLMatcher, Url,
 };
 
 use super::common::{_is_bandcamp, _path_is};
@@ -66,30 +67,13 @@ impl RecordingExtractor for BandcampRE {
                 id: tralbum.url,
                 title: trackinfo.title.clone(),
                 duration: trackinfo.duration.map(Duration::from_secs_f64),
-                created_time: tralbum
-                    .current
-                    .new_date
-                    .as_deref()
-                    .map(chrono::DateTime::parse_from_rfc2822)
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
+                created_time: tralbum.current.new_date.as_deref().and_then(parse_datetime),
                 published_time: tralbum
                     .current
                     .publish_date
                     .as_deref()
-                    .map(chrono::DateTime::parse_from_rfc2822)
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
-                modified_time: tralbum
-                    .current
-                    .mod_date
-                    .as_deref()
-                    .map(chrono::DateTime::parse_from_rfc2822)
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
+                    .and_then(parse_datetime),
+                modified_time: tralbum.current.mod_date.as_deref().and_then(parse_datetime),
                 ..Default::default()
             },
 
@@ -97,19 +81,27 @@ impl RecordingExtractor for BandcampRE {
                 .file
                 .keys()
                 .into_iter()
-                .map(|quality| MediaFormatEstablished {
-                    url: MediaFormatURL::HTTP(
-                        Url::parse(&trackinfo.file.get(quality).unwrap().to_string()).unwrap(),
-                        HTTPDownloadOptions::default(),
-                    ),
-                    details: MediaFormatDetails {
-                        id: quality.to_string(),
-                        breed: FormatBreed::Audio,
-                        audio_details: Some(AudioDetails {
-                            ..Default::default()
-                        }),
-                        video_details: None,
-                    },
+                .map(|quality| {
+                    // `mp3-128`: the codec and kbps
+                    let (codec, kbps) = quality.split_once('-').unwrap_or((quality, ""));
+                    MediaFormatEstablished {
+                        url: MediaFormatURL::HTTP(
+                            Url::parse(&trackinfo.file.get(quality).unwrap().to_string()).unwrap(),
+                            HTTPDownloadOptions::default(),
+                        ),
+                        details: MediaFormatDetails {
+                            id: quality.to_string(),
+                            breed: FormatBreed::Audio,
+                            audio_details: Some(AudioDetails {
+                                codec: Some(codec.to_string()),
+                                ..Default::default()
+                            }),
+                            video_details: None,
+                            source: None,
+                            bitrate: kbps.parse::<u64>().ok().map(|kbps| kbps * 1000),
+                            protocol: FormatProtocol::HTTP,
+                        },
+                    }
                 })
                 .collect(),
             ..Default::default()
@@ -147,6 +139,7 @@ mod tests {
                 &Extractable {
                     metadata: ExtractLevel::Basic,
                     playback: ExtractLevel::Extended,
+                    ..Default::default()
                 },
             )
             .await
@@ -166,6 +159,7 @@ mod tests {
                 &Extractable {
                     metadata: ExtractLevel::Basic,
                     playback: ExtractLevel::Extended,
+                    ..Default::default()
                 },
             )
             .await
@@ -6,6 +6,10 @@ pub mod web_fragments {
         pub current: parts::Current,
         pub trackinfo: Vec<parts::Trackinfo>,
         pub url: String,
+        /// artist name, as displayed
+        pub artist: Option<String>,
+        /// cover art ID, resolves to `https://f4.bcbits.com/img/a{art_id}_{size}.jpg`
+        pub art_id: Option<u64>,
     }
 
     pub mod parts {
@@ -30,6 +34,8 @@ pub mod web_fragments {
             pub new_date: Option<String>,
             pub publish_date: Option<String>,
             pub mod_date: Option<String>,
+            pub about: Option<String>,
+            pub band_id: Option<u64>,
         }
     }
 }
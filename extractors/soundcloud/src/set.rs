@@ -1,10 +1,11 @@
 use itertools::intersperse;
 use qstring::QString;
 use reytan_extractor_api::anyhow::Result;
+use reytan_extractor_api::parse::parse_datetime;
 use reytan_extractor_api::url::Url;
 use reytan_extractor_api::{
     async_trait, AnyExtraction, Extraction, ExtractionContext, ListBreed, ListContinuation,
-    ListExtraction, ListExtractor, NewExtractor, URLMatcher,
+    ListExtraction, ListExtractor, ListOwner, NewExtractor, URLMatcher,
 };
 
 use crate::common::get_api_request;
@@ -44,7 +45,15 @@ impl ListExtractor for SoundcloudSetLE {
         Ok(ListExtraction {
             id: set.id.to_string(),
             breed: ListBreed::Album,
-            title: set.title,
+            title: set.title.clone(),
+            description: set.description.clone().filter(|d| !d.is_empty()),
+            owner: set.user.as_ref().map(|u| ListOwner {
+                id: Some(u.id.to_string()),
+                name: u.username.clone().unwrap_or_else(|| u.permalink.clone()),
+            }),
+            item_count: Some(set.track_count as u64),
+            modified_time: set.last_modified.as_deref().and_then(parse_datetime),
+            thumbnails: set.thumbnails(),
             is_endless: false,
             entries: Some(Ok(set
                 .tracks
@@ -63,6 +72,7 @@ impl ListExtractor for SoundcloudSetLE {
                 )
                 .collect(),
             ),
+            ..Default::default()
         })
     }
 
@@ -113,6 +123,9 @@ mod tests {
         assert_eq!(initial.id, "1459801735");
         assert_eq!(initial.breed, ListBreed::Album);
         assert_eq!(initial.is_endless, false);
+        assert!(initial.owner.is_some());
+        assert!(initial.item_count.unwrap() > 55);
+        assert!(initial.thumbnails.len() > 0);
         assert_eq!(initial.entries.as_ref().unwrap().as_ref().unwrap().len(), 5);
         assert!(initial.continuation.is_some());
 
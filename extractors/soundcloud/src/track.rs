@@ -119,6 +119,7 @@ mod tests {
         let recording = soundcloud.extract_recording(&ctx, &Url::parse("https://soundcloud.com/goophouse/nyancrimew-this-video-game-has?in=goophouse/sets/goop-house-volume-7").unwrap(), &Extractable {
             metadata: ExtractLevel::Extended,
             playback: ExtractLevel::Extended,
+            ..Default::default()
         }).await.unwrap();
         let metadata = recording.metadata;
         assert_eq!(metadata.id, "1294648321");
@@ -140,6 +141,7 @@ mod tests {
                 &Extractable {
                     metadata: ExtractLevel::Extended,
                     playback: ExtractLevel::Extended,
+                    ..Default::default()
                 },
             )
             .await
@@ -161,6 +163,7 @@ mod tests {
                 &Extractable {
                     metadata: ExtractLevel::Extended,
                     playback: ExtractLevel::Extended,
+                    ..Default::default()
                 },
             )
             .await
@@ -1,8 +1,9 @@
 use std::time::Duration;
 
+use reytan_extractor_api::parse::parse_datetime;
 use reytan_extractor_api::{
-    chrono, AudioDetails, Extraction, FormatBreed, HLSDownloadOptions, HTTPDownloadOptions,
-    LiveStatus, MediaFormatDetails, MediaFormatEstablished, MediaFormatURL, MediaMetadata, Utc,
+    AudioDetails, Extraction, FormatBreed, HLSDownloadOptions, HTTPDownloadOptions, LiveStatus,
+    MediaFormatDetails, MediaFormatEstablished, MediaFormatURL, MediaMetadata, Thumbnail, Url,
 };
 use serde::Deserialize;
 
@@ -33,25 +34,41 @@ pub struct Transcoding {
     pub preset: String,
 }
 
+impl TranscodingFormat {
+    /// `audio/ogg; codecs="opus"`, `audio/mpeg`
+    pub fn codec(&self) -> Option<String> {
+        match self.mime_type.split_once("codecs=") {
+            Some((_, codecs)) => Some(codecs.trim_matches('"').to_string()),
+            None if self.mime_type == "audio/mpeg" => Some("mp3".to_string()),
+            None => None,
+        }
+    }
+}
+
 impl From<Transcoding> for MediaFormatEstablished {
     fn from(t: Transcoding) -> Self {
+        let url = match t.format.protocol {
+            MediaProtocol::Progressive => {
+                MediaFormatURL::HTTP(t.url.parse().unwrap(), HTTPDownloadOptions::default())
+            }
+            MediaProtocol::Hls => {
+                MediaFormatURL::HLS(t.url.parse().unwrap(), HLSDownloadOptions::default())
+            }
+        };
         MediaFormatEstablished {
             details: MediaFormatDetails {
                 id: t.preset,
                 breed: FormatBreed::Audio,
                 video_details: None,
                 audio_details: Some(AudioDetails {
+                    codec: t.format.codec(),
                     ..Default::default()
                 }),
+                source: None,
+                bitrate: None,
+                protocol: url.protocol(),
             },
-            url: match t.format.protocol {
-                MediaProtocol::Progressive => {
-                    MediaFormatURL::HTTP(t.url.parse().unwrap(), HTTPDownloadOptions::default())
-                }
-                MediaProtocol::Hls => {
-                    MediaFormatURL::HLS(t.url.parse().unwrap(), HLSDownloadOptions::default())
-                }
-            },
+            url,
         }
     }
 }
@@ -85,27 +102,9 @@ impl From<Track> for Extraction {
                 duration: Some(Duration::from_millis(track.duration)),
                 view_count: Some(track.playback_count),
                 live_status: Some(LiveStatus::NotLive), // no live functionality
-                created_time: track
-                    .created_at
-                    .as_deref()
-                    .map(chrono::DateTime::parse_from_rfc3339)
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
-                published_time: track
-                    .release_date
-                    .as_deref()
-                    .map(chrono::DateTime::parse_from_rfc3339)
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
-                modified_time: track
-                    .last_modified
-                    .as_deref()
-                    .map(chrono::DateTime::parse_from_rfc3339)
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
+                created_time: track.created_at.as_deref().and_then(parse_datetime),
+                published_time: track.release_date.as_deref().and_then(parse_datetime),
+                modified_time: track.last_modified.as_deref().and_then(parse_datetime),
                 ..Default::default()
             },
             established_formats: track
@@ -155,5 +154,31 @@ pub struct Set {
     pub id: u64,
     pub track_count: usize,
     pub title: String,
+    pub description: Option<String>,
+    pub user: Option<User>,
+    pub last_modified: Option<String>,
+    /// 100x100 ("large"), other sizes are available by replacing the suffix
+    pub artwork_url: Option<String>,
     pub tracks: Vec<MaybeTrackInfo>,
 }
+
+impl Set {
+    pub fn thumbnails(&self) -> Vec<Thumbnail> {
+        let artwork_url = match &self.artwork_url {
+            Some(url) => url,
+            None => return vec![],
+        };
+        [("large", 100), ("t500x500", 500)]
+            .into_iter()
+            .flat_map(|(suffix, size)| {
+                Url::parse(&artwork_url.replace("-large.", &format!("-{}.", suffix)))
+                    .ok()
+                    .map(|url| Thumbnail {
+                        url,
+                        width: Some(size),
+                        height: Some(size),
+                    })
+            })
+            .collect()
+    }
+}
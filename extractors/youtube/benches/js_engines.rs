@@ -0,0 +1,96 @@
+//! Compares the JS engines on solving a video worth of signatures,
+//! with the [SigDefinition] already cached (as it is after the first video).
+//!
+//! Run with: `cargo bench -p reytan_extractor_youtube --features js_quickjs`
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use reytan_extractor_youtube::js::{self, JsEngineKind};
+use reytan_extractor_youtube::recording::{parse_js_functions, SigDefinition};
+use serde::Deserialize;
+
+// a typical video has around 20-40 formats, with the same n-parameter value
+const FORMATS_PER_VIDEO: usize = 30;
+const VIDEOS: u32 = 20;
+
+/// An entry of `fixtures/players/tests.json`, with the player script in `<player>.js`
+#[derive(Deserialize)]
+struct PlayerJsFixture {
+    player: String,
+    sig: Vec<(String, String)>,
+    n: Vec<(String, String)>,
+}
+
+fn solve_video(fixture: &PlayerJsFixture, call: &mut dyn FnMut(&str, &str)) {
+    for (i, (input, _)) in fixture
+        .sig
+        .iter()
+        .cycle()
+        .take(FORMATS_PER_VIDEO)
+        .enumerate()
+    {
+        call("sig", input);
+        call("ncode", &fixture.n[i % fixture.n.len()].0);
+    }
+}
+
+/// A new context for every video
+fn bench_fresh(
+    fixture: &PlayerJsFixture,
+    sig_definition: &SigDefinition,
+    kind: JsEngineKind,
+) -> Duration {
+    let start = Instant::now();
+    for _ in 0..VIDEOS {
+        let mut js_engine = sig_definition.js_engine(kind).unwrap();
+        solve_video(fixture, &mut |function, arg| {
+            js_engine.call(function, arg).unwrap();
+        });
+    }
+    start.elapsed() / VIDEOS
+}
+
+/// The solver cache, as in handling the signatures while extracting
+fn bench_cached(
+    fixture: &PlayerJsFixture,
+    sig_definition: &SigDefinition,
+    kind: JsEngineKind,
+) -> Duration {
+    let start = Instant::now();
+    for _ in 0..VIDEOS {
+        js::with_solver(
+            (&fixture.player, kind),
+            &[&sig_definition.sig_code, &sig_definition.ncode_code],
+            |solver| {
+                solve_video(fixture, &mut |function, arg| {
+                    solver.call(function, arg).unwrap();
+                });
+                Ok(())
+            },
+        )
+        .unwrap();
+    }
+    start.elapsed() / VIDEOS
+}
+
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/players");
+    let fixtures: Vec<PlayerJsFixture> =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("tests.json")).unwrap()).unwrap();
+
+    for fixture in fixtures {
+        let player_js =
+            std::fs::read_to_string(dir.join(format!("{}.js", fixture.player))).unwrap();
+        let sig_definition = parse_js_functions(&player_js).unwrap();
+        for kind in JsEngineKind::ALL {
+            println!(
+                "{} {}: {:?} per video, {:?} with the solver cache",
+                fixture.player,
+                kind.name(),
+                bench_fresh(&fixture, &sig_definition, *kind),
+                bench_cached(&fixture, &sig_definition, *kind),
+            );
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Stores a web player in `fixtures/players`, with the signatures and n-params as solved now,
+//! for the tests to catch the extraction breaking on it later. Needs the network.
+//!
+//! Run with: `cargo run -p reytan_extractor_youtube --example record_player_fixture -- <player id>`,
+//! the player id being the hash in the `/s/player/<player id>/` script path
+
+use std::path::Path;
+
+use reytan_extractor_api::anyhow::{anyhow, Result};
+use reytan_extractor_api::url::Url;
+use reytan_extractor_api::{uri, ExtractionContext, Request};
+use reytan_extractor_youtube::js::JsEngineKind;
+use reytan_extractor_youtube::recording::parse_js_functions;
+use serde::{Deserialize, Serialize};
+
+/// An entry of `fixtures/players/tests.json`, with the player script in `<player>.js`
+#[derive(Deserialize, Serialize)]
+struct PlayerJsFixture {
+    player: String,
+    sts: Option<u32>,
+    sig: Vec<(String, String)>,
+    n: Vec<(String, String)>,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let player = std::env::args()
+        .nth(1)
+        .ok_or_else(|| anyhow!("usage: record_player_fixture <player id>"))?;
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/players");
+    let mut fixtures: Vec<PlayerJsFixture> =
+        serde_json::from_str(&std::fs::read_to_string(dir.join("tests.json"))?)?;
+
+    let ctx = ExtractionContext::new()?;
+    let script_url = Url::parse(&format!(
+        "https://www.youtube.com/s/player/{}/player_ias.vflset/en_US/base.js",
+        player
+    ))?;
+    let player_js = ctx
+        .get_body("js player", Request::get(uri(script_url)).body(())?)
+        .await?;
+    let sig_definition = parse_js_functions(&player_js)?;
+    let mut js_engine = sig_definition.js_engine(JsEngineKind::ALL[0])?;
+    // the same inputs as for the other players
+    let inputs = fixtures
+        .first()
+        .ok_or_else(|| anyhow!("no fixtures to take the inputs from"))?;
+    let mut solved = |function: &str, inputs: &[(String, String)]| {
+        inputs
+            .iter()
+            .map(|(input, _)| Ok((input.clone(), js_engine.call(function, input)?)))
+            .collect::<Result<Vec<_>>>()
+    };
+    let fixture = PlayerJsFixture {
+        player: player.clone(),
+        sts: sig_definition.js_sts,
+        sig: solved("sig", &inputs.sig)?,
+        n: solved("ncode", &inputs.n)?,
+    };
+
+    std::fs::write(dir.join(format!("{}.js", player)), &player_js)?;
+    fixtures.retain(|f| f.player != player);
+    fixtures.push(fixture);
+    std::fs::write(
+        dir.join("tests.json"),
+        serde_json::to_string_pretty(&fixtures)? + "\n",
+    )?;
+    println!("recorded {}", player);
+
+    Ok(())
+}
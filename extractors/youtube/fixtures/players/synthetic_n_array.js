@@ -0,0 +1,24 @@
+// Synthetic player script, hand-written in the shape of a YouTube base.js (not a real player).
+// It covers the signature function called through `c` in a setter, and the n-parameter function in an array.
+// An edge case of the function extraction, the real players are recorded next to it by their id.
+var _yt_player={};(function(g){var window=this;/*
+
+ Copyright The Closure Library Authors.
+ SPDX-License-Identifier: Apache-2.0
+*/
+'use strict';
+var ba,ea,ka,vy,lma,Kya;ba=function(a){var b=0;return function(){return b<a.length?{done:!1,value:a[b++]}:{done:!0}}};
+ea=typeof Object.defineProperties=="function"?Object.defineProperty:function(a,b,c){if(a==Array.prototype||a==Object.prototype)return a;a[b]=c.value;return a};
+var uy={VP:function(a){a.reverse()},
+eG:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c},
+li:function(a,b){a.splice(0,b)}};
+vy=function(a){a=a.split("");uy.eG(a,50);uy.VP(a,1);uy.li(a,3);uy.eG(a,13);uy.VP(a,19);uy.li(a,2);return a.join("")};
+g.Wp=function(a,b,c){c&&(c=vy(decodeURIComponent(c)));a.set(b,encodeURIComponent(c))};
+lma=function(a){var b=a.split(""),c=[function(d,e){e=(e%d.length+d.length)%d.length;d.splice(-e).reverse().forEach(function(f){d.unshift(f)})},
+-1234,function(d){d.reverse()},function(d,e){e=(e%d.length+d.length)%d.length;var f=d[0];d[0]=d[e];d[e]=f},
+null,"RbbS",function(d,e,f){var h=f.length;d.forEach(function(l,m,n){this.push(n[m]=f[(f.indexOf(l)-f.indexOf(this[m])+m+h--)%f.length])},e.split(""))},
+"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"];c[4]=b;try{c[0](c[4],c[1]),c[2](c[4]),c[3](c[4],7),c[6](c[4],c[5],c[7]),c[0](c[4],3)}catch(d){return"enhanced_except_"+a}return b.join("")};
+var Kya=[lma];
+g.Ef=function(a){var b;a.D&&(b=a.get("n"))&&(b=Kya[0](b),a.set("n",b),Kya.length||lma(""))};
+g.Uda={clientName:"WEB",signatureTimestamp:19369,hl:"en_US"};
+})(_yt_player);
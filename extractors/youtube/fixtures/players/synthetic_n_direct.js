@@ -0,0 +1,22 @@
+// Synthetic player script, hand-written in the shape of a YouTube base.js (not a real player).
+// It covers the signature function declared with `function`, and the n-parameter function called directly.
+// An edge case of the function extraction, the real players are recorded next to it by their id.
+var _yt_player={};(function(g){var window=this;/*
+
+ Copyright The Closure Library Authors.
+ SPDX-License-Identifier: Apache-2.0
+*/
+'use strict';
+var aa=function(a){return a.raw=a},Bza;
+var $D={Zc:function(a,b){a.splice(0,b)},
+GQ:function(a){a.reverse()},
+x3:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};
+function ZL(a){a=a.split("");$D.x3(a,9);$D.Zc(a,1);$D.GQ(a,47);$D.x3(a,38);$D.Zc(a,3);$D.x3(a,61);return a.join("")};
+g.Vx=function(a,b,c,d){c&&d.set(b,encodeURIComponent(ZL(decodeURIComponent(c))))};
+Bza=function(a){var b=a.split(""),c=["0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ-_",function(d,e){d.push(e)},
+function(d,e){e=(e%d.length+d.length)%d.length;d.splice(e,1)},b,-817,function(d,e){for(e=(e%d.length+d.length)%d.length;e--;)d.unshift(d.pop())},
+function(d,e,f){var h=f.length;e.forEach(function(l,m,n){this.push(n[m]=f[(f.indexOf(l)-f.indexOf(this[m])+m+h--)%f.length])},d.split(""))},"mTrwd"];
+c[3]=b;try{c[5](c[3],c[4]),c[6](c[7],c[3],c[0]),c[2](c[3],12),c[5](c[3],5)}catch(d){return"enhanced_except_"+a}return b.join("")};
+g.Kp=function(a){var b;a.j&&(b=a.get("n"))&&(b=Bza(b),a.set("n",b))};
+g.cea={signatureTimestamp:19403,clientVersion:"2.20230306.01.00"};
+})(_yt_player);
@@ -0,0 +1,24 @@
+// Synthetic player script, hand-written in the shape of a YouTube base.js (not a real player).
+// It covers the signature function called on `h.s`, and the n-parameter function in an array.
+// An edge case of the function extraction, the real players are recorded next to it by their id.
+var _yt_player={};(function(g){var window=this;/*
+
+ Copyright The Closure Library Authors.
+ SPDX-License-Identifier: Apache-2.0
+*/
+'use strict';
+var q5=function(a,b){return a.indexOf(b)>=0},Ida,Pza;
+var Vn={
+Ma:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c},
+tq:function(a){a.reverse()},
+jK:function(a,b){a.splice(0,b)}};
+var Yq=function(a){a=a.split("");Vn.tq(a,33);Vn.jK(a,2);Vn.Ma(a,26);Vn.tq(a,4);return a.join("")};
+g.Cz=function(h){var m;h.s&&(m=Yq(decodeURIComponent(h.s)),h.url+="&sig="+m)};
+Ida=function(a){var b=a.split(""),c=[b,"Ljt4",function(d,e){e=(e%d.length+d.length)%d.length;d.splice(-e).reverse().forEach(function(f){d.unshift(f)})},
+509,function(d,e){d.splice(d.length,0,e)},function(d){for(var e=d.length;e;)d.push(d.splice(--e,1)[0])},
+function(d,e,f){var h=f.length;d.forEach(function(l,m,n){this.push(n[m]=f[(f.indexOf(l)-f.indexOf(this[m])+m+h--)%f.length])},e.split(""))},
+"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-_"];try{c[2](c[0],c[3]),c[5](c[0]),c[6](c[0],c[1],c[7]),c[2](c[0],-41)}catch(d){return"enhanced_except_"+a}return b.join("")};
+var Pza=[Ida];
+g.pq=function(a){var b;a.G&&(b=a.get("n"))&&(b=Pza[0](b),a.set("n",b))};
+g.sJa={"signatureTimestamp":19425};
+})(_yt_player);
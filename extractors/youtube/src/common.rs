@@ -1,7 +1,8 @@
 use super::types::request;
 use once_cell::sync::Lazy;
 use reytan_extractor_api::anyhow::Result;
-use reytan_extractor_api::{header, ExtractionContext, Request};
+use reytan_extractor_api::parse::{now, parse_date, parse_relative_date};
+use reytan_extractor_api::{header, url::Url, DateTime, ExtractionContext, Request, Utc};
 use serde::{Deserialize, Serialize};
 
 pub static YOUTUBE_HOSTS_MAIN: Lazy<Vec<&str>> = Lazy::new(|| {
@@ -17,6 +18,65 @@ pub static YOUTUBE_HOSTS_MAIN: Lazy<Vec<&str>> = Lazy::new(|| {
 
 pub static YOUTUBE_HOSTS_SHORT: Lazy<Vec<&str>> = Lazy::new(|| vec!["youtu.be", "y2u.be"]);
 
+pub static YOUTUBE_HOSTS_NOCOOKIE: Lazy<Vec<&str>> =
+    Lazy::new(|| vec!["youtube-nocookie.com", "www.youtube-nocookie.com"]);
+
+/// The `hl` language code for innertube, from the first locale (`pl-PL` - `pl`),
+/// English if there is none or it doesn't start with a two-letter code (`C`)
+pub fn interface_language(locales: &[String]) -> &str {
+    locales
+        .first()
+        .and_then(|locale| locale.get(..2))
+        .filter(|code| code.bytes().all(|b| b.is_ascii_alphabetic()))
+        .unwrap_or("en")
+}
+
+/// video IDs are always 11 characters of base64url
+pub fn is_video_id(id: &str) -> bool {
+    id.len() == 11
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// The watch page of the video, for the list entries
+pub fn watch_url(video_id: &str) -> Option<Url> {
+    Url::parse_with_params("https://www.youtube.com/watch", [("v", video_id)]).ok()
+}
+
+/// Deserializes the JSON value placed right after `"key":` in the webpage
+pub fn find_json_value<T>(webpage: &str, key: &str) -> Option<T>
+where
+    T: for<'a> Deserialize<'a>,
+{
+    let prefix = format!("\"{}\":", key);
+    let start = webpage.find(&prefix)? + prefix.len();
+    serde_json::Deserializer::from_str(&webpage[start..])
+        .into_iter()
+        .next()?
+        .ok()
+}
+
+/// Parses counters like "1,234 videos" (the first number in the text, no abbreviations)
+pub fn parse_count(text: &str) -> Option<u64> {
+    text.split_whitespace()
+        .next()?
+        .replace([',', '.', '\u{a0}'], "")
+        .parse()
+        .ok()
+}
+
+/// Parses "Last updated on Mar 5, 2023", "Updated today", "Updated 3 days ago" (English only)
+pub fn parse_updated_date(text: &str) -> Option<DateTime<Utc>> {
+    parse_relative_date(text, now()).or_else(|| {
+        let words: Vec<&str> = text.split_whitespace().collect();
+        parse_date(&words.get(words.len().checked_sub(3)?..)?.join(" "))
+    })
+}
+
+/// user-agent used when the client does not have a specific one
+pub static DEFAULT_USER_AGENT: &str = "okhttp/4.9.3";
+
 pub async fn innertube_request<T, S>(
     ctx: &ExtractionContext,
     resource_name: &str,
@@ -34,7 +94,7 @@ where
     ))
     .header(
         header::USER_AGENT,
-        client.user_agent.unwrap_or("okhttp/4.9.3"),
+        client.user_agent.unwrap_or(DEFAULT_USER_AGENT),
     )
     .header(header::ORIGIN, format!("https://{}", client.host))
     .header(header::CONTENT_TYPE, "application/json")
@@ -47,3 +107,44 @@ where
         .await?;
     Ok(resp)
 }
+
+#[cfg(test)]
+mod tests {
+    use reytan_extractor_api::chrono::NaiveDate;
+
+    use super::{interface_language, parse_count, parse_updated_date};
+
+    #[test]
+    fn test_interface_language() {
+        let language = |locales: &[&str]| {
+            let locales = locales.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+            interface_language(&locales).to_string()
+        };
+        assert_eq!(language(&["pl-PL", "en-US"]), "pl");
+        assert_eq!(language(&["de"]), "de");
+        assert_eq!(language(&[]), "en");
+        assert_eq!(language(&["C"]), "en");
+        assert_eq!(language(&["C.UTF-8"]), "en");
+        assert_eq!(language(&["ü"]), "en");
+    }
+
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("74 videos"), Some(74));
+        assert_eq!(parse_count("1,234 views"), Some(1234));
+        assert_eq!(parse_count("1.2K subscribers"), None);
+        assert_eq!(parse_count(""), None);
+    }
+
+    #[test]
+    fn test_parse_updated_date() {
+        assert_eq!(
+            parse_updated_date("Last updated on Mar 5, 2023").map(|d| d.date_naive()),
+            NaiveDate::from_ymd_opt(2023, 3, 5)
+        );
+        assert!(parse_updated_date("Updated today").is_some());
+        assert!(parse_updated_date("Updated yesterday").is_some());
+        assert!(parse_updated_date("Updated 3 days ago").is_some());
+        assert_eq!(parse_updated_date("74 videos"), None);
+    }
+}
@@ -0,0 +1,306 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use reytan_extractor_api::anyhow::{anyhow, Result};
+use reytan_extractor_api::cache::lru::Lru;
+use reytan_extractor_api::ExtractionContext;
+
+#[cfg(not(any(feature = "js_boa", feature = "js_quickjs")))]
+compile_error!("allow_js needs a JS engine, enable js_boa and/or js_quickjs");
+
+/// Runs the functions extracted from the player JS (signature, n-parameter)
+pub trait JsEngine {
+    /// Evaluates the code defining the functions
+    fn eval(&mut self, code: &str) -> Result<()>;
+    /// Calls a function defined before with a single string argument
+    fn call(&mut self, function: &str, arg: &str) -> Result<String>;
+}
+
+/// The engines available in this build, in the order of preference
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum JsEngineKind {
+    #[cfg(feature = "js_quickjs")]
+    QuickJs,
+    #[cfg(feature = "js_boa")]
+    Boa,
+}
+
+impl JsEngineKind {
+    pub const ALL: &'static [JsEngineKind] = &[
+        #[cfg(feature = "js_quickjs")]
+        JsEngineKind::QuickJs,
+        #[cfg(feature = "js_boa")]
+        JsEngineKind::Boa,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "js_quickjs")]
+            JsEngineKind::QuickJs => "quickjs",
+            #[cfg(feature = "js_boa")]
+            JsEngineKind::Boa => "boa",
+        }
+    }
+
+    /// Set with the extractor args: `youtube:js_engine=boa`.
+    /// Defaults to the preferred engine
+    pub fn from_context(ctx: &ExtractionContext) -> Result<JsEngineKind> {
+        match ctx.extractor_args.get("youtube", "js_engine") {
+            None | Some([]) => Ok(JsEngineKind::ALL[0]),
+            Some([name]) => JsEngineKind::ALL
+                .iter()
+                .copied()
+                .find(|e| e.name() == name)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "JS engine not available: {} (available: {})",
+                        name,
+                        JsEngineKind::ALL
+                            .iter()
+                            .map(|e| e.name())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                }),
+            Some(names) => Err(anyhow!(
+                "youtube:js_engine expects a single value, got {}",
+                names.len()
+            )),
+        }
+    }
+
+    pub fn new_engine(self) -> Result<Box<dyn JsEngine>> {
+        Ok(match self {
+            #[cfg(feature = "js_quickjs")]
+            JsEngineKind::QuickJs => Box::new(QuickJsEngine::new()?),
+            #[cfg(feature = "js_boa")]
+            JsEngineKind::Boa => Box::new(BoaEngine::new()),
+        })
+    }
+}
+
+#[cfg(feature = "js_boa")]
+pub struct BoaEngine {
+    context: boa_engine::Context,
+}
+
+#[cfg(feature = "js_boa")]
+impl BoaEngine {
+    pub fn new() -> Self {
+        BoaEngine {
+            context: boa_engine::Context::default(),
+        }
+    }
+}
+
+#[cfg(feature = "js_boa")]
+fn boa_error(
+    context: &mut boa_engine::Context,
+    error: boa_engine::JsValue,
+) -> reytan_extractor_api::anyhow::Error {
+    match error.to_string(context) {
+        Ok(message) => anyhow!("{}", message.to_string()),
+        Err(_) => anyhow!("unknown JS error"),
+    }
+}
+
+#[cfg(feature = "js_boa")]
+impl JsEngine for BoaEngine {
+    fn eval(&mut self, code: &str) -> Result<()> {
+        self.context
+            .eval(code)
+            .map_err(|e| boa_error(&mut self.context, e))?;
+        Ok(())
+    }
+
+    fn call(&mut self, function: &str, arg: &str) -> Result<String> {
+        let result = self
+            .context
+            .eval(format!("{}({})", function, serde_json::to_string(arg)?))
+            .and_then(|v| v.to_string(&mut self.context))
+            .map_err(|e| boa_error(&mut self.context, e))?;
+        Ok(result.to_string())
+    }
+}
+
+#[cfg(feature = "js_quickjs")]
+pub struct QuickJsEngine {
+    // the context has to be dropped before the runtime
+    context: rquickjs::Context,
+    _runtime: rquickjs::Runtime,
+}
+
+#[cfg(feature = "js_quickjs")]
+impl QuickJsEngine {
+    pub fn new() -> Result<Self> {
+        let runtime = rquickjs::Runtime::new()?;
+        let context = rquickjs::Context::full(&runtime)?;
+        Ok(QuickJsEngine {
+            context,
+            _runtime: runtime,
+        })
+    }
+
+    fn eval_as<T>(&mut self, code: String) -> Result<T>
+    where
+        T: for<'js> rquickjs::FromJs<'js>,
+    {
+        use rquickjs::CatchResultExt;
+
+        self.context.with(|ctx| {
+            ctx.eval::<T, _>(code)
+                .catch(&ctx)
+                .map_err(|e| anyhow!("{}", e))
+        })
+    }
+}
+
+#[cfg(feature = "js_quickjs")]
+impl JsEngine for QuickJsEngine {
+    fn eval(&mut self, code: &str) -> Result<()> {
+        self.eval_as::<()>(code.to_string())
+    }
+
+    fn call(&mut self, function: &str, arg: &str) -> Result<String> {
+        self.eval_as::<String>(format!("{}({})", function, serde_json::to_string(arg)?))
+    }
+}
+
+/// How many players to keep the solvers for, per thread
+const SOLVERS_PER_THREAD: usize = 4;
+/// Memoized results are dropped after that, to not grow endlessly on long runs
+const SOLVER_RESULTS_LIMIT: usize = 4096;
+
+/// JS engine with the player functions defined, memoizing the results
+pub struct Solver {
+    engine: Box<dyn JsEngine>,
+    /// (function, argument) -> result
+    results: HashMap<(String, String), String>,
+}
+
+impl Solver {
+    pub fn new(kind: JsEngineKind, code: &[&str]) -> Result<Solver> {
+        let mut engine = kind.new_engine()?;
+        for code in code {
+            engine.eval(code)?;
+        }
+        Ok(Solver {
+            engine,
+            results: HashMap::new(),
+        })
+    }
+
+    /// Calls the function, unless it was called with the same argument before
+    pub fn call(&mut self, function: &str, arg: &str) -> Result<String> {
+        let key = (function.to_string(), arg.to_string());
+        if let Some(result) = self.results.get(&key) {
+            return Ok(result.clone());
+        }
+        let result = self.engine.call(function, arg)?;
+        if self.results.len() >= SOLVER_RESULTS_LIMIT {
+            self.results.clear();
+        }
+        self.results.insert(key, result.clone());
+        Ok(result)
+    }
+}
+
+thread_local! {
+    /// JS engines are not Send, so the solvers are kept per thread
+    static SOLVERS: RefCell<Lru<(String, JsEngineKind), Solver>> =
+        const { RefCell::new(Lru::new(SOLVERS_PER_THREAD)) };
+}
+
+/// Runs `f` with the solver for the player (`player_hash`), set up with `code` if not cached yet.
+/// The solver is reused across the formats and videos using the same player
+pub fn with_solver<T>(
+    (player_hash, kind): (&str, JsEngineKind),
+    code: &[&str],
+    f: impl FnOnce(&mut Solver) -> Result<T>,
+) -> Result<T> {
+    SOLVERS.with(|solvers| {
+        solvers.borrow_mut().with(
+            &(player_hash.to_string(), kind),
+            || Solver::new(kind, code),
+            f,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use reytan_extractor_api::ExtractionContext;
+
+    use super::{with_solver, JsEngineKind};
+
+    #[test]
+    fn test_js_engines() {
+        for kind in JsEngineKind::ALL {
+            let mut engine = kind.new_engine().unwrap();
+            engine
+                .eval("const rev=function(a){return a.split('').reverse().join('')};")
+                .unwrap();
+            assert_eq!(
+                engine.call("rev", "a\"bc").unwrap(),
+                "cb\"a",
+                "{}",
+                kind.name()
+            );
+            assert!(
+                engine.call("undefinedfn", "abc").is_err(),
+                "{}",
+                kind.name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_js_engine_from_context() {
+        let mut ctx = ExtractionContext::new().unwrap();
+        assert_eq!(
+            JsEngineKind::from_context(&ctx).unwrap(),
+            JsEngineKind::ALL[0]
+        );
+        for kind in JsEngineKind::ALL {
+            ctx.extractor_args
+                .parse_and_add(&format!("youtube:js_engine={}", kind.name()))
+                .unwrap();
+            assert_eq!(JsEngineKind::from_context(&ctx).unwrap(), *kind);
+        }
+        ctx.extractor_args
+            .parse_and_add("youtube:js_engine=spidermonkey")
+            .unwrap();
+        assert!(JsEngineKind::from_context(&ctx).is_err());
+    }
+
+    #[test]
+    fn test_with_solver() {
+        // counts the actual calls
+        let code = [
+            "let calls=0;",
+            "const f=function(a){calls++;return a+calls};",
+        ];
+        for kind in JsEngineKind::ALL {
+            let key = ("test_with_solver", *kind);
+            with_solver(key, &code, |solver| {
+                assert_eq!(solver.call("f", "a")?, "a1");
+                assert_eq!(solver.call("f", "a")?, "a1");
+                assert_eq!(solver.call("f", "b")?, "b2");
+                Ok(())
+            })
+            .unwrap();
+            // the same context
+            with_solver(key, &code, |solver| {
+                assert_eq!(solver.call("f", "a")?, "a1");
+                assert_eq!(solver.call("f", "c")?, "c3");
+                Ok(())
+            })
+            .unwrap();
+            with_solver(("test_with_solver_other", *kind), &code, |solver| {
+                assert_eq!(solver.call("f", "c")?, "c1");
+                Ok(())
+            })
+            .unwrap();
+        }
+    }
+}
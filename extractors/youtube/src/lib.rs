@@ -2,10 +2,17 @@
 extern crate smart_default;
 
 mod common;
+#[cfg(feature = "allow_js")]
+pub mod js;
+mod manifest;
+pub mod mix;
+pub mod music;
 pub mod recording;
 pub mod tab;
 pub mod types;
 
+pub use mix::YoutubeMixLE;
+pub use music::YoutubeMusicLE;
 use once_cell::sync::Lazy;
 pub use recording::YoutubeRE;
 use reytan_extractor_api::{AnyExtractor, NewExtractor};
@@ -13,6 +20,9 @@ pub use tab::YoutubeTabLE;
 
 pub static EXTRACTORS: Lazy<Vec<AnyExtractor>> = Lazy::new(|| {
     vec![
+        AnyExtractor::List(Box::new(YoutubeMixLE::new())),
+        // before YoutubeTabLE, which matches music.youtube.com as well
+        AnyExtractor::List(Box::new(YoutubeMusicLE::new())),
         AnyExtractor::List(Box::new(YoutubeTabLE::new())),
         AnyExtractor::Recording(Box::new(YoutubeRE::new())),
     ]
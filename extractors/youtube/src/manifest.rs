@@ -0,0 +1,456 @@
+//! Expanding the DASH and HLS manifests (given for live and post-live streams) into formats
+
+use std::collections::{HashMap, HashSet};
+
+use reytan_extractor_api::anyhow::{bail, Result};
+use reytan_extractor_api::url::Url;
+use reytan_extractor_api::{
+    AudioDetails, DASHDownloadOptions, FormatBreed, FormatProtocol, HLSDownloadOptions,
+    HTTPConnectionOptions, MediaFormatDetails, MediaFormatEstablished, MediaFormatURL,
+    VideoDetails,
+};
+
+/// Gets the itag from the manifest URLs: `.../itag/96/...`
+fn itag_from_url(url: &str) -> Option<&str> {
+    let mut segments = url.split(['/', '?']);
+    segments.find(|s| *s == "itag")?;
+    segments.next().filter(|itag| !itag.is_empty())
+}
+
+fn is_audio_codec(codec: &str) -> bool {
+    matches!(
+        codec.split('.').next().unwrap_or(codec),
+        "mp4a" | "opus" | "vorbis" | "ac-3" | "ec-3" | "flac"
+    )
+}
+
+/// Guesses the breed from the codecs list (`avc1.4d401f,mp4a.40.2`)
+fn breed_from_codecs(codecs: &str) -> Option<FormatBreed> {
+    let codecs = codecs
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>();
+    let audio = codecs.iter().any(|c| is_audio_codec(c));
+    let video = codecs.iter().any(|c| !is_audio_codec(c));
+    match (audio, video) {
+        (true, true) => Some(FormatBreed::AudioVideo),
+        (true, false) => Some(FormatBreed::Audio),
+        (false, true) => Some(FormatBreed::Video),
+        (false, false) => None,
+    }
+}
+
+/// Splits the codecs list (`avc1.4d401f,mp4a.40.2`) into the video and audio codec
+pub(crate) fn split_codecs(codecs: &str) -> (Option<String>, Option<String>) {
+    let codecs = codecs
+        .split(',')
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>();
+    (
+        codecs
+            .iter()
+            .find(|c| !is_audio_codec(c))
+            .map(|c| c.to_string()),
+        codecs
+            .iter()
+            .find(|c| is_audio_codec(c))
+            .map(|c| c.to_string()),
+    )
+}
+
+/// What the manifest says about a stream
+#[derive(Default)]
+struct StreamInfo<'a> {
+    resolution: (Option<u32>, Option<u32>),
+    fps: Option<u32>,
+    channels: Option<u8>,
+    codecs: Option<&'a str>,
+    bitrate: Option<u64>,
+}
+
+fn format_details(
+    id: String,
+    breed: FormatBreed,
+    info: StreamInfo,
+    source: Option<&str>,
+    protocol: FormatProtocol,
+) -> MediaFormatDetails {
+    let (width, height) = info.resolution;
+    let (video_codec, audio_codec) = info.codecs.map(split_codecs).unwrap_or_default();
+    MediaFormatDetails {
+        id,
+        video_details: if breed == FormatBreed::Video || breed == FormatBreed::AudioVideo {
+            Some(VideoDetails {
+                width,
+                height,
+                fps: info.fps,
+                codec: video_codec,
+            })
+        } else {
+            None
+        },
+        audio_details: if breed == FormatBreed::Audio || breed == FormatBreed::AudioVideo {
+            Some(AudioDetails {
+                channels: info.channels,
+                codec: audio_codec,
+                ..Default::default()
+            })
+        } else {
+            None
+        },
+        breed,
+        source: source.map(String::from),
+        bitrate: info.bitrate,
+        protocol,
+    }
+}
+
+/// Frame rates can be fractional (`29.970`)
+fn parse_fps(fps: &str) -> Option<u32> {
+    fps.parse::<f32>().ok().map(|fps| fps.round() as u32)
+}
+
+/// Parses the attribute list: `BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2"`
+fn parse_hls_attributes(list: &str) -> HashMap<&str, &str> {
+    let mut attributes = HashMap::new();
+    let mut rest = list;
+    while let Some((key, value_and_rest)) = rest.split_once('=') {
+        let (value, next) = match value_and_rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                (&quoted[..end], &quoted[(end + 1).min(quoted.len())..])
+            }
+            None => value_and_rest
+                .split_once(',')
+                .unwrap_or((value_and_rest, "")),
+        };
+        attributes.insert(key.trim(), value);
+        rest = next.strip_prefix(',').unwrap_or(next);
+    }
+    attributes
+}
+
+/// Every variant of the master playlist (and the separate audio playlists) as a format
+pub fn parse_hls_manifest(
+    playlist: &str,
+    playlist_url: &Url,
+    connection: &HTTPConnectionOptions,
+    source: Option<&str>,
+) -> Result<Vec<MediaFormatEstablished>> {
+    if !playlist.trim_start().starts_with("#EXTM3U") {
+        bail!("not a HLS playlist");
+    }
+    let mut formats = Vec::new();
+    let mut format_ids = HashSet::new();
+    // audio groups with the audio in separate playlists
+    let mut audio_groups = HashSet::new();
+    let mut stream_inf = None;
+    let mut push_format = |url: Url, breed: FormatBreed, info| {
+        let id = format!(
+            "{}-hls",
+            itag_from_url(url.as_str())
+                .map(String::from)
+                .unwrap_or_else(|| format!("{}", format_ids.len()))
+        );
+        if format_ids.insert(id.clone()) {
+            formats.push(MediaFormatEstablished {
+                details: format_details(id, breed, info, source, FormatProtocol::HLS),
+                url: MediaFormatURL::HLS(
+                    url,
+                    HLSDownloadOptions {
+                        connection: connection.clone(),
+                    },
+                ),
+            });
+        }
+    };
+    for line in playlist.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        if let Some(attributes) = line.strip_prefix("#EXT-X-STREAM-INF:") {
+            stream_inf = Some(parse_hls_attributes(attributes));
+        } else if let Some(attributes) = line.strip_prefix("#EXT-X-MEDIA:") {
+            let attributes = parse_hls_attributes(attributes);
+            if let (Some(&"AUDIO"), Some(uri)) = (attributes.get("TYPE"), attributes.get("URI")) {
+                if let Some(group) = attributes.get("GROUP-ID") {
+                    audio_groups.insert(group.to_string());
+                }
+                let channels = attributes
+                    .get("CHANNELS")
+                    .and_then(|c| c.split('/').next()?.parse().ok());
+                push_format(
+                    playlist_url.join(uri)?,
+                    FormatBreed::Audio,
+                    StreamInfo {
+                        channels,
+                        codecs: attributes.get("CODECS").copied(),
+                        ..Default::default()
+                    },
+                );
+            }
+        } else if !line.starts_with('#') {
+            let attributes = match stream_inf.take() {
+                Some(attributes) => attributes,
+                None => continue,
+            };
+            let has_separate_audio = attributes
+                .get("AUDIO")
+                .map_or(false, |group| audio_groups.contains(*group));
+            let breed = match attributes.get("CODECS").and_then(|c| breed_from_codecs(c)) {
+                Some(FormatBreed::AudioVideo) if has_separate_audio => FormatBreed::Video,
+                Some(breed) => breed,
+                None if has_separate_audio => FormatBreed::Video,
+                None => FormatBreed::AudioVideo,
+            };
+            let resolution = attributes
+                .get("RESOLUTION")
+                .and_then(|r| r.split_once('x'))
+                .map(|(w, h)| (w.parse().ok(), h.parse().ok()))
+                .unwrap_or((None, None));
+            let info = StreamInfo {
+                resolution,
+                fps: attributes.get("FRAME-RATE").and_then(|f| parse_fps(f)),
+                codecs: attributes.get("CODECS").copied(),
+                bitrate: attributes.get("BANDWIDTH").and_then(|b| b.parse().ok()),
+                ..Default::default()
+            };
+            push_format(playlist_url.join(line)?, breed, info);
+        }
+    }
+    Ok(formats)
+}
+
+/// Every representation of the manifest as a format
+pub fn parse_dash_manifest(
+    mpd: &str,
+    mpd_url: &Url,
+    connection: &HTTPConnectionOptions,
+    source: Option<&str>,
+) -> Result<Vec<MediaFormatEstablished>> {
+    let document = roxmltree::Document::parse(mpd)?;
+    if document.root_element().tag_name().name() != "MPD" {
+        bail!("not a DASH manifest");
+    }
+    let mut formats = Vec::new();
+    let mut format_ids = HashSet::new();
+    let adaptation_sets = document
+        .descendants()
+        .filter(|n| n.tag_name().name() == "AdaptationSet");
+    for adaptation_set in adaptation_sets {
+        let representations = adaptation_set
+            .children()
+            .filter(|n| n.tag_name().name() == "Representation");
+        for representation in representations {
+            let id = match representation.attribute("id") {
+                Some(id) => id,
+                None => continue,
+            };
+            // most of the attributes can be set on either of them
+            let attribute = |name| {
+                representation
+                    .attribute(name)
+                    .or_else(|| adaptation_set.attribute(name))
+            };
+            let codecs = attribute("codecs");
+            let breed = match (attribute("mimeType"), codecs.and_then(breed_from_codecs)) {
+                (Some(mime), _) if mime.starts_with("audio/") => FormatBreed::Audio,
+                (Some(mime), Some(FormatBreed::AudioVideo)) if mime.starts_with("video/") => {
+                    FormatBreed::AudioVideo
+                }
+                (Some(mime), _) if mime.starts_with("video/") => FormatBreed::Video,
+                // subtitles and the like
+                (Some(_), _) => continue,
+                (None, Some(breed)) => breed,
+                (None, None) => continue,
+            };
+            let channels = [representation, adaptation_set]
+                .into_iter()
+                .flat_map(|n| n.children())
+                .find(|n| n.tag_name().name() == "AudioChannelConfiguration")
+                .and_then(|n| n.attribute("value")?.parse().ok());
+            let format_id = format!("{}-dash", id);
+            if !format_ids.insert(format_id.clone()) {
+                continue;
+            }
+            formats.push(MediaFormatEstablished {
+                details: format_details(
+                    format_id,
+                    breed,
+                    StreamInfo {
+                        resolution: (
+                            attribute("width").and_then(|w| w.parse().ok()),
+                            attribute("height").and_then(|h| h.parse().ok()),
+                        ),
+                        fps: attribute("frameRate").and_then(parse_fps),
+                        channels,
+                        codecs,
+                        bitrate: attribute("bandwidth").and_then(|b| b.parse().ok()),
+                    },
+                    source,
+                    FormatProtocol::DASH,
+                ),
+                url: MediaFormatURL::DASH(
+                    mpd_url.clone(),
+                    DASHDownloadOptions {
+                        connection: connection.clone(),
+                        representation_id: Some(id.to_string()),
+                    },
+                ),
+            });
+        }
+    }
+    Ok(formats)
+}
+
+#[cfg(test)]
+mod tests {
+    use reytan_extractor_api::url::Url;
+    use reytan_extractor_api::{FormatBreed, HTTPConnectionOptions, MediaFormatURL, VideoDetails};
+
+    use super::{
+        breed_from_codecs, itag_from_url, parse_dash_manifest, parse_hls_attributes,
+        parse_hls_manifest, split_codecs,
+    };
+
+    #[test]
+    fn test_parse_hls_attributes() {
+        let attributes = parse_hls_attributes(
+            r#"BANDWIDTH=1280000,CODECS="avc1.4d401f,mp4a.40.2",RESOLUTION=640x360,AUDIO="a""#,
+        );
+        assert_eq!(attributes.len(), 4);
+        assert_eq!(attributes["BANDWIDTH"], "1280000");
+        assert_eq!(attributes["CODECS"], "avc1.4d401f,mp4a.40.2");
+        assert_eq!(attributes["RESOLUTION"], "640x360");
+        assert_eq!(attributes["AUDIO"], "a");
+    }
+
+    #[test]
+    fn test_breed_from_codecs() {
+        assert_eq!(
+            breed_from_codecs("avc1.4d401f,mp4a.40.2"),
+            Some(FormatBreed::AudioVideo)
+        );
+        assert_eq!(breed_from_codecs("mp4a.40.2"), Some(FormatBreed::Audio));
+        assert_eq!(breed_from_codecs("vp09.00.51.08"), Some(FormatBreed::Video));
+        assert_eq!(breed_from_codecs(""), None);
+        assert_eq!(
+            split_codecs("avc1.4d401f, mp4a.40.2"),
+            (
+                Some("avc1.4d401f".to_string()),
+                Some("mp4a.40.2".to_string())
+            )
+        );
+        assert_eq!(split_codecs("opus"), (None, Some("opus".to_string())));
+        assert_eq!(
+            itag_from_url("https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1/itag/96/playlist/index.m3u8"),
+            Some("96")
+        );
+        assert_eq!(itag_from_url("https://example.com/index.m3u8"), None);
+    }
+
+    #[test]
+    fn test_parse_hls_manifest() {
+        let url = Url::parse(
+            "https://manifest.googlevideo.com/api/manifest/hls_variant/id/x/file/index.m3u8",
+        )
+        .unwrap();
+        let fmts = parse_hls_manifest(
+            include_str!("../fixtures/manifest_live.m3u8"),
+            &url,
+            &HTTPConnectionOptions::default(),
+            Some("ios"),
+        )
+        .unwrap();
+        assert_eq!(
+            fmts.iter()
+                .map(|f| f.details.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["233-hls", "91-hls", "93-hls", "96-hls", "301-hls"]
+        );
+        assert_eq!(fmts[0].details.breed, FormatBreed::Audio);
+        assert_eq!(
+            fmts[0].details.audio_details.as_ref().unwrap().channels,
+            Some(2)
+        );
+        assert_eq!(fmts[1].details.breed, FormatBreed::AudioVideo);
+        assert_eq!(
+            fmts[3].details.video_details,
+            Some(VideoDetails {
+                width: Some(1920),
+                height: Some(1080),
+                fps: Some(30),
+                codec: Some("avc1.640028".to_string()),
+            })
+        );
+        assert_eq!(fmts[3].details.bitrate, Some(5105632));
+        assert_eq!(
+            fmts[3]
+                .details
+                .audio_details
+                .as_ref()
+                .unwrap()
+                .codec
+                .as_deref(),
+            Some("mp4a.40.2")
+        );
+        // with the audio from the 233 playlist
+        assert_eq!(fmts[4].details.breed, FormatBreed::Video);
+        assert_eq!(fmts[4].details.source.as_deref(), Some("ios"));
+        match &fmts[1].url {
+            MediaFormatURL::HLS(u, _) => assert_eq!(
+                u.as_str(),
+                "https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1678500000/itag/91/playlist/index.m3u8"
+            ),
+            _ => panic!("not HLS"),
+        }
+
+        assert!(parse_hls_manifest("<MPD/>", &url, &Default::default(), None).is_err());
+    }
+
+    #[test]
+    fn test_parse_dash_manifest() {
+        let url = Url::parse(
+            "https://manifest.googlevideo.com/api/manifest/dash/id/x/source/yt_live_broadcast",
+        )
+        .unwrap();
+        let fmts = parse_dash_manifest(
+            include_str!("../fixtures/manifest_live.mpd"),
+            &url,
+            &HTTPConnectionOptions::default(),
+            Some("web"),
+        )
+        .unwrap();
+        assert_eq!(
+            fmts.iter()
+                .map(|f| f.details.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["140-dash", "133-dash", "137-dash", "299-dash"]
+        );
+        assert_eq!(fmts[0].details.breed, FormatBreed::Audio);
+        assert_eq!(
+            fmts[0].details.audio_details.as_ref().unwrap().channels,
+            Some(2)
+        );
+        assert_eq!(fmts[2].details.breed, FormatBreed::Video);
+        assert_eq!(
+            fmts[2].details.video_details,
+            Some(VideoDetails {
+                width: Some(1920),
+                height: Some(1080),
+                fps: Some(30),
+                codec: Some("avc1.640028".to_string()),
+            })
+        );
+        assert_eq!(fmts[2].details.bitrate, Some(4504000));
+        match &fmts[3].url {
+            MediaFormatURL::DASH(u, options) => {
+                assert_eq!(u, &url);
+                assert_eq!(options.representation_id.as_deref(), Some("299"));
+            }
+            _ => panic!("not DASH"),
+        }
+
+        assert!(parse_dash_manifest("#EXTM3U", &url, &Default::default(), None).is_err());
+        assert!(parse_dash_manifest("<html/>", &url, &Default::default(), None).is_err());
+    }
+}
@@ -0,0 +1,410 @@
+use reytan_extractor_api::anyhow::{anyhow, Result};
+use reytan_extractor_api::url::Url;
+use reytan_extractor_api::{
+    async_trait, AnyExtraction, Extraction, ExtractionContext, ListBreed, ListContinuation,
+    ListExtraction, ListExtractor, NewExtractor, URLMatcher,
+};
+
+use super::common::{innertube_request, interface_language, YOUTUBE_HOSTS_MAIN};
+use super::types::request::clients::WEB;
+use super::types::response::parts::{PlaylistPanel, PlaylistPanelVideoRenderer};
+use super::types::{request, response};
+
+/// YouTube Mixes (also known as radios), playlists generated for the user
+/// based on a video, artist, etc. - with IDs starting with `RD`
+#[derive(Clone, Copy)]
+pub struct YoutubeMixLE {}
+
+impl NewExtractor for YoutubeMixLE {
+    fn new() -> Self {
+        YoutubeMixLE {}
+    }
+}
+
+impl YoutubeMixLE {
+    async fn yti_next(
+        self,
+        ctx: &ExtractionContext,
+        resource_name: &str,
+        playlist_id: &str,
+        video_id: Option<String>,
+        index: Option<u32>,
+        client_: &request::Client<'_>,
+    ) -> Result<response::Next> {
+        let mut client = client_.clone();
+        client.context.hl = Some(interface_language(&ctx.locales));
+        let json = request::Next {
+            video_id,
+            playlist_id: Some(playlist_id.to_string()),
+            index,
+            context: request::parts::Context {
+                client: client.context,
+                third_party: client.third_party,
+            },
+            ..Default::default()
+        };
+        innertube_request(ctx, resource_name, &client, "next", json).await
+    }
+}
+
+/// whether the playlist ID belongs to a mix
+pub fn is_mix_id(playlist_id: &str) -> bool {
+    playlist_id.starts_with("RD")
+}
+
+fn get_mix_id(url: &Url) -> Option<String> {
+    url.query_pairs()
+        .find(|(k, _)| k == "list")
+        .map(|(_, v)| v.to_string())
+        .filter(|l| is_mix_id(l))
+}
+
+impl URLMatcher for YoutubeMixLE {
+    fn match_extractor(&self, url: &Url) -> bool {
+        Some(url)
+            .filter(|u| match u.scheme() {
+                "http" | "https" => true,
+                _ => false,
+            })
+            .filter(|u| {
+                let host = u.host_str().unwrap_or_default();
+                let first_segment = u.path_segments().and_then(|mut s| s.next());
+                YOUTUBE_HOSTS_MAIN.contains(&host)
+                    && [Some("watch"), Some("playlist")].contains(&first_segment)
+            })
+            .filter(|u| get_mix_id(u).is_some())
+            .is_some()
+    }
+}
+
+/// How many of the returned videos are remembered in the continuation, not to return them again
+const SEEN_LIMIT: usize = 200;
+
+/// Where the next page of the mix starts
+#[derive(PartialEq, Debug)]
+struct MixContinuation {
+    /// The last video returned, as mixes can only be paged further by "watching" it
+    video_id: String,
+    /// Its position in the mix
+    index: Option<u32>,
+    /// The latest videos returned, including `video_id`
+    seen: Vec<String>,
+}
+
+fn make_continuation(continuation: &MixContinuation) -> String {
+    format!(
+        "{}:{}:{}",
+        continuation.video_id,
+        continuation
+            .index
+            .map(|i| i.to_string())
+            .unwrap_or_default(),
+        continuation.seen.join(",")
+    )
+}
+
+fn parse_continuation(continuation: &str) -> Result<MixContinuation> {
+    let mut parts = continuation.splitn(3, ':');
+    let video_id = parts.next().unwrap_or_default().to_string();
+    let index = match parts.next() {
+        Some(index) if !video_id.is_empty() => index.parse().ok(),
+        _ => return Err(anyhow!("malformed mix continuation: {}", continuation)),
+    };
+    let mut seen: Vec<String> = parts
+        .next()
+        .unwrap_or_default()
+        .split(',')
+        .filter(|id| !id.is_empty())
+        .map(String::from)
+        .collect();
+    if !seen.contains(&video_id) {
+        seen.push(video_id.clone());
+    }
+    Ok(MixContinuation {
+        video_id,
+        index,
+        seen,
+    })
+}
+
+/// Returns the videos on the page that come after the previous one and weren't returned yet,
+/// and the continuation for the next page
+fn get_page(
+    panel: PlaylistPanel,
+    previous: Option<&MixContinuation>,
+) -> (Vec<PlaylistPanelVideoRenderer>, Option<String>) {
+    let videos: Vec<PlaylistPanelVideoRenderer> = panel
+        .contents
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|w| w.playlist_panel_video_renderer)
+        .collect();
+    // the page also includes the videos that were before the seed video
+    let start = previous
+        .and_then(|p| videos.iter().position(|v| v.video_id == p.video_id))
+        .map(|p| p + 1)
+        .unwrap_or(0);
+    let mut seen = previous.map(|p| p.seen.clone()).unwrap_or_default();
+    let new_videos: Vec<PlaylistPanelVideoRenderer> = videos
+        .into_iter()
+        .skip(start)
+        .filter(|v| {
+            if seen.contains(&v.video_id) {
+                return false;
+            }
+            seen.push(v.video_id.clone());
+            true
+        })
+        .collect();
+    let last = match new_videos.last() {
+        Some(last) => last,
+        // nothing new, youtube is out of ideas
+        None => return (new_videos, None),
+    };
+    let returned = new_videos.len() as u32;
+    let index = last
+        .navigation_endpoint
+        .as_ref()
+        .and_then(|ne| ne.watch_endpoint.as_ref())
+        .and_then(|we| we.index)
+        .or(match previous {
+            Some(previous) => previous.index.map(|i| i + returned),
+            // the first page starts at the beginning of the mix
+            None => Some(returned - 1),
+        });
+    if seen.len() > SEEN_LIMIT {
+        seen.drain(..seen.len() - SEEN_LIMIT);
+    }
+    let continuation = make_continuation(&MixContinuation {
+        video_id: last.video_id.clone(),
+        index,
+        seen,
+    });
+    (new_videos, Some(continuation))
+}
+
+#[async_trait]
+impl ListExtractor for YoutubeMixLE {
+    async fn extract_list_initial(
+        &self,
+        ctx: &ExtractionContext,
+        url: &Url,
+    ) -> Result<ListExtraction> {
+        let mix_id = get_mix_id(url).ok_or_else(|| anyhow!("no mix id in the url"))?;
+        // the seed video is in the url or at the end of the mix id (e.g. RD<video_id>, RDMM<video_id>)
+        let video_id = url
+            .query_pairs()
+            .find(|(k, _)| k == "v")
+            .map(|(_, v)| v.to_string())
+            .or_else(|| {
+                Some(&mix_id)
+                    .filter(|m| m.len() >= 2 + 11)
+                    .map(|m| m[m.len() - 11..].to_string())
+            });
+        let next = self
+            .yti_next(ctx, "mix first page", &mix_id, video_id, None, &WEB)
+            .await?;
+        let panel = next
+            .contents
+            .and_then(|c| c.playlist())
+            .ok_or_else(|| anyhow!("no mix in the response"))?;
+        let title = panel.title.clone().unwrap_or_default();
+        let (videos, continuation) = get_page(panel, None);
+
+        Ok(ListExtraction {
+            id: mix_id,
+            breed: ListBreed::Mix,
+            title,
+            is_endless: true,
+            entries: Some(Ok(videos
+                .into_iter()
+                .map(Extraction::from)
+                .map(AnyExtraction::Recording)
+                .collect())),
+            continuation,
+            ..Default::default()
+        })
+    }
+
+    async fn extract_list_continuation(
+        &self,
+        ctx: &ExtractionContext,
+        id: &str,
+        continuation: &str,
+    ) -> Result<ListContinuation> {
+        let previous = parse_continuation(continuation)?;
+        let next = self
+            .yti_next(
+                ctx,
+                "mix continuation",
+                id,
+                Some(previous.video_id.clone()),
+                previous.index,
+                &WEB,
+            )
+            .await?;
+        let panel = next
+            .contents
+            .and_then(|c| c.playlist())
+            .ok_or_else(|| anyhow!("no mix in the response"))?;
+        let (videos, continuation) = get_page(panel, Some(&previous));
+
+        Ok(ListContinuation {
+            id: id.to_string(),
+            entries: Some(Ok(videos
+                .into_iter()
+                .map(Extraction::from)
+                .map(AnyExtraction::Recording)
+                .collect())),
+            continuation,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use reytan_extractor_api::url::Url;
+    use reytan_extractor_api::{
+        AnyExtraction, ExtractionContext, ListBreed, ListExtractor, URLMatcher,
+    };
+
+    use super::{get_page, parse_continuation, MixContinuation, YoutubeMixLE};
+    use crate::types::response::parts::{PlaylistPanel, PlaylistPanelVideoRenderer};
+
+    fn panel(videos: &[(&str, Option<u32>)]) -> PlaylistPanel {
+        serde_json::from_value(serde_json::json!({
+            "playlistId": "RDdQw4w9WgXcQ",
+            "contents": videos
+                .iter()
+                .map(|(id, index)| serde_json::json!({
+                    "playlistPanelVideoRenderer": {
+                        "videoId": id,
+                        "navigationEndpoint": {
+                            "watchEndpoint": {"videoId": id, "index": index},
+                        },
+                    },
+                }))
+                .collect::<Vec<_>>(),
+        }))
+        .unwrap()
+    }
+
+    fn ids(page: &[PlaylistPanelVideoRenderer]) -> Vec<&str> {
+        page.iter().map(|v| v.video_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_get_page() {
+        let (first, continuation) = get_page(panel(&[("a", None), ("b", None), ("c", None)]), None);
+        assert_eq!(ids(&first), vec!["a", "b", "c"]);
+        let previous = parse_continuation(&continuation.unwrap()).unwrap();
+        assert_eq!(
+            previous,
+            MixContinuation {
+                video_id: "c".to_string(),
+                index: Some(2),
+                seen: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            }
+        );
+
+        // the page starts after the previous video, and skips the ones returned before
+        let (second, continuation) = get_page(
+            panel(&[
+                ("b", None),
+                ("c", None),
+                ("d", None),
+                ("a", None),
+                ("e", None),
+            ]),
+            Some(&previous),
+        );
+        assert_eq!(ids(&second), vec!["d", "e"]);
+        let previous = parse_continuation(&continuation.unwrap()).unwrap();
+        assert_eq!((previous.video_id.as_str(), previous.index), ("e", Some(4)));
+
+        // without the previous video on the page, still no repeats
+        let (third, continuation) = get_page(
+            panel(&[("a", Some(0)), ("f", Some(7)), ("d", Some(8))]),
+            Some(&previous),
+        );
+        assert_eq!(ids(&third), vec!["f"]);
+        let previous = parse_continuation(&continuation.unwrap()).unwrap();
+        assert_eq!((previous.video_id.as_str(), previous.index), ("f", Some(7)));
+
+        // nothing new
+        let (fourth, continuation) = get_page(panel(&[("a", None), ("f", None)]), Some(&previous));
+        assert!(fourth.is_empty());
+        assert_eq!(continuation, None);
+    }
+
+    #[test]
+    fn test_parse_continuation() {
+        assert_eq!(
+            parse_continuation("dQw4w9WgXcQ:25").unwrap(),
+            MixContinuation {
+                video_id: "dQw4w9WgXcQ".to_string(),
+                index: Some(25),
+                seen: vec!["dQw4w9WgXcQ".to_string()],
+            }
+        );
+        assert!(parse_continuation("dQw4w9WgXcQ").is_err());
+        assert!(parse_continuation(":25").is_err());
+    }
+
+    #[test]
+    fn test_url_match_mix() {
+        let ytm = YoutubeMixLE {};
+        for (url, expected) in [
+            (
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ",
+                true,
+            ),
+            ("https://www.youtube.com/playlist?list=RDdQw4w9WgXcQ", true),
+            (
+                "https://www.youtube.com/playlist?list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr",
+                false,
+            ),
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ", false),
+        ] {
+            assert_eq!(
+                ytm.match_extractor(&Url::parse(url).unwrap()),
+                expected,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn do_extract_youtube_mix() {
+        let ctx = ExtractionContext::new().unwrap();
+        let ytm = YoutubeMixLE {};
+        let url =
+            Url::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ").unwrap();
+        let initial = ytm.extract_list_initial(&ctx, &url).await.unwrap();
+        assert_eq!(initial.id, "RDdQw4w9WgXcQ");
+        assert_eq!(initial.breed, ListBreed::Mix);
+        assert_eq!(initial.is_endless, true);
+        let mut ids = HashSet::new();
+        for entry in initial.entries.unwrap().unwrap() {
+            match entry {
+                AnyExtraction::Recording(r) => assert!(ids.insert(r.metadata.id)),
+                _ => panic!("mix should only contain recordings"),
+            }
+        }
+        let continuation = ytm
+            .extract_list_continuation(&ctx, &initial.id, &initial.continuation.unwrap())
+            .await
+            .unwrap();
+        let entries = continuation.entries.unwrap().unwrap();
+        assert!(entries.len() > 0);
+        for entry in entries {
+            match entry {
+                AnyExtraction::Recording(r) => assert!(ids.insert(r.metadata.id)),
+                _ => panic!("mix should only contain recordings"),
+            }
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use reytan_extractor_api::anyhow::{anyhow, Result};
+use reytan_extractor_api::url::Url;
+use reytan_extractor_api::{
+    async_trait, AnyExtraction, ExtractionContext, ListBreed, ListContinuation, ListExtraction,
+    ListExtractor, ListOwner, NewExtractor, TrackMetadata, URLMatcher,
+};
+
+use super::common::{innertube_request, interface_language, YOUTUBE_HOSTS_MAIN};
+use super::types::request::clients::WEB_MUSIC;
+use super::types::response::parts::{MusicHeaderRenderer, MusicSection};
+use super::types::{request, response};
+
+/// YouTube Music albums (`MPREb...`), album playlists (`OLAK5uy_...`) and artists
+#[derive(Clone, Copy)]
+pub struct YoutubeMusicLE {}
+
+impl NewExtractor for YoutubeMusicLE {
+    fn new() -> Self {
+        YoutubeMusicLE {}
+    }
+}
+
+impl YoutubeMusicLE {
+    async fn yti_browse(
+        self,
+        ctx: &ExtractionContext,
+        resource_name: &str,
+        id: &str,
+        client_: &request::Client<'_>,
+    ) -> Result<response::MusicBrowse> {
+        let mut client = client_.clone();
+        client.context.hl = Some(interface_language(&ctx.locales));
+        let json = request::Browse {
+            browse_id: id.to_string(),
+            context: request::parts::Context {
+                client: client.context,
+                third_party: client.third_party,
+            },
+            ..Default::default()
+        };
+        innertube_request(ctx, resource_name, &client, "browse", json).await
+    }
+}
+
+/// whether the playlist ID belongs to an auto-generated album playlist
+pub fn is_album_playlist_id(playlist_id: &str) -> bool {
+    playlist_id.starts_with("OLAK5uy_")
+}
+
+/// Gets the browse ID from the url: `MPREb...` (album), `UC...` (artist),
+/// or `VLOLAK5uy_...` (album playlist)
+fn get_browse_id(url: &Url) -> Option<String> {
+    let host = url.host_str().unwrap_or_default();
+    let segments: Vec<&str> = url.path_segments().map(|s| s.collect()).unwrap_or_default();
+    match segments.as_slice() {
+        ["playlist", ..] if YOUTUBE_HOSTS_MAIN.contains(&host) => url
+            .query_pairs()
+            .find(|(k, _)| k == "list")
+            .map(|(_, v)| v.to_string())
+            .filter(|l| is_album_playlist_id(l))
+            .map(|l| format!("VL{}", l)),
+        ["browse", id, ..] if host == "music.youtube.com" && id.starts_with("MPREb") => {
+            Some(id.to_string())
+        }
+        ["channel", id, ..] if host == "music.youtube.com" && id.starts_with("UC") => {
+            Some(id.to_string())
+        }
+        _ => None,
+    }
+}
+
+impl URLMatcher for YoutubeMusicLE {
+    fn match_extractor(&self, url: &Url) -> bool {
+        Some(url)
+            .filter(|u| match u.scheme() {
+                "http" | "https" => true,
+                _ => false,
+            })
+            .filter(|u| get_browse_id(u).is_some())
+            .is_some()
+    }
+}
+
+/// Album-wide track details from the header
+fn album_track_metadata(header: &MusicHeaderRenderer) -> TrackMetadata {
+    TrackMetadata {
+        album: Some(header.title.clone().into()),
+        album_artists: header.artists().into_iter().map(|(_, name)| name).collect(),
+        release_year: header.year(),
+        ..Default::default()
+    }
+}
+
+fn extract_album(browse_id: String, browse: response::MusicBrowse) -> Result<ListExtraction> {
+    let sections: Vec<MusicSection> = browse
+        .contents
+        .ok_or_else(|| anyhow!("no contents in the album"))?
+        .sections();
+    let header = browse
+        .header
+        .and_then(|h| h.renderer())
+        .or_else(|| {
+            sections
+                .iter()
+                .find_map(|s| s.music_responsive_header_renderer.clone())
+        })
+        .ok_or_else(|| anyhow!("no album header"))?;
+    let album = album_track_metadata(&header);
+    let entries: Vec<AnyExtraction> = sections
+        .into_iter()
+        .flat_map(|s| s.music_shelf_renderer.or(s.music_playlist_shelf_renderer))
+        .flat_map(|shelf| shelf.contents)
+        .flat_map(|item| item.music_responsive_list_item_renderer)
+        .enumerate()
+        .flat_map(|(i, item)| item.into_track(&album, i as u32 + 1))
+        .map(AnyExtraction::Recording)
+        .collect();
+
+    Ok(ListExtraction {
+        id: browse_id,
+        breed: ListBreed::Album,
+        title: album.album.clone().unwrap_or_default(),
+        description: header.description(),
+        owner: header
+            .artists()
+            .into_iter()
+            .next()
+            .map(|(id, name)| ListOwner { id: Some(id), name }),
+        item_count: Some(entries.len() as u64),
+        thumbnails: header.thumbnail.map(Vec::from).unwrap_or_default(),
+        is_endless: false,
+        entries: Some(Ok(entries)),
+        continuation: None,
+        ..Default::default()
+    })
+}
+
+/// Artists come with albums and singles as the nested lists (without the entries)
+fn extract_artist(browse_id: String, browse: response::MusicBrowse) -> Result<ListExtraction> {
+    let header = browse.header.and_then(|h| h.renderer());
+    let name: Option<String> = header.as_ref().map(|h| h.title.clone().into());
+    let owner = name.clone().map(|name| ListOwner {
+        id: Some(browse_id.clone()),
+        name,
+    });
+    let entries: Vec<AnyExtraction> = browse
+        .contents
+        .ok_or_else(|| anyhow!("no contents on the artist page"))?
+        .sections()
+        .into_iter()
+        .flat_map(|s| s.music_carousel_shelf_renderer)
+        .flat_map(|carousel| carousel.contents)
+        .flat_map(|item| item.music_two_row_item_renderer)
+        .flat_map(|mtrir| {
+            let id = mtrir.album_id()?.to_string();
+            Some(AnyExtraction::List(ListExtraction {
+                id,
+                breed: ListBreed::Album,
+                title: mtrir.title.into(),
+                owner: owner.clone(),
+                thumbnails: mtrir.thumbnail_renderer.map(Vec::from).unwrap_or_default(),
+                is_endless: false,
+                // not fetched yet
+                entries: None,
+                ..Default::default()
+            }))
+        })
+        .collect();
+
+    Ok(ListExtraction {
+        id: browse_id,
+        breed: ListBreed::Channel,
+        title: name.unwrap_or_default(),
+        description: header.as_ref().and_then(|h| h.description()),
+        owner,
+        thumbnails: header
+            .and_then(|h| h.thumbnail)
+            .map(Vec::from)
+            .unwrap_or_default(),
+        is_endless: false,
+        entries: Some(Ok(entries)),
+        continuation: None,
+        ..Default::default()
+    })
+}
+
+#[async_trait]
+impl ListExtractor for YoutubeMusicLE {
+    async fn extract_list_initial(
+        &self,
+        ctx: &ExtractionContext,
+        url: &Url,
+    ) -> Result<ListExtraction> {
+        let browse_id = get_browse_id(url).ok_or_else(|| anyhow!("no browse id in the url"))?;
+        let browse = self
+            .yti_browse(ctx, "music browse", &browse_id, &WEB_MUSIC)
+            .await?;
+        if browse_id.starts_with("UC") {
+            extract_artist(browse_id, browse)
+        } else {
+            extract_album(browse_id, browse)
+        }
+    }
+
+    async fn extract_list_continuation(
+        &self,
+        _ctx: &ExtractionContext,
+        id: &str,
+        _continuation: &str,
+    ) -> Result<ListContinuation> {
+        Err(anyhow!("{} is returned as a whole, no continuations", id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reytan_extractor_api::url::Url;
+    use reytan_extractor_api::{
+        AnyExtraction, ExtractionContext, ListBreed, ListExtractor, URLMatcher,
+    };
+
+    use super::{get_browse_id, YoutubeMusicLE};
+
+    #[test]
+    fn test_get_browse_id() {
+        for (url, expected) in [
+            (
+                "https://music.youtube.com/browse/MPREb_2vHBHgbjNAz",
+                Some("MPREb_2vHBHgbjNAz"),
+            ),
+            (
+                "https://music.youtube.com/channel/UCvGEK5_U-kLgO6-AMDPeTUQ",
+                Some("UCvGEK5_U-kLgO6-AMDPeTUQ"),
+            ),
+            (
+                "https://music.youtube.com/playlist?list=OLAK5uy_lb8zg6LWXvdBdz1EnD5ZKizgy7V7j4s5w",
+                Some("VLOLAK5uy_lb8zg6LWXvdBdz1EnD5ZKizgy7V7j4s5w"),
+            ),
+            (
+                "https://www.youtube.com/playlist?list=OLAK5uy_lb8zg6LWXvdBdz1EnD5ZKizgy7V7j4s5w",
+                Some("VLOLAK5uy_lb8zg6LWXvdBdz1EnD5ZKizgy7V7j4s5w"),
+            ),
+            (
+                "https://music.youtube.com/playlist?list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr",
+                None,
+            ),
+            (
+                "https://www.youtube.com/channel/UCvGEK5_U-kLgO6-AMDPeTUQ",
+                None,
+            ),
+            ("https://music.youtube.com/browse/FEmusic_home", None),
+            ("https://music.youtube.com/watch?v=dQw4w9WgXcQ", None),
+        ] {
+            let url = Url::parse(url).unwrap();
+            assert_eq!(get_browse_id(&url).as_deref(), expected, "{}", url);
+            assert_eq!(
+                YoutubeMusicLE {}.match_extractor(&url),
+                expected.is_some(),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn do_extract_music_album() {
+        let ctx = ExtractionContext::new().unwrap();
+        let ytm = YoutubeMusicLE {};
+        let album = ytm
+            .extract_list_initial(
+                &ctx,
+                &Url::parse("https://music.youtube.com/browse/MPREb_2vHBHgbjNAz").unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(album.breed, ListBreed::Album);
+        assert!(album.owner.is_some());
+        let entries = album.entries.unwrap().unwrap();
+        assert!(entries.len() > 0);
+        for (i, entry) in entries.into_iter().enumerate() {
+            match entry {
+                AnyExtraction::Recording(r) => {
+                    let track = r.metadata.track.unwrap();
+                    assert_eq!(track.album.as_deref(), Some(album.title.as_str()));
+                    assert!(track.artists.len() > 0);
+                    assert_eq!(track.track_number, Some(i as u32 + 1));
+                }
+                _ => panic!("album should only contain recordings"),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn do_extract_music_album_playlist() {
+        let ctx = ExtractionContext::new().unwrap();
+        let ytm = YoutubeMusicLE {};
+        let album = ytm
+            .extract_list_initial(
+                &ctx,
+                &Url::parse(
+                    "https://music.youtube.com/playlist?list=OLAK5uy_lb8zg6LWXvdBdz1EnD5ZKizgy7V7j4s5w",
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(album.breed, ListBreed::Album);
+        assert!(album.entries.unwrap().unwrap().len() > 0);
+    }
+
+    #[tokio::test]
+    async fn do_extract_music_artist() {
+        let ctx = ExtractionContext::new().unwrap();
+        let ytm = YoutubeMusicLE {};
+        let artist = ytm
+            .extract_list_initial(
+                &ctx,
+                &Url::parse("https://music.youtube.com/channel/UCvGEK5_U-kLgO6-AMDPeTUQ").unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(artist.breed, ListBreed::Channel);
+        assert!(artist.title.len() > 0);
+        let entries = artist.entries.unwrap().unwrap();
+        assert!(entries.len() > 0);
+        for entry in entries {
+            match entry {
+                AnyExtraction::List(l) => {
+                    assert_eq!(l.breed, ListBreed::Album);
+                    assert!(l.id.starts_with("MPREb"));
+                }
+                _ => panic!("artist should only contain albums and singles"),
+            }
+        }
+    }
+}
@@ -1,28 +1,35 @@
 use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
-use super::common::{innertube_request, YOUTUBE_HOSTS_MAIN, YOUTUBE_HOSTS_SHORT};
+use super::common::{
+    find_json_value, innertube_request, interface_language, is_video_id, YOUTUBE_HOSTS_MAIN,
+    YOUTUBE_HOSTS_NOCOOKIE, YOUTUBE_HOSTS_SHORT,
+};
+use super::manifest::{parse_dash_manifest, parse_hls_manifest};
 use super::types::request::{self, clients};
 use super::types::response;
-use super::types::response::parts::{Format, StreamingData};
+use super::types::response::parts::{ClipConfig, Format, ManifestUrl, StreamingData, VideoIdOnly};
 
 #[cfg(feature = "allow_js")]
-use boa_engine::Context as JSContext;
+use super::js::{self, JsEngineKind};
 #[cfg(feature = "allow_js")]
 use qstring::QString;
 #[cfg(feature = "allow_js")]
 use regex::Regex;
 #[cfg(feature = "allow_js")]
-use reytan_extractor_api::anyhow::Error;
+use reytan_extractor_api::anyhow::Context;
 #[cfg(feature = "allow_js")]
 use serde::{Deserialize, Serialize};
 
 use once_cell::sync::Lazy;
-use reytan_extractor_api::anyhow::{bail, Result};
+use reytan_extractor_api::anyhow::{anyhow, bail, Result};
+use reytan_extractor_api::parse::{parse_clock_duration, parse_date, parse_datetime};
 use reytan_extractor_api::url::Url;
 use reytan_extractor_api::{
-    async_trait, chrono, uri, ExtractLevel, Extractable, Extraction, ExtractionContext, LiveStatus,
-    MediaFormatEstablished, MediaMetadata, NewExtractor, RecordingExtractor, URLMatcher, Utc,
+    async_trait, chrono, chrono::TimeZone, header, uri, Chapter, ClipSection, ExtractLevel,
+    Extractable, Extraction, ExtractionContext, HTTPConnectionOptions, LiveStatus,
+    MediaFormatEstablished, MediaMetadata, NewExtractor, RecordingExtractor, Request, URLMatcher,
+    Utc,
 };
 
 pub struct YoutubeRE {}
@@ -42,12 +49,7 @@ impl YoutubeRE {
         sts: Option<u32>,
     ) -> Result<response::Player> {
         let mut client = client_.clone();
-        let hl = &ctx
-            .locales
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "en".to_string())[0..2];
-        client.context.hl = Some(hl);
+        client.context.hl = Some(interface_language(&ctx.locales));
         let json = request::Player {
             video_id: id.to_string(),
             context: request::parts::Context {
@@ -80,57 +82,161 @@ impl URLMatcher for YoutubeRE {
                 "http" | "https" => true,
                 _ => false,
             })
-            .filter(|u| {
-                let host = u.host_str().unwrap();
-                let first_segment = u.path_segments().unwrap().next().unwrap();
-                (YOUTUBE_HOSTS_MAIN.contains(&host)
-                    && ["watch", "video", "shorts"].contains(&first_segment))
-                    || YOUTUBE_HOSTS_SHORT.contains(&host)
-            })
+            .filter(|u| parse_url(u).is_ok())
             .is_some()
     }
 }
 
-impl YoutubeRE {
-    fn get_id(&self, url: &Url) -> String {
-        let host = url.host_str().unwrap();
-        let mut segments = url.path_segments().unwrap();
-        let first_segment = segments.next().unwrap();
-        if YOUTUBE_HOSTS_MAIN.contains(&host) {
-            if first_segment == "watch" {
-                return url
+/// What a recording URL is pointing at
+#[derive(PartialEq, Debug)]
+enum YoutubeURL {
+    Video(String),
+    /// clip ID (not a video ID), has to be resolved to the video and the section
+    Clip(String),
+}
+
+fn parse_url(url: &Url) -> Result<YoutubeURL> {
+    let host = url.host_str().ok_or_else(|| anyhow!("no host in url"))?;
+    let mut segments = url
+        .path_segments()
+        .ok_or_else(|| anyhow!("url cannot be a base"))?;
+    let first_segment = segments.next().unwrap_or_default();
+    let id = if YOUTUBE_HOSTS_SHORT.contains(&host) {
+        first_segment.to_string()
+    } else if YOUTUBE_HOSTS_NOCOOKIE.contains(&host) && first_segment == "embed" {
+        segments.next().unwrap_or_default().to_string()
+    } else if YOUTUBE_HOSTS_MAIN.contains(&host) {
+        match first_segment {
+            "watch" => url
+                .query_pairs()
+                .find(|(k, _)| k == "v")
+                .map(|(_, v)| v.to_string())
+                .ok_or_else(|| anyhow!("no video id in the watch url"))?,
+            "video" | "shorts" | "live" | "embed" | "v" | "e" => {
+                segments.next().unwrap_or_default().to_string()
+            }
+            "clip" => {
+                let clip_id = segments.next().unwrap_or_default();
+                if clip_id.is_empty() {
+                    bail!("no clip id in the url");
+                }
+                return Ok(YoutubeURL::Clip(clip_id.to_string()));
+            }
+            // https://www.youtube.com/attribution_link?a=...&u=/watch%3Fv%3D...%26feature%3Dshare
+            "attribution_link" => {
+                let target = url
                     .query_pairs()
-                    .find(|(k, _)| k == "v")
-                    .unwrap()
-                    .1
-                    .to_string();
-            } else {
-                return segments.next().unwrap().to_string();
+                    .find(|(k, _)| k == "u")
+                    .map(|(_, v)| v.to_string())
+                    .ok_or_else(|| anyhow!("no target in the attribution link"))?;
+                return parse_url(&url.join(&target)?);
             }
-        } else {
-            return first_segment.to_string();
+            _ => bail!("not a video url"),
         }
+    } else {
+        bail!("not a youtube host");
+    };
+    if !is_video_id(&id) {
+        bail!("invalid video id: {:?}", id);
+    }
+    Ok(YoutubeURL::Video(id))
+}
+
+impl YoutubeRE {
+    async fn resolve_clip(
+        &self,
+        ctx: &ExtractionContext,
+        clip_id: &str,
+    ) -> Result<(String, ClipSection)> {
+        let webpage = ctx
+            .get_body(
+                "clip page",
+                Request::get(format!("https://www.youtube.com/clip/{clip_id}"))
+                    .header(header::USER_AGENT, clients::WEB.user_agent.unwrap())
+                    .body(())?,
+            )
+            .await?;
+        let clip_config: ClipConfig = find_json_value(&webpage, "clipConfig")
+            .ok_or_else(|| anyhow!("no clip config on the clip page"))?;
+        let video_details: VideoIdOnly = find_json_value(&webpage, "videoDetails")
+            .ok_or_else(|| anyhow!("no video details on the clip page"))?;
+        Ok((
+            video_details.video_id,
+            ClipSection {
+                start: Duration::from_millis(clip_config.start_time_ms),
+                end: Duration::from_millis(clip_config.end_time_ms),
+            },
+        ))
     }
 }
 
 fn parse_formats(strm: StreamingData) -> Vec<MediaFormatEstablished> {
     let mut fmts: Vec<MediaFormatEstablished> = vec![];
-    if let Some(formats) = strm.formats {
+    for formats in [strm.formats, strm.adaptive_formats, strm.hls_formats]
+        .into_iter()
+        .flatten()
+    {
         for fmt in formats {
-            fmts.push(fmt.into());
+            match MediaFormatEstablished::try_from(fmt) {
+                Ok(fmt) => fmts.push(fmt),
+                Err(e) => log::warn!("skipping the format: {:?}", e),
+            }
         }
     }
-    if let Some(formats) = strm.adaptive_formats {
-        for fmt in formats {
-            fmts.push(fmt.into());
-        }
+    fmts
+}
+
+/// Requested with the same connection settings as the formats will be
+async fn fetch_manifest(
+    ctx: &ExtractionContext,
+    resource_name: &str,
+    manifest: &ManifestUrl,
+) -> Result<(String, Url, HTTPConnectionOptions)> {
+    let url = Url::parse(&manifest.url)?;
+    let connection = manifest.connection();
+    let mut request = Request::get(url.as_str());
+    if let Some(user_agent) = &connection.user_agent {
+        request = request.header(header::USER_AGENT, user_agent);
     }
-    if let Some(formats) = strm.hls_formats {
-        for fmt in formats {
-            fmts.push(fmt.into());
+    for (name, value) in &connection.headers {
+        request = request.header(name, value);
+    }
+    let body = ctx.get_body(resource_name, request.body(())?).await?;
+    Ok((body, url, connection))
+}
+
+impl YoutubeRE {
+    /// Formats listed in the manifests of the live and post-live streams.
+    /// A manifest that can't be fetched or parsed (e.g. expired) is skipped,
+    /// the formats from the player response are still there
+    async fn expand_manifests(
+        &self,
+        ctx: &ExtractionContext,
+        strm: &StreamingData,
+    ) -> Vec<MediaFormatEstablished> {
+        let mut fmts = Vec::new();
+        if let Some(manifest) = &strm.dash_manifest_url {
+            match fetch_manifest(ctx, "DASH manifest", manifest)
+                .await
+                .and_then(|(body, url, connection)| {
+                    parse_dash_manifest(&body, &url, &connection, manifest.source_client.as_deref())
+                }) {
+                Ok(manifest_fmts) => fmts.extend(manifest_fmts),
+                Err(e) => log::warn!("skipping the DASH manifest: {:?}", e),
+            }
         }
+        if let Some(manifest) = &strm.hls_manifest_url {
+            match fetch_manifest(ctx, "HLS manifest", manifest)
+                .await
+                .and_then(|(body, url, connection)| {
+                    parse_hls_manifest(&body, &url, &connection, manifest.source_client.as_deref())
+                }) {
+                Ok(manifest_fmts) => fmts.extend(manifest_fmts),
+                Err(e) => log::warn!("skipping the HLS manifest: {:?}", e),
+            }
+        }
+        fmts
     }
-    fmts
 }
 
 #[cfg(feature = "allow_js")]
@@ -240,13 +346,87 @@ static PLAYABILITY_STATUS_TYPE: Lazy<HashMap<String, PlayabilityCategory>> = Laz
 static WEB_JS_FUNCTIONS_POOL: &'static str = "youtube_js_player_fns";
 
 #[cfg(feature = "allow_js")]
-#[derive(Serialize, Deserialize, Default)]
-struct SigDefinition {
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SigDefinition {
     pub sig_code: String,
     pub ncode_code: String,
     pub js_sts: Option<u32>,
 }
 
+/// Extracts the signature and n-parameter functions (with their dependencies) from the player JS
+#[cfg(feature = "allow_js")]
+pub fn parse_js_functions(player_js: &str) -> Result<SigDefinition> {
+    let sig_fn_name = WEB_JS_SIG_FN_NAME_RE
+        .iter()
+        .find_map(|r| r.captures(player_js))
+        .and_then(|c| c.name("sig"))
+        .ok_or_else(|| anyhow!("signature function name not found"))?
+        .as_str();
+    let sig_fn_match = Regex::new(&format!(
+                r#"(?:function\s+{0}|[{{;,]\s*{0}\s*=\s*function|(?:var|const|let)\s+{0}\s*=\s*function)\s*\((?P<args>[^)]*)\)\s*(?P<code>\{{\s*a\s*=\s*a\s*\.\s*split\s*\(\s*(?:""|'')\s*\)\s*;\s*(?P<mangler>[a-zA-Z0-9_$]{{2}})\s*\..+?}})"#,
+                regex::escape(sig_fn_name),
+            ))?
+        .captures(player_js)
+        .ok_or_else(|| anyhow!("signature function {} not found", sig_fn_name))?;
+    let sig_fn_code = &sig_fn_match["code"];
+    let sig_fn_args = &sig_fn_match["args"];
+    let sig_fn_mangler_name = &sig_fn_match["mangler"];
+    let sig_manglers_match = Regex::new(&format!(
+        r#"(?s)(?:(?:var|const|let)\s+|[{{;,]\s*){0}\s*=\s*(?P<code>\{{.+?}}\s*}}\s*);"#,
+        regex::escape(sig_fn_mangler_name),
+    ))?
+    .captures(player_js)
+    .ok_or_else(|| anyhow!("signature helper object {} not found", sig_fn_mangler_name))?;
+    let sig_manglers = &sig_manglers_match["code"];
+    let ncode_fn_init_name_match = WEB_JS_NCODE_FN_INITIAL_NAME_RE
+        .captures(player_js)
+        .ok_or_else(|| anyhow!("n-parameter function call not found"))?;
+    let ncode_fn_var = &ncode_fn_init_name_match["ncvar"];
+    let ncode_fn_name_match;
+    let ncode_fn_name = if ncode_fn_init_name_match.name("index").is_some() {
+        ncode_fn_name_match = Regex::new(&format!(
+            r#"(?:(?:var|const|let)\s+|[}};]\s*){}\s*=\s*\[\s*([a-zA-Z0-9_$]{{2,}})\s*\]\s*;"#,
+            regex::escape(ncode_fn_var),
+        ))?
+        .captures(player_js)
+        .ok_or_else(|| anyhow!("n-parameter function array {} not found", ncode_fn_var))?;
+        &ncode_fn_name_match[1]
+    } else {
+        ncode_fn_var
+    };
+    let ncode_match = Regex::new(&format!(
+        r#"(?s){0}\s*=\s*function\s*\((?P<args>[^)]*)\)(?P<code>\{{.+?return\s+b\.join\((?:""|'')\);?\}});"#,
+        regex::escape(ncode_fn_name)
+    ))?
+    .captures(player_js)
+    .ok_or_else(|| anyhow!("n-parameter function {} not found", ncode_fn_name))?;
+    let ncode_fn_args = &ncode_match["args"];
+    let ncode_fn_code = &ncode_match["code"];
+    let js_sts = WEB_JS_STS_RE
+        .captures(player_js)
+        .and_then(|c| c[1].parse().ok());
+    Ok(SigDefinition {
+        sig_code: format!(
+            "
+            const {sig_fn_mangler_name}={sig_manglers};
+            const sig=function({sig_fn_args}){sig_fn_code};"
+        ),
+        ncode_code: format!("const ncode=function({ncode_fn_args}){ncode_fn_code};"),
+        js_sts,
+    })
+}
+
+#[cfg(feature = "allow_js")]
+impl SigDefinition {
+    /// JS engine with the `sig` and `ncode` functions defined, not cached like [js::with_solver]
+    pub fn js_engine(&self, kind: JsEngineKind) -> Result<Box<dyn js::JsEngine>> {
+        let mut engine = kind.new_engine()?;
+        engine.eval(&self.sig_code)?;
+        engine.eval(&self.ncode_code)?;
+        Ok(engine)
+    }
+}
+
 impl YoutubeRE {
     #[cfg(feature = "allow_js")]
     async fn get_js_functions(
@@ -270,60 +450,8 @@ impl YoutubeRE {
                 Request::get(uri(script_url)).body(())?,
             )
             .await?;
-        let sig_fn_name = WEB_JS_SIG_FN_NAME_RE
-            .iter()
-            .find_map(|r| r.captures(&player_js))
-            .unwrap()
-            .name("sig")
-            .unwrap()
-            .as_str();
-        let sig_fn_match = Regex::new(&format!(
-                    r#"(?:function\s+{0}|[{{;,]\s*{0}\s*=\s*function|(?:var|const|let)\s+{0}\s*=\s*function)\s*\((?P<args>[^)]*)\)\s*(?P<code>\{{\s*a\s*=\s*a\s*\.\s*split\s*\(\s*(?:""|'')\s*\)\s*;\s*(?P<mangler>[a-zA-Z0-9_$]{{2}})\s*\..+?}})"#,
-                    regex::escape(sig_fn_name),
-                )).unwrap().captures(&player_js).unwrap();
-        let sig_fn_code = sig_fn_match.name("code").unwrap().as_str();
-        let sig_fn_args = sig_fn_match.name("args").unwrap().as_str();
-        let sig_fn_mangler_name = sig_fn_match.name("mangler").unwrap().as_str();
-        let sig_manglers = Regex::new(&format!(
-            r#"(?s)(?:(?:var|const|let)\s+|[{{;,]\s*){0}\s*=\s*(?P<code>\{{.+?}}\s*}}\s*);"#,
-            regex::escape(sig_fn_mangler_name),
-        ))
-        .unwrap()
-        .captures(&player_js)
-        .unwrap()
-        .name("code")
-        .unwrap()
-        .as_str();
-        let ncode_fn_init_name_match = WEB_JS_NCODE_FN_INITIAL_NAME_RE
-            .captures(&player_js)
-            .unwrap();
-        let ncode_fn_name =
-            if ncode_fn_init_name_match.name("index").is_some() {
-                Regex::new(&format!(
-                r#"(?:(?:var|const|let)\s+|[}};]\s*){}\s*=\s*\[\s*([a-zA-Z0-9_$]{{2,}})\s*\]\s*;"#,
-                regex::escape(ncode_fn_init_name_match.name("ncvar").unwrap().as_str()),
-            ))
-            .unwrap().captures(&player_js).unwrap().get(1).unwrap().as_str()
-            } else {
-                ncode_fn_init_name_match.name("ncvar").unwrap().as_str()
-            };
-        let ncode_match = Regex::new(&format!(
-            r#"(?s){0}\s*=\s*function\s*\((?P<args>[^)]*)\)(?P<code>\{{.+?return\s+b\.join\((?:""|'')\);?\}});"#,
-            regex::escape(ncode_fn_name))).unwrap().captures(&player_js).unwrap();
-        let ncode_fn_args = ncode_match.name("args").unwrap().as_str();
-        let ncode_fn_code = ncode_match.name("code").unwrap().as_str();
-        let js_sts = WEB_JS_STS_RE
-            .captures(&player_js)
-            .map(|c| c.get(1).unwrap().as_str().parse().unwrap());
-        let js_payload = SigDefinition {
-            sig_code: format!(
-                "
-                const {sig_fn_mangler_name}={sig_manglers};
-                const sig=function({sig_fn_args}){sig_fn_code};"
-            ),
-            ncode_code: format!("const ncode=function({ncode_fn_args}){ncode_fn_code};"),
-            js_sts,
-        };
+        let js_payload = parse_js_functions(&player_js)
+            .with_context(|| format!("failed parsing js player {}", script_hash))?;
 
         ctx.cache
             .set(WEB_JS_FUNCTIONS_POOL, &script_hash, &js_payload)
@@ -335,18 +463,19 @@ impl YoutubeRE {
     #[cfg(feature = "allow_js")]
     async fn handle_sig(
         &self,
-        _ctx: &ExtractionContext,
-        js_payload: SigDefinition,
+        ctx: &ExtractionContext,
+        (js_payload, script_hash): (SigDefinition, &str),
         streaming_data: &mut StreamingData,
     ) -> Result<()> {
-        let mut js_context = JSContext::default();
-        js_context
-            .eval(&js_payload.sig_code)
-            .map_err(|e| Error::msg(e.to_string(&mut js_context).unwrap().to_string()))?;
-        js_context
-            .eval(&js_payload.ncode_code)
-            .map_err(|e| Error::msg(e.to_string(&mut js_context).unwrap().to_string()))?;
+        js::with_solver(
+            (script_hash, JsEngineKind::from_context(ctx)?),
+            &[&js_payload.sig_code, &js_payload.ncode_code],
+            |solver| Self::handle_sig_with(solver, streaming_data),
+        )
+    }
 
+    #[cfg(feature = "allow_js")]
+    fn handle_sig_with(solver: &mut js::Solver, streaming_data: &mut StreamingData) -> Result<()> {
         for formats in [
             streaming_data.formats.as_mut(),
             streaming_data.adaptive_formats.as_mut(),
@@ -358,27 +487,20 @@ impl YoutubeRE {
             for format in formats {
                 let mut url = if let Some(fmt_url_) = &format.url {
                     // format.url = ncode(fmt_url)
-                    Url::parse(fmt_url_).unwrap()
+                    Url::parse(fmt_url_)?
                 } else if let Some(sc) = &format.signature_cipher {
                     let args = QString::from(sc.as_str());
-                    let sc_url_s = args.get("url").unwrap();
-                    let mut sc_url = Url::parse(sc_url_s).unwrap();
+                    let sc_url_s = args
+                        .get("url")
+                        .ok_or_else(|| anyhow!("no url in signatureCipher"))?;
+                    let mut sc_url = Url::parse(sc_url_s)?;
                     if let Some(s) = args.get("s") {
                         // the param name under which the processed signature should be available
                         // format.url = ncode(decipher(sc))
-                        let sig_r = js_context
-                            .eval(format!("sig({})", serde_json::to_string(s).unwrap()))
-                            .map_err(|e| {
-                                Error::msg(e.to_string(&mut js_context).unwrap().to_string())
-                            })?
-                            .to_string(&mut js_context)
-                            .map_err(|e| {
-                                Error::msg(e.to_string(&mut js_context).unwrap().to_string())
-                            })?
-                            .to_string();
+                        let sig_r = solver.call("sig", s)?;
 
                         let signature_param = args.get("sp").unwrap_or("signature");
-                        let mut sc_url_params = QString::from(sc_url.query().unwrap());
+                        let mut sc_url_params = QString::from(sc_url.query().unwrap_or_default());
                         sc_url_params.add_pair((signature_param, sig_r));
                         sc_url.set_query(Some(&sc_url_params.to_string()));
                         sc_url
@@ -389,17 +511,9 @@ impl YoutubeRE {
                     bail!("neither of url or signatureCipher found in format");
                 };
 
-                let mut url_params = QString::from(url.query().unwrap());
+                let mut url_params = QString::from(url.query().unwrap_or_default());
                 if let Some(original_n) = url_params.get("n") {
-                    let ncode_r = js_context
-                        .eval(format!(
-                            "ncode({})",
-                            serde_json::to_string(original_n).unwrap()
-                        ))
-                        .map_err(|e| Error::msg(e.to_string(&mut js_context).unwrap().to_string()))?
-                        .to_string(&mut js_context)
-                        .map_err(|e| Error::msg(e.to_string(&mut js_context).unwrap().to_string()))?
-                        .to_string();
+                    let ncode_r = solver.call("ncode", original_n)?;
                     url_params = QString::new(
                         url_params
                             .into_pairs()
@@ -427,7 +541,10 @@ impl YoutubeRE {
     ) -> Result<response::Player> {
         if player.playability_status.status == "OK" {
             if let Some(streaming_data) = player.streaming_data.as_mut() {
-                match self.handle_sig(ctx, sig_definition, streaming_data).await {
+                match self
+                    .handle_sig(ctx, (sig_definition, script_hash), streaming_data)
+                    .await
+                {
                     Ok(_) => {}
                     Err(e) => {
                         player.streaming_data = None;
@@ -451,8 +568,6 @@ impl YoutubeRE {
         id: &str,
         client: &request::Client<'_>,
     ) -> Result<((Url, String), Option<u32>, Option<response::Player>)> {
-        use reytan_extractor_api::{header, Request};
-
         let is_embed = client.name.ends_with("_embedded");
         let mut request = Request::get(format!(
             "https://{}/{}{id}",
@@ -469,27 +584,27 @@ impl YoutubeRE {
             )
             .await?;
 
-        let script_match = WEB_JS_URL_RE.captures(&webpage).unwrap();
-        let script_path = script_match.get(1).unwrap().as_str();
-        let script_hash = script_match.get(2).unwrap().as_str();
-        let script_url = Url::join(
-            &Url::parse(&format!("https://{}/", client.host)).unwrap(),
-            script_path,
-        )
-        .unwrap();
+        let script_match = WEB_JS_URL_RE
+            .captures(&webpage)
+            .ok_or_else(|| anyhow!("no player JS URL on the webpage"))?;
+        let script_path = &script_match[1];
+        let script_hash = &script_match[2];
+        let script_url = Url::parse(&format!("https://{}/", client.host))?.join(script_path)?;
 
         let sts = WEB_STS_RE
             .captures(&webpage)
-            .map(|c| c.get(1).unwrap().as_str().parse().unwrap());
+            .and_then(|c| c[1].parse().ok());
 
-        let player: Option<response::Player> =
-            WEB_PLAYER_RE.captures(&webpage).map(|player_json| {
-                serde_json::Deserializer::from_str(player_json.get(1).unwrap().as_str())
+        let player: Option<response::Player> = WEB_PLAYER_RE
+            .captures(&webpage)
+            .map(|player_json| {
+                serde_json::Deserializer::from_str(&player_json[1])
                     .into_iter()
                     .next()
-                    .unwrap()
-                    .unwrap()
-            });
+                    .ok_or_else(|| anyhow!("empty player response on the webpage"))?
+                    .context("failed parsing player response on the webpage")
+            })
+            .transpose()?;
 
         Ok(((script_url, script_hash.to_string()), sts, player))
     }
@@ -507,7 +622,8 @@ impl YoutubeRE {
             .get_js_functions(ctx, (script_url, &script_hash))
             .await?;
 
-        let mut player = maybe_player.unwrap();
+        let mut player =
+            maybe_player.ok_or_else(|| anyhow!("no player response on the webpage"))?;
 
         player = self
             .decode_formats(ctx, client, (js_payload, &script_hash), player)
@@ -530,12 +646,12 @@ impl YoutubeRE {
             .await?;
 
         // the player on the webpage is WEB_EMBEDDED client, might not the one we want
-        let mut player = if client.name != clients::WEB_EMBEDDED.name || maybe_player.is_none() {
-            self.yti_player(ctx, id, client, sts_web.or(js_payload.js_sts))
-                .await
-                .unwrap()
-        } else {
-            maybe_player.unwrap()
+        let mut player = match maybe_player {
+            Some(player) if client.name == clients::WEB_EMBEDDED.name => player,
+            _ => {
+                self.yti_player(ctx, id, client, sts_web.or(js_payload.js_sts))
+                    .await?
+            }
         };
 
         player = self
@@ -589,7 +705,7 @@ impl YoutubeRE {
 
     async fn attempt_client<'a>(
         &self,
-        players: &mut HashSet<response::Player>,
+        players: &mut Vec<response::Player>,
         attempted_clients: &mut HashSet<&'a str>,
         ctx: &ExtractionContext,
         id: &str,
@@ -597,8 +713,11 @@ impl YoutubeRE {
     ) {
         if attempted_clients.insert(&client.name) {
             let result = self.extract_player(ctx, id, client).await;
-            if let Ok(player) = result {
-                players.insert(player);
+            if let Ok(mut player) = result {
+                if let Some(streaming_data) = player.streaming_data.as_mut() {
+                    streaming_data.set_source_client(client.name);
+                }
+                players.push(player);
             }
         }
     }
@@ -606,148 +725,269 @@ impl YoutubeRE {
     async fn get_players(
         &self,
         ctx: &ExtractionContext,
-        url: &Url,
+        id: &str,
         wanted: &Extractable,
-    ) -> Result<(response::Player, HashSet<response::Player>)> {
-        let id = self.get_id(url);
-        let mut players = HashSet::new();
-        let mut attempted_clients = HashSet::new();
-
-        self.attempt_client(
-            &mut players,
-            &mut attempted_clients,
-            ctx,
-            &id,
-            &match () {
-                // WEB gets more metadata at the expense of JS signatures existing
-                _ if wanted.metadata == ExtractLevel::Extended => clients::WEB,
-                // IOS hates open media standards and returns avc1/mp4a only
-                _ if wanted.playback == ExtractLevel::None => clients::IOS,
-                // simple choice
-                _ => clients::ANDROID,
-            },
-        )
-        .await;
-
-        // if the previous fetching of playback failed, try ANDROID,
-        // also ignore if the error was agegate
-        if players.len() == 0
-            || (wanted.playback != ExtractLevel::None
-                && !players.iter().all(|p| {
-                    [PlayabilityCategory::Ok, PlayabilityCategory::AgeGate]
-                        .into_iter()
-                        .any(|s| {
-                            Some(&s) == PLAYABILITY_STATUS_TYPE.get(&p.playability_status.status)
-                        })
-                }))
-        {
-            self.attempt_client(
-                &mut players,
-                &mut attempted_clients,
-                ctx,
-                &id,
-                &clients::ANDROID,
-            )
-            .await;
-        }
-
-        // TV_EMBEDDED is known to get age-gated videos without logging in: https://github.com/yt-dlp/yt-dlp/pull/3233
-        if cfg!(feature = "allow_js")
-            && wanted.playback != ExtractLevel::None
-            && players.iter().any(|p| {
-                PLAYABILITY_STATUS_TYPE.get(&p.playability_status.status)
-                    == Some(&PlayabilityCategory::AgeGate)
-            })
-        {
+    ) -> Result<(response::Player, Vec<response::Player>)> {
+        let options = PlayerOptions::from_context(ctx)?;
+        // in the order of attempting, so the preferred clients come first
+        let mut players = Vec::new();
+        // skipped clients are treated as if they were attempted already
+        let mut attempted_clients: HashSet<&str> =
+            options.skip_clients.iter().map(|c| c.name).collect();
+
+        if let Some(player_clients) = &options.clients {
+            for client in player_clients {
+                if !options.merge_formats && has_playable(&players) {
+                    break;
+                }
+                self.attempt_client(&mut players, &mut attempted_clients, ctx, id, client)
+                    .await;
+            }
+        } else {
             self.attempt_client(
                 &mut players,
                 &mut attempted_clients,
                 ctx,
-                &id,
-                &clients::TV_EMBEDDED,
+                id,
+                &match () {
+                    // WEB gets more metadata at the expense of JS signatures existing
+                    _ if wanted.metadata == ExtractLevel::Extended => clients::WEB,
+                    // IOS hates open media standards and returns avc1/mp4a only
+                    _ if wanted.playback == ExtractLevel::None => clients::IOS,
+                    // simple choice
+                    _ => clients::ANDROID,
+                },
             )
             .await;
-        }
 
-        // if live, iOS has unique formats: https://github.com/TeamNewPipe/NewPipeExtractor/issues/680
-        if wanted.playback == ExtractLevel::Extended
-            && players.iter().any(|p| p.video_details.is_live)
-        {
-            self.attempt_client(
-                &mut players,
-                &mut attempted_clients,
-                ctx,
-                &id,
-                &clients::IOS,
-            )
-            .await;
-        }
+            // if the previous fetching of playback failed, try ANDROID,
+            // also ignore if the error was agegate
+            if players.len() == 0
+                || (wanted.playback != ExtractLevel::None
+                    && !players.iter().all(|p| {
+                        [PlayabilityCategory::Ok, PlayabilityCategory::AgeGate]
+                            .into_iter()
+                            .any(|s| {
+                                Some(&s)
+                                    == PLAYABILITY_STATUS_TYPE.get(&p.playability_status.status)
+                            })
+                    }))
+            {
+                self.attempt_client(
+                    &mut players,
+                    &mut attempted_clients,
+                    ctx,
+                    id,
+                    &clients::ANDROID,
+                )
+                .await;
+            }
 
-        match players.clone().into_iter().reduce(|mut prev, cur| {
-            prev.microformat = prev.microformat.or(cur.microformat);
-            if prev.playability_status.status != "OK" && cur.playability_status.status == "OK" {
-                prev.playability_status.status = "OK".to_string();
+            // TV_EMBEDDED is known to get age-gated videos without logging in: https://github.com/yt-dlp/yt-dlp/pull/3233
+            if cfg!(feature = "allow_js")
+                && wanted.playback != ExtractLevel::None
+                && players.iter().any(|p| {
+                    PLAYABILITY_STATUS_TYPE.get(&p.playability_status.status)
+                        == Some(&PlayabilityCategory::AgeGate)
+                })
+            {
+                self.attempt_client(
+                    &mut players,
+                    &mut attempted_clients,
+                    ctx,
+                    id,
+                    &clients::TV_EMBEDDED,
+                )
+                .await;
             }
-            if let Some(prev_streaming_data) = prev.streaming_data.as_mut() {
-                if let Some(cur_streaming_data) = &cur.streaming_data {
-                    let mut available_formats = HashSet::new();
-
-                    let mut merge_formats =
-                        |prev_formats: &Vec<Format>, cur_formats: &Vec<Format>| {
-                            prev_formats
-                                .into_iter()
-                                .chain(cur_formats)
-                                .map(Format::clone)
-                                .filter(|f| available_formats.insert(f.itag))
-                                .collect()
-                        };
-
-                    if let Some((prev_formats, cur_formats)) = prev_streaming_data
-                        .adaptive_formats
-                        .as_ref()
-                        .zip(cur_streaming_data.adaptive_formats.as_ref())
-                    {
-                        prev_streaming_data.adaptive_formats =
-                            Some(merge_formats(prev_formats, cur_formats));
-                    } else if cur_streaming_data.adaptive_formats.is_some() {
-                        prev_streaming_data.adaptive_formats =
-                            cur_streaming_data.adaptive_formats.clone();
-                    }
 
-                    if let Some((prev_formats, cur_formats)) = prev_streaming_data
-                        .formats
-                        .as_ref()
-                        .zip(cur_streaming_data.formats.as_ref())
-                    {
-                        prev_streaming_data.formats =
-                            Some(merge_formats(prev_formats, cur_formats));
-                    } else if cur_streaming_data.formats.is_some() {
-                        prev_streaming_data.formats = cur_streaming_data.formats.clone();
-                    }
+            // if live, iOS has unique formats: https://github.com/TeamNewPipe/NewPipeExtractor/issues/680
+            if options.merge_formats
+                && wanted.playback == ExtractLevel::Extended
+                && players.iter().any(|p| p.video_details.is_live)
+            {
+                self.attempt_client(&mut players, &mut attempted_clients, ctx, id, &clients::IOS)
+                    .await;
+            }
+        }
 
-                    if let Some((prev_formats, cur_formats)) = prev_streaming_data
-                        .hls_formats
-                        .as_ref()
-                        .zip(cur_streaming_data.hls_formats.as_ref())
-                    {
-                        prev_streaming_data.formats =
-                            Some(merge_formats(prev_formats, cur_formats));
-                    } else if cur_streaming_data.formats.is_some() {
-                        prev_streaming_data.hls_formats = cur_streaming_data.hls_formats.clone();
+        // without merging, take the formats from a single client that has got them
+        if !options.merge_formats {
+            if let Some(playable) = players.iter().position(is_playable) {
+                for (i, player) in players.iter_mut().enumerate() {
+                    if i != playable {
+                        player.streaming_data = None;
                     }
                 }
-            } else {
-                prev.streaming_data = cur.streaming_data;
             }
+        }
 
-            prev
-        }) {
+        match merge_players(&players) {
             Some(player) => Ok((player, players)),
             None => bail!("no players fetched successfully"),
         }
     }
 }
 
+/// Client selection, set with the extractor args:
+/// `youtube:player_client=android,web;player_skip_client=ios;merge_formats=false`
+struct PlayerOptions {
+    /// clients to try, in order (instead of the default strategy)
+    clients: Option<Vec<&'static request::Client<'static>>>,
+    skip_clients: Vec<&'static request::Client<'static>>,
+    /// whether to fetch and merge the formats from more than one client
+    merge_formats: bool,
+}
+
+impl PlayerOptions {
+    fn from_context(ctx: &ExtractionContext) -> Result<PlayerOptions> {
+        let get_clients = |key: &str| -> Result<Option<Vec<&'static request::Client<'static>>>> {
+            ctx.extractor_args
+                .get("youtube", key)
+                .map(|names| {
+                    names
+                        .iter()
+                        .map(|name| {
+                            clients::by_name(name).ok_or_else(|| {
+                                anyhow!(
+                                    "unknown youtube client in {}: {} (known: {})",
+                                    key,
+                                    name,
+                                    clients::ALL.map(|c| c.name).join(", ")
+                                )
+                            })
+                        })
+                        .collect()
+                })
+                .transpose()
+        };
+        Ok(PlayerOptions {
+            clients: get_clients("player_client")?.filter(|c| !c.is_empty()),
+            skip_clients: get_clients("player_skip_client")?.unwrap_or_default(),
+            merge_formats: ctx
+                .extractor_args
+                .get_bool("youtube", "merge_formats")?
+                .unwrap_or(true),
+        })
+    }
+}
+
+fn is_playable(player: &response::Player) -> bool {
+    player.playability_status.status == "OK" && player.streaming_data.is_some()
+}
+
+fn has_playable(players: &[response::Player]) -> bool {
+    players.iter().any(is_playable)
+}
+
+/// Merges the player responses into one, keeping every distinct format.
+/// On duplicates, the format from the earlier player (client attempted first) is kept
+fn merge_players(players: &[response::Player]) -> Option<response::Player> {
+    players.iter().cloned().reduce(|mut prev, cur| {
+        prev.microformat = prev.microformat.or(cur.microformat);
+        if prev.playability_status.status != "OK" && cur.playability_status.status == "OK" {
+            prev.playability_status.status = "OK".to_string();
+        }
+        prev.streaming_data = match (prev.streaming_data, cur.streaming_data) {
+            (Some(prev_streaming_data), Some(cur_streaming_data)) => Some(StreamingData {
+                formats: merge_formats(prev_streaming_data.formats, cur_streaming_data.formats),
+                adaptive_formats: merge_formats(
+                    prev_streaming_data.adaptive_formats,
+                    cur_streaming_data.adaptive_formats,
+                ),
+                hls_formats: merge_formats(
+                    prev_streaming_data.hls_formats,
+                    cur_streaming_data.hls_formats,
+                ),
+                dash_manifest_url: prev_streaming_data
+                    .dash_manifest_url
+                    .or(cur_streaming_data.dash_manifest_url),
+                hls_manifest_url: prev_streaming_data
+                    .hls_manifest_url
+                    .or(cur_streaming_data.hls_manifest_url),
+            }),
+            (prev_streaming_data, cur_streaming_data) => prev_streaming_data.or(cur_streaming_data),
+        };
+        prev
+    })
+}
+
+/// Same itag is not enough to consider formats duplicates - DRC and other language variants
+/// of the audio share the itag, see [Format::merge_key]
+fn merge_formats(
+    prev_formats: Option<Vec<Format>>,
+    cur_formats: Option<Vec<Format>>,
+) -> Option<Vec<Format>> {
+    match (prev_formats, cur_formats) {
+        (Some(prev_formats), Some(cur_formats)) => {
+            let mut available_formats = HashSet::new();
+            Some(
+                prev_formats
+                    .into_iter()
+                    .chain(cur_formats)
+                    .filter(|f| available_formats.insert(f.merge_key()))
+                    .collect(),
+            )
+        }
+        (prev_formats, cur_formats) => prev_formats.or(cur_formats),
+    }
+}
+
+/// The chapters that YouTube makes from the description: the lines starting with a timestamp,
+/// at least 3 of them in order, the first one at 0:00
+fn parse_chapters(description: &str, duration: Duration) -> Vec<Chapter> {
+    let marks: Vec<(Duration, &str)> = description
+        .lines()
+        .filter_map(|line| {
+            let (timestamp, title) = line.trim().split_once(char::is_whitespace)?;
+            let start = parse_clock_duration(timestamp)?;
+            Some((
+                start,
+                title.trim_start_matches(['-', '–', ':', '|', ' ']).trim(),
+            ))
+        })
+        .collect();
+    let is_valid = marks.len() >= 3
+        && marks[0].0.is_zero()
+        && marks.windows(2).all(|m| m[0].0 < m[1].0)
+        && marks[marks.len() - 1].0 < duration;
+    if !is_valid {
+        return Vec::new();
+    }
+    marks
+        .iter()
+        .enumerate()
+        .map(|(i, (start, title))| Chapter {
+            start: *start,
+            end: marks.get(i + 1).map_or(duration, |(next, _)| *next),
+            title: title.to_string(),
+        })
+        .collect()
+}
+
+/// Planned start of the upcoming stream or premiere
+fn scheduled_start_time(player: &response::Player) -> Option<chrono::DateTime<Utc>> {
+    if !player.video_details.is_upcoming {
+        return None;
+    }
+    player
+        .playability_status
+        .scheduled_start_time()
+        .and_then(|t| Utc.timestamp_opt(t, 0).single())
+        .or_else(|| {
+            player
+                .microformat
+                .as_ref()?
+                .player_microformat_renderer
+                .as_ref()?
+                .live_broadcast_details
+                .as_ref()?
+                .start_timestamp
+                .as_deref()
+                .and_then(parse_datetime)
+        })
+}
+
 #[async_trait]
 impl RecordingExtractor for YoutubeRE {
     async fn extract_recording(
@@ -756,9 +996,55 @@ impl RecordingExtractor for YoutubeRE {
         url: &Url,
         wanted: &Extractable,
     ) -> Result<Extraction> {
-        let (player, players) = self.get_players(ctx, url, wanted).await?;
+        let (id, clip) = match parse_url(url)? {
+            YoutubeURL::Video(id) => (id, None),
+            YoutubeURL::Clip(clip_id) => {
+                let (id, clip) = self.resolve_clip(ctx, &clip_id).await?;
+                (id, Some(clip))
+            }
+        };
+        let (player, players) = self.get_players(ctx, &id, wanted).await?;
+        let live_status = player.video_details.live_status();
+        let live_details = player.video_details.live_details();
+        let scheduled_start_time = scheduled_start_time(&player);
+        let duration = player
+            .video_details
+            .length_seconds
+            // on livestreams (and the upcoming ones), duration always equals 0
+            .filter(|_| !matches!(live_status, LiveStatus::IsLive | LiveStatus::IsUpcoming))
+            .map(Duration::from_secs);
+        let thumbnails = match wanted.thumbnails {
+            ExtractLevel::None => Vec::new(),
+            _ => player
+                .video_details
+                .thumbnail
+                .clone()
+                .map(Vec::from)
+                .unwrap_or_default(),
+        };
+        let chapters = match (
+            wanted.chapters,
+            player.video_details.short_description.as_deref(),
+            duration,
+        ) {
+            (ExtractLevel::None, _, _) => Vec::new(),
+            (_, Some(description), Some(duration)) => parse_chapters(description, duration),
+            _ => Vec::new(),
+        };
         let fmts = if let Some(stream) = player.streaming_data {
-            parse_formats(stream)
+            let manifest_fmts = if wanted.playback == ExtractLevel::Extended {
+                self.expand_manifests(ctx, &stream).await
+            } else {
+                Vec::new()
+            };
+            let mut fmts = parse_formats(stream);
+            // the HLS formats from ios are the same as in the manifest
+            for fmt in manifest_fmts {
+                if !fmts.iter().any(|f| f.details.id == fmt.details.id) {
+                    fmts.push(fmt);
+                }
+            }
+            fmts
         } else {
             Vec::new()
         };
@@ -767,56 +1053,21 @@ impl RecordingExtractor for YoutubeRE {
                 id: player.video_details.video_id,
                 title: player.video_details.title,
                 description: player.video_details.short_description,
-                duration: player
-                    .video_details
-                    .length_seconds
-                    // on livestreams, duration always equals 0
-                    .filter(|_| !player.video_details.is_live)
-                    .map(Duration::from_secs),
+                duration,
                 view_count: player.video_details.view_count,
-                live_status: if player.video_details.is_live {
-                    Some(LiveStatus::IsLive)
-                } else if player.video_details.is_live_content {
-                    Some(LiveStatus::WasLive)
-                } else {
-                    Some(LiveStatus::NotLive)
-                },
+                live_status: Some(live_status),
+                scheduled_start_time,
+                live_details,
                 published_time: player
                     .microformat
                     .as_ref()
-                    .map(|w| {
-                        if let Some(m) = &w.player_microformat_renderer {
-                            &m.publish_date
-                        } else if let Some(m) = &w.microformat_data_renderer {
-                            &m.publish_date
-                        } else {
-                            &None
-                        }
-                    })
-                    .map(|o| o.as_ref())
-                    .flatten()
-                    .map(|d| chrono::DateTime::parse_from_rfc2822(&d))
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
+                    .and_then(|m| m.publish_date())
+                    .and_then(parse_date),
                 created_time: player
                     .microformat
                     .as_ref()
-                    .map(|w| {
-                        if let Some(m) = &w.player_microformat_renderer {
-                            &m.upload_date
-                        } else if let Some(m) = &w.microformat_data_renderer {
-                            &m.upload_date
-                        } else {
-                            &None
-                        }
-                    })
-                    .map(|o| o.as_ref())
-                    .flatten()
-                    .map(|d| chrono::DateTime::parse_from_rfc2822(&d))
-                    .map(Result::ok)
-                    .flatten()
-                    .map(chrono::DateTime::<Utc>::from),
+                    .and_then(|m| m.upload_date())
+                    .and_then(parse_date),
                 age_limit: players
                     .iter()
                     .any(|p| {
@@ -825,12 +1076,18 @@ impl RecordingExtractor for YoutubeRE {
                     })
                     .then_some(18)
                     .or(Some(0)),
+                clip,
+                thumbnails,
+                chapters,
                 ..Default::default()
             },
             established_formats: fmts,
             established_subtitles: player
                 .captions
-                .map(|w| w.player_captions_tracklist_renderer.into())
+                .map(|w| {
+                    w.player_captions_tracklist_renderer
+                        .into_subtitles(&wanted.subtitles)
+                })
                 .unwrap_or_else(|| Vec::new()),
             ..Default::default()
         })
@@ -839,14 +1096,358 @@ impl RecordingExtractor for YoutubeRE {
 
 #[cfg(test)]
 mod tests {
+    use reytan_extractor_api::chrono::TimeZone;
+    use reytan_extractor_api::parse::parse_date;
     use reytan_extractor_api::url::Url;
     use reytan_extractor_api::{
-        ExtractLevel, Extractable, ExtractionContext, FormatBreed, LiveStatus, MediaFormatURL,
-        RecordingExtractor, URLMatcher,
+        AudioDetails, AudioTrackRole, ExtractLevel, Extractable, ExtractionContext, FormatBreed,
+        LatencyClass, LiveDetails, LiveStatus, MediaFormatURL, RecordingExtractor, SubtitleExt,
+        URLMatcher, Utc,
     };
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    #[cfg(feature = "allow_js")]
+    use super::super::js::JsEngineKind;
+    use super::super::types::request::clients::{self, ANDROID_MUSIC};
+    use super::super::types::{request, response};
+    use super::{merge_players, parse_formats, parse_url, PlayerOptions, YoutubeRE, YoutubeURL};
+
+    /// fixture player response, as if it was returned to the client
+    fn fixture_player(json: &str, client: &request::Client<'_>) -> response::Player {
+        let mut player: response::Player = serde_json::from_str(json).unwrap();
+        player
+            .streaming_data
+            .as_mut()
+            .unwrap()
+            .set_source_client(client.name);
+        player
+    }
 
-    use super::super::types::request::clients::ANDROID_MUSIC;
-    use super::YoutubeRE;
+    #[test]
+    fn test_merge_players() {
+        let android = fixture_player(
+            include_str!("../fixtures/player_android.json"),
+            &clients::ANDROID,
+        );
+        let ios = fixture_player(include_str!("../fixtures/player_ios.json"), &clients::IOS);
+        let player = merge_players(&[android, ios]).unwrap();
+        let streaming_data = player.streaming_data.unwrap();
+
+        let adaptive_formats = streaming_data.adaptive_formats.as_ref().unwrap();
+        // 137 and english 140 are duplicates, french 140 is only in ios
+        assert_eq!(adaptive_formats.len(), 6);
+        assert_eq!(
+            adaptive_formats
+                .iter()
+                .filter(|f| f.itag == 140)
+                .map(|f| f.source_client.as_deref().unwrap())
+                .collect::<Vec<_>>(),
+            vec!["android", "android", "ios"]
+        );
+        // the first client wins on duplicates
+        assert_eq!(
+            adaptive_formats
+                .iter()
+                .find(|f| f.itag == 137)
+                .unwrap()
+                .source_client
+                .as_deref(),
+            Some("android")
+        );
+        // DRC has the same itag
+        assert_eq!(adaptive_formats.iter().filter(|f| f.itag == 251).count(), 2);
+
+        assert_eq!(streaming_data.formats.as_ref().unwrap().len(), 1);
+        assert_eq!(streaming_data.hls_formats.as_ref().unwrap().len(), 1);
+
+        let fmts = parse_formats(streaming_data);
+        let ids: Vec<&str> = fmts.iter().map(|f| f.details.id.as_str()).collect();
+        assert!(ids.contains(&"251-drc"));
+        assert!(ids.contains(&"96-hls"));
+        for fmt in &fmts {
+            let connection = match &fmt.url {
+                MediaFormatURL::HTTP(_, options) => &options.connection,
+                MediaFormatURL::HLS(_, options) => &options.connection,
+                _ => panic!("unexpected format protocol"),
+            };
+            let source = fmt.details.source.as_deref().unwrap();
+            assert_eq!(
+                connection.user_agent.as_deref(),
+                Some(
+                    clients::by_name(source)
+                        .unwrap()
+                        .user_agent
+                        .unwrap_or("okhttp/4.9.3")
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_audio_tracks() {
+        let android = fixture_player(
+            include_str!("../fixtures/player_android.json"),
+            &clients::ANDROID,
+        );
+        let ios = fixture_player(include_str!("../fixtures/player_ios.json"), &clients::IOS);
+        let player = merge_players(&[android, ios]).unwrap();
+        let fmts = parse_formats(player.streaming_data.unwrap());
+        let audio_140: Vec<(&str, AudioDetails)> = fmts
+            .iter()
+            .filter(|f| f.details.id.starts_with("140"))
+            .map(|f| {
+                (
+                    f.details.id.as_str(),
+                    f.details.audio_details.clone().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            audio_140
+                .iter()
+                .map(|(id, audio)| (
+                    *id,
+                    audio.language.as_deref().unwrap(),
+                    audio.track.as_ref().unwrap().role.clone().unwrap()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("140-en-US.4", "en-US", AudioTrackRole::Original),
+                ("140-de-DE.3", "de-DE", AudioTrackRole::Dubbed),
+                ("140-fr-FR.3", "fr-FR", AudioTrackRole::Dubbed),
+            ]
+        );
+        assert_eq!(audio_140[0].1.track.as_ref().unwrap().is_default, true);
+        assert_eq!(audio_140[1].1.track.as_ref().unwrap().is_default, false);
+
+        // single audio track
+        let f251 = fmts.iter().find(|f| f.details.id == "251").unwrap();
+        let audio = f251.details.audio_details.as_ref().unwrap();
+        assert_eq!(audio.track, None);
+        assert_eq!(audio.language, None);
+    }
+
+    #[test]
+    fn test_subtitles_translated() {
+        let captions: response::parts::CaptionsWrapper =
+            serde_json::from_str(include_str!("../fixtures/captions.json")).unwrap();
+        let mut renderer = captions.player_captions_tracklist_renderer;
+        assert_eq!(renderer.original_lang(), Some("ja"));
+
+        let subtitles = renderer.clone().into_subtitles(&ExtractLevel::Basic);
+        // 3 tracks + translations to 3 languages, vtt and ttml
+        assert_eq!(subtitles.len(), (3 + 3) * 2);
+        let original = subtitles
+            .iter()
+            .filter(|s| s.details.is_machine_translated == Some(false))
+            .collect::<Vec<_>>();
+        assert_eq!(original.len(), 3 * 2);
+        assert_eq!(
+            original
+                .iter()
+                .map(|s| (s.details.lang.as_str(), s.details.is_original_lang))
+                .collect::<Vec<_>>(),
+            vec![
+                ("ja", Some(true)),
+                ("ja", Some(true)),
+                ("ja", Some(true)),
+                ("ja", Some(true)),
+                ("en-US", Some(false)),
+                ("en-US", Some(false)),
+            ]
+        );
+
+        let english = subtitles
+            .iter()
+            .find(|s| s.details.lang == "en" && s.details.ext == SubtitleExt::VTT)
+            .unwrap();
+        assert_eq!(english.details.is_machine_translated, Some(true));
+        // translated from the non-automatic japanese captions
+        assert_eq!(english.details.is_machine_generated, Some(false));
+        assert_eq!(english.details.is_original_lang, Some(false));
+        let query = english.url.query_pairs().collect::<HashMap<_, _>>();
+        assert_eq!(query["tlang"], "en");
+        assert_eq!(query["fmt"], "vtt");
+        assert_eq!(query["signature"], "DEF456");
+        assert_eq!(subtitles.iter().any(|s| s.details.lang == "de"), true);
+        // not translating to the source language
+        assert_eq!(
+            subtitles
+                .iter()
+                .filter(|s| s.details.lang == "ja")
+                .all(|s| s.details.is_machine_translated == Some(false)),
+            true
+        );
+
+        // from the default audio track, if there are no automatic captions
+        renderer.caption_tracks.as_mut().unwrap()[0].kind = None;
+        assert_eq!(renderer.original_lang(), Some("ja"));
+        renderer.default_audio_track_index = None;
+        assert_eq!(renderer.original_lang(), None);
+        let subtitles = renderer.clone().into_subtitles(&ExtractLevel::Basic);
+        assert_eq!(
+            subtitles
+                .iter()
+                .all(|s| s.details.is_original_lang.is_none()),
+            true
+        );
+        // still translated, from the first translatable track
+        assert_eq!(subtitles.len(), (3 + 3) * 2);
+
+        for track in renderer.caption_tracks.as_mut().unwrap() {
+            track.is_translatable = false;
+        }
+        let subtitles = renderer.into_subtitles(&ExtractLevel::Extended);
+        assert_eq!(subtitles.len(), 3 * 6);
+    }
+
+    #[test]
+    fn test_upcoming_player() {
+        let mut player: response::Player =
+            serde_json::from_str(include_str!("../fixtures/player_upcoming.json")).unwrap();
+        assert_eq!(player.video_details.live_status(), LiveStatus::IsUpcoming);
+        assert_eq!(
+            super::PLAYABILITY_STATUS_TYPE.get(&player.playability_status.status)
+                == Some(&super::PlayabilityCategory::NotYet),
+            true
+        );
+        assert_eq!(
+            player.video_details.live_details(),
+            Some(LiveDetails {
+                dvr_enabled: true,
+                latency_class: Some(LatencyClass::Low),
+            })
+        );
+        let expected = Utc.with_ymd_and_hms(2023, 3, 10, 16, 0, 0).single();
+        assert_eq!(super::scheduled_start_time(&player), expected);
+        // from the microformat, if not in the playability status
+        player.playability_status.live_streamability = None;
+        assert_eq!(super::scheduled_start_time(&player), expected);
+        let microformat = player.microformat.as_ref().unwrap();
+        assert_eq!(
+            microformat.publish_date().and_then(parse_date),
+            Utc.with_ymd_and_hms(2023, 3, 1, 17, 12, 45).single()
+        );
+        assert_eq!(microformat.upload_date(), microformat.publish_date());
+
+        player.video_details.is_upcoming = false;
+        player.video_details.is_live = true;
+        assert_eq!(player.video_details.live_status(), LiveStatus::IsLive);
+        assert_eq!(super::scheduled_start_time(&player), None);
+        player.video_details.is_live = false;
+        player.video_details.is_post_live_dvr = true;
+        assert_eq!(player.video_details.live_status(), LiveStatus::PostLive);
+        player.video_details.is_post_live_dvr = false;
+        assert_eq!(player.video_details.live_status(), LiveStatus::WasLive);
+        assert_eq!(player.video_details.live_details(), None);
+    }
+
+    #[test]
+    fn test_merge_players_hls() {
+        let ios = fixture_player(include_str!("../fixtures/player_ios.json"), &clients::IOS);
+        let mut ios_live = ios.clone();
+        let hls_formats = ios_live
+            .streaming_data
+            .as_mut()
+            .unwrap()
+            .hls_formats
+            .as_mut()
+            .unwrap();
+        hls_formats[0].itag = 95;
+        hls_formats[0].source_client = Some(clients::IOS_MUSIC.name.to_string());
+
+        let player = merge_players(&[ios, ios_live]).unwrap();
+        let streaming_data = player.streaming_data.unwrap();
+        assert_eq!(
+            streaming_data
+                .hls_formats
+                .unwrap()
+                .into_iter()
+                .map(|f| (f.itag, f.source_client.unwrap()))
+                .collect::<Vec<_>>(),
+            vec![(96, "ios".to_string()), (95, "ios_music".to_string())]
+        );
+        // not polluted with the hls formats
+        assert_eq!(streaming_data.formats, None);
+        assert_eq!(streaming_data.adaptive_formats.unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_format_connection_options() {
+        let web = fixture_player(include_str!("../fixtures/player_ios.json"), &clients::WEB);
+        let fmts = parse_formats(web.streaming_data.unwrap());
+        match &fmts[0].url {
+            MediaFormatURL::HTTP(_, options) => {
+                assert_eq!(
+                    options.connection.user_agent.as_deref(),
+                    clients::WEB.user_agent
+                );
+                assert!(options
+                    .connection
+                    .headers
+                    .contains(&("origin".to_string(), "https://www.youtube.com".to_string())));
+            }
+            _ => panic!("expected a HTTP format"),
+        }
+        assert_eq!(fmts[0].details.source.as_deref(), Some("web"));
+    }
+
+    #[test]
+    fn test_player_options() {
+        let mut ctx = ExtractionContext::new().unwrap();
+        let options = PlayerOptions::from_context(&ctx).unwrap();
+        assert!(options.clients.is_none());
+        assert_eq!(options.merge_formats, true);
+
+        ctx.extractor_args
+            .parse_and_add("youtube:player_client=ios,web_embedded;player_skip_client=android;merge_formats=false")
+            .unwrap();
+        let options = PlayerOptions::from_context(&ctx).unwrap();
+        assert_eq!(
+            options
+                .clients
+                .unwrap()
+                .iter()
+                .map(|c| c.name)
+                .collect::<Vec<_>>(),
+            vec![clients::IOS.name, clients::WEB_EMBEDDED.name]
+        );
+        assert_eq!(
+            options
+                .skip_clients
+                .iter()
+                .map(|c| c.name)
+                .collect::<Vec<_>>(),
+            vec![clients::ANDROID.name]
+        );
+        assert_eq!(options.merge_formats, false);
+
+        ctx.extractor_args
+            .parse_and_add("youtube:player_client=netscape")
+            .unwrap();
+        assert!(PlayerOptions::from_context(&ctx).is_err());
+    }
+
+    #[tokio::test]
+    async fn do_extract_with_player_client() {
+        let mut ctx = ExtractionContext::new().unwrap();
+        ctx.extractor_args
+            .parse_and_add("youtube:player_client=ios;merge_formats=false")
+            .unwrap();
+        let response = YoutubeRE {}
+            .extract_recording(
+                &ctx,
+                &Url::parse("https://www.youtube.com/watch?v=dQw4w9WgXcQ").unwrap(),
+                &Extractable {
+                    playback: ExtractLevel::Extended,
+                    ..Default::default()
+                },
+            )
+            .await
+            .expect("extracted player");
+        assert!(response.established_formats.len() > 0);
+    }
 
     #[tokio::test]
     async fn do_yti_player_protected() {
@@ -967,6 +1568,7 @@ mod tests {
                 &Extractable {
                     metadata: ExtractLevel::Basic,
                     playback: ExtractLevel::Basic,
+                    subtitles: ExtractLevel::Extended,
                     ..Default::default()
                 },
             )
@@ -976,8 +1578,42 @@ mod tests {
         assert_eq!(meta.title, "稲葉曇『ラグトレイン』Vo. 歌愛ユキ");
         assert_eq!(meta.live_status, Some(LiveStatus::NotLive));
         let subtitles = response.established_subtitles;
+        let original = subtitles
+            .iter()
+            .filter(|s| s.details.is_machine_translated == Some(false))
+            .count();
         // 3 languages, 6 formats
-        assert_eq!(subtitles.len(), 3 * 6);
+        assert_eq!(original, 3 * 6);
+        assert_eq!(
+            subtitles
+                .iter()
+                .any(|s| s.details.is_machine_translated == Some(true) && s.details.lang == "en"),
+            true
+        );
+    }
+
+    #[tokio::test]
+    async fn do_extract_subtitles_basic() {
+        let youtube = YoutubeRE {};
+        let response = youtube
+            .extract_recording(
+                &ExtractionContext::new().unwrap(),
+                &Url::parse("https://www.youtube.com/watch?v=UnIhRpIT7nc").unwrap(),
+                &Extractable {
+                    metadata: ExtractLevel::Basic,
+                    subtitles: ExtractLevel::Basic,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let subtitles = response.established_subtitles;
+        let original = subtitles
+            .iter()
+            .filter(|s| s.details.is_machine_translated == Some(false))
+            .count();
+        // 3 languages, only vtt and ttml
+        assert_eq!(original, 3 * 2);
     }
 
     #[test]
@@ -1012,6 +1648,324 @@ mod tests {
         assert_eq!(url_match, true);
     }
 
+    #[tokio::test]
+    async fn do_extract_clip() {
+        let youtube = YoutubeRE {};
+        let response = youtube
+            .extract_recording(
+                &ExtractionContext::new().unwrap(),
+                &Url::parse("https://www.youtube.com/clip/UgytZKpehg-hEMBSn3F4AaABCQ").unwrap(),
+                &Extractable {
+                    metadata: ExtractLevel::Basic,
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        let clip = response.metadata.clip.expect("clip section");
+        assert!(clip.start < clip.end);
+    }
+
+    #[test]
+    fn test_url_match_table() {
+        let youtube = YoutubeRE {};
+        for (url, expected) in [
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ", true),
+            ("https://m.youtube.com/watch?feature=share&v=dQw4w9WgXcQ", true),
+            ("https://music.youtube.com/watch?v=dQw4w9WgXcQ", true),
+            ("https://www.youtube.com/live/jfKfPfyJRdk?feature=share", true),
+            ("https://www.youtube.com/embed/dQw4w9WgXcQ?start=10", true),
+            ("https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ", true),
+            ("https://youtube-nocookie.com/embed/dQw4w9WgXcQ", true),
+            ("https://www.youtube.com/v/dQw4w9WgXcQ", true),
+            ("https://www.youtube.com/clip/UgytZKpehg-hEMBSn3F4AaABCQ", true),
+            ("https://www.youtube.com/attribution_link?a=JdfC0C9V6ZI&u=%2Fwatch%3Fv%3DEhxJLojIE_o%26feature%3Dshare", true),
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr", true),
+            ("https://youtu.be/dQw4w9WgXcQ?t=5", true),
+            ("http://y2u.be/dQw4w9WgXcQ", true),
+            ("https://www.youtube.com/watch", false),
+            ("https://www.youtube.com/watch?v=dQw4w9", false),
+            ("https://www.youtube.com/embed/", false),
+            ("https://www.youtube.com/clip/", false),
+            ("https://www.youtube.com/attribution_link?a=JdfC0C9V6ZI", false),
+            ("https://www.youtube.com/@LinusTechTips", false),
+            ("https://www.youtube.com/playlist?list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr", false),
+            ("https://www.youtube-nocookie.com/watch?v=dQw4w9WgXcQ", false),
+            ("https://youtu.be/", false),
+            ("https://example.com/watch?v=dQw4w9WgXcQ", false),
+            ("ftp://www.youtube.com/watch?v=dQw4w9WgXcQ", false),
+            ("data:text/plain,youtube", false),
+        ] {
+            assert_eq!(
+                youtube.match_extractor(&Url::parse(url).unwrap()),
+                expected,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_url() {
+        for (url, expected) in [
+            (
+                "https://www.youtube.com/shorts/dQw4w9WgXcQ",
+                YoutubeURL::Video("dQw4w9WgXcQ".to_string()),
+            ),
+            (
+                "https://www.youtube.com/live/jfKfPfyJRdk?feature=share",
+                YoutubeURL::Video("jfKfPfyJRdk".to_string()),
+            ),
+            (
+                "https://www.youtube-nocookie.com/embed/dQw4w9WgXcQ?start=10",
+                YoutubeURL::Video("dQw4w9WgXcQ".to_string()),
+            ),
+            (
+                "https://www.youtube.com/attribution_link?a=JdfC0C9V6ZI&u=%2Fwatch%3Fv%3DEhxJLojIE_o%26feature%3Dshare",
+                YoutubeURL::Video("EhxJLojIE_o".to_string()),
+            ),
+            (
+                "https://www.youtube.com/watch?list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr&v=dQw4w9WgXcQ",
+                YoutubeURL::Video("dQw4w9WgXcQ".to_string()),
+            ),
+            (
+                "https://www.youtube.com/clip/UgytZKpehg-hEMBSn3F4AaABCQ",
+                YoutubeURL::Clip("UgytZKpehg-hEMBSn3F4AaABCQ".to_string()),
+            ),
+        ] {
+            assert_eq!(parse_url(&Url::parse(url).unwrap()).unwrap(), expected, "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_parse_chapters() {
+        let chapters = super::parse_chapters(
+            "Tracklist:\n0:00 Intro\n1:05 - The Song\n12:30 | Outro\n\nhttps://example.com",
+            Duration::from_secs(15 * 60),
+        );
+        assert_eq!(
+            chapters
+                .iter()
+                .map(|c| (c.start.as_secs(), c.end.as_secs(), c.title.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(0, 65, "Intro"), (65, 750, "The Song"), (750, 900, "Outro")]
+        );
+        assert_eq!(
+            super::parse_chapters("1:00:00 one\n1:00:01 two", Duration::from_secs(7200)),
+            vec![]
+        );
+        // not starting at 0:00, out of order, past the end
+        for description in [
+            "0:10 a\n1:00 b\n2:00 c",
+            "0:00 a\n2:00 b\n1:00 c",
+            "0:00 a\n1:00 b\n20:00 c",
+        ] {
+            assert!(
+                super::parse_chapters(description, Duration::from_secs(15 * 60)).is_empty(),
+                "{}",
+                description
+            );
+        }
+        // the overflowing timestamps are skipped
+        assert_eq!(
+            super::parse_chapters(
+                "0:00 a\n99999999999999999:59:59 b\n1:00 c\n2:00 d",
+                Duration::from_secs(15 * 60)
+            )
+            .iter()
+            .map(|c| c.title.as_str())
+            .collect::<Vec<_>>(),
+            vec!["a", "c", "d"]
+        );
+    }
+
+    /// An entry of `fixtures/players/tests.json`, with the player script in `<player>.js`.
+    /// The `synthetic_*` ones are written by hand in the shapes of the real players,
+    /// for the edge cases of the function extraction. No real player is stored yet,
+    /// they are added by their id with the `record_player_fixture` example
+    #[cfg(feature = "allow_js")]
+    #[derive(serde::Deserialize)]
+    struct PlayerJsFixture {
+        player: String,
+        sts: Option<u32>,
+        sig: Vec<(String, String)>,
+        n: Vec<(String, String)>,
+    }
+
+    #[cfg(feature = "allow_js")]
+    fn player_js_fixtures() -> Vec<(PlayerJsFixture, String)> {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/players");
+        let fixtures: Vec<PlayerJsFixture> =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("tests.json")).unwrap())
+                .unwrap();
+        fixtures
+            .into_iter()
+            .map(|fixture| {
+                let player_js =
+                    std::fs::read_to_string(dir.join(format!("{}.js", fixture.player))).unwrap();
+                (fixture, player_js)
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "allow_js")]
+    #[test]
+    fn test_parse_player_js() {
+        for (fixture, player_js) in player_js_fixtures() {
+            let sig_definition = super::parse_js_functions(&player_js)
+                .unwrap_or_else(|e| panic!("{}: {:?}", fixture.player, e));
+            assert_eq!(sig_definition.js_sts, fixture.sts, "{}", fixture.player);
+        }
+    }
+
+    #[cfg(feature = "allow_js")]
+    #[test]
+    fn test_parse_player_js_errors() {
+        let err = super::parse_js_functions("").unwrap_err();
+        assert_eq!(err.to_string(), "signature function name not found");
+
+        let (_, player_js) = player_js_fixtures().remove(0);
+        let err = super::parse_js_functions(&player_js.replace("var uy=", "var uy_=")).unwrap_err();
+        assert_eq!(err.to_string(), "signature helper object uy not found");
+        let err =
+            super::parse_js_functions(&player_js.replace("var Kya=", "Kya.push(")).unwrap_err();
+        assert_eq!(err.to_string(), "n-parameter function array Kya not found");
+        let err = super::parse_js_functions(&player_js.replace("lma=function(a)", "lma=(a)=>"))
+            .unwrap_err();
+        assert_eq!(err.to_string(), "n-parameter function lma not found");
+    }
+
+    #[cfg(feature = "allow_js")]
+    #[test]
+    fn test_player_js_solve() {
+        for (fixture, player_js) in player_js_fixtures() {
+            let sig_definition = super::parse_js_functions(&player_js)
+                .unwrap_or_else(|e| panic!("{}: {:?}", fixture.player, e));
+            for kind in JsEngineKind::ALL {
+                let mut js_engine = sig_definition
+                    .js_engine(*kind)
+                    .unwrap_or_else(|e| panic!("{} ({}): {:?}", fixture.player, kind.name(), e));
+                for (input, expected) in &fixture.sig {
+                    assert_eq!(
+                        &js_engine.call("sig", input).unwrap(),
+                        expected,
+                        "sig in {} ({})",
+                        fixture.player,
+                        kind.name()
+                    );
+                }
+                for (input, expected) in &fixture.n {
+                    assert_eq!(
+                        &js_engine.call("ncode", input).unwrap(),
+                        expected,
+                        "n in {} ({})",
+                        fixture.player,
+                        kind.name()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_formats_skips_undeciphered() {
+        use super::super::types::response::parts::{Format, StreamingData};
+
+        let formats = parse_formats(StreamingData {
+            formats: None,
+            adaptive_formats: Some(vec![
+                Format {
+                    itag: 251,
+                    signature_cipher: Some(
+                        "s=AOq0QJ8w&sp=sig&url=https%3A%2F%2Fexample.com".to_string(),
+                    ),
+                    mime_type: "audio/webm; codecs=\"opus\"".to_string(),
+                    ..Default::default()
+                },
+                Format {
+                    itag: 140,
+                    url: Some(
+                        "https://rr2---sn-4g5e6nzz.googlevideo.com/videoplayback?itag=140"
+                            .to_string(),
+                    ),
+                    mime_type: "audio/mp4; codecs=\"mp4a.40.2\"".to_string(),
+                    ..Default::default()
+                },
+            ]),
+            hls_formats: None,
+            dash_manifest_url: None,
+            hls_manifest_url: None,
+        });
+        assert_eq!(
+            formats
+                .iter()
+                .map(|f| f.details.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["140"]
+        );
+    }
+
+    #[cfg(feature = "allow_js")]
+    #[tokio::test]
+    async fn test_handle_sig_fixture() {
+        use reytan_extractor_api::url::form_urlencoded;
+
+        use super::super::types::response::parts::{Format, StreamingData};
+
+        let (fixture, player_js) = player_js_fixtures().remove(0);
+        let (sig_in, sig_out) = &fixture.sig[0];
+        let (n_in, n_out) = &fixture.n[0];
+        let mut streaming_data = StreamingData {
+            formats: None,
+            adaptive_formats: Some(vec![Format {
+                itag: 251,
+                signature_cipher: Some(
+                    form_urlencoded::Serializer::new(String::new())
+                        .append_pair("s", sig_in)
+                        .append_pair("sp", "sig")
+                        .append_pair(
+                            "url",
+                            &format!(
+                                "https://rr2---sn-4g5e6nzz.googlevideo.com/videoplayback?itag=251&n={}",
+                                n_in
+                            ),
+                        )
+                        .finish(),
+                ),
+                mime_type: "audio/webm; codecs=\"opus\"".to_string(),
+                ..Default::default()
+            }]),
+            hls_formats: None,
+            dash_manifest_url: None,
+            hls_manifest_url: None,
+        };
+        YoutubeRE {}
+            .handle_sig(
+                &ExtractionContext::new().unwrap(),
+                (
+                    super::parse_js_functions(&player_js).unwrap(),
+                    &fixture.player,
+                ),
+                &mut streaming_data,
+            )
+            .await
+            .unwrap();
+        let url = Url::parse(
+            streaming_data.adaptive_formats.unwrap()[0]
+                .url
+                .as_ref()
+                .unwrap(),
+        )
+        .unwrap();
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.to_string())
+        };
+        assert_eq!(param("sig").as_ref(), Some(sig_out));
+        assert_eq!(param("n").as_ref(), Some(n_out));
+    }
+
     #[cfg(feature = "allow_js")]
     #[test]
     fn test_regexes_compile() {
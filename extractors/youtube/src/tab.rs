@@ -1,13 +1,15 @@
-use reytan_extractor_api::anyhow::Result;
+use reytan_extractor_api::anyhow::{anyhow, Result};
 use reytan_extractor_api::url::Url;
 use reytan_extractor_api::{
     async_trait, AnyExtraction, Extraction, ExtractionContext, ListBreed, ListContinuation,
-    ListExtraction, ListExtractor, NewExtractor, URLMatcher,
+    ListExtraction, ListExtractor, ListOwner, NewExtractor, URLMatcher,
 };
+use serde::Deserialize;
 
-use super::common::innertube_request;
 use super::common::YOUTUBE_HOSTS_MAIN;
-use super::types::request::clients::ANDROID;
+use super::common::{innertube_request, interface_language, parse_count, parse_updated_date};
+use super::mix::is_mix_id;
+use super::types::request::clients::{ANDROID, WEB};
 use super::types::response::parts::{ActualVideoListRenderer, Renderer};
 use super::types::VideoList;
 use super::types::{request, response};
@@ -22,21 +24,19 @@ impl NewExtractor for YoutubeTabLE {
 }
 
 impl YoutubeTabLE {
-    async fn yti_browse(
+    async fn yti_browse<T>(
         self,
         ctx: &ExtractionContext,
         resource_name: &str,
         id: &str,
         client_: &request::Client<'_>,
         params: Option<String>,
-    ) -> Result<response::Browse> {
+    ) -> Result<T>
+    where
+        T: for<'a> Deserialize<'a> + Unpin,
+    {
         let mut client = client_.clone();
-        let hl = &ctx
-            .locales
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "en".to_string())[0..2];
-        client.context.hl = Some(hl);
+        client.context.hl = Some(interface_language(&ctx.locales));
         let json = request::Browse {
             browse_id: id.to_string(),
             params,
@@ -48,21 +48,19 @@ impl YoutubeTabLE {
         };
         innertube_request(ctx, resource_name, &client, "browse", json).await
     }
-    async fn yti_browse_cont(
+    async fn yti_browse_cont<T>(
         self,
         ctx: &ExtractionContext,
         resource_name: &str,
         id: &str,
         client_: &request::Client<'_>,
         continuation: String,
-    ) -> Result<response::BrowseContinuation> {
+    ) -> Result<T>
+    where
+        T: for<'a> Deserialize<'a> + Unpin,
+    {
         let mut client = client_.clone();
-        let hl = &ctx
-            .locales
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "en".to_string())[0..2];
-        client.context.hl = Some(hl);
+        client.context.hl = Some(interface_language(&ctx.locales));
         let json = request::Browse {
             browse_id: id.to_string(),
             continuation: Some(continuation),
@@ -81,12 +79,7 @@ impl YoutubeTabLE {
         client_: &request::Client<'_>,
     ) -> Result<response::NavigationResolve> {
         let mut client = client_.clone();
-        let hl = &ctx
-            .locales
-            .first()
-            .cloned()
-            .unwrap_or_else(|| "en".to_string())[0..2];
-        client.context.hl = Some(hl);
+        client.context.hl = Some(interface_language(&ctx.locales));
         let json = request::NavigationResolve {
             url: url.to_string(),
             context: request::parts::Context {
@@ -107,15 +100,79 @@ impl URLMatcher for YoutubeTabLE {
                 _ => false,
             })
             .filter(|u| {
-                let host = u.host_str().unwrap();
-                let first_segment = u.path_segments().unwrap().next().unwrap();
+                let host = u.host_str().unwrap_or_default();
+                let first_segment = u
+                    .path_segments()
+                    .and_then(|mut s| s.next())
+                    .unwrap_or_default();
                 YOUTUBE_HOSTS_MAIN.contains(&host)
-                    && ["playlist", "channel", "c", "user"].contains(&first_segment)
+                    && (["playlist", "channel", "c", "user"].contains(&first_segment)
+                        // @handle
+                        || (first_segment.starts_with('@') && first_segment.len() > 1)
+                        // a video opened from a playlist - YoutubeRE matches it too
+                        || (first_segment == "watch" && get_list_id(u).is_some()))
             })
+            // mixes are not browsable, see YoutubeMixLE
+            .filter(|u| !get_list_id(u).map(|l| is_mix_id(&l)).unwrap_or(false))
             .is_some()
     }
 }
 
+fn get_list_id(url: &Url) -> Option<String> {
+    url.query_pairs()
+        .find(|(k, _)| k == "list")
+        .map(|(_, v)| v.to_string())
+        .filter(|l| !l.is_empty())
+}
+
+/// Tabs of the channel page, selected with the url (e.g. `/@handle/shorts`).
+/// Videos are the default, if no (supported) tab is in the url.
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum ChannelTab {
+    Videos,
+    Shorts,
+    /// live streams, current and past ones
+    Live,
+    /// user-made playlists, returned as nested lists
+    Playlists,
+    /// albums and singles, returned as nested lists
+    Releases,
+    /// returned as nested lists
+    Podcasts,
+}
+
+impl ChannelTab {
+    fn from_url(url: &Url) -> ChannelTab {
+        let segments: Vec<&str> = url.path_segments().map(|s| s.collect()).unwrap_or_default();
+        // `/@handle/shorts`, but `/channel/UC.../shorts`
+        let tab_segment = if segments.first().filter(|s| s.starts_with('@')).is_some() {
+            segments.get(1)
+        } else {
+            segments.get(2)
+        };
+        match tab_segment.copied() {
+            Some("shorts") => ChannelTab::Shorts,
+            Some("streams") => ChannelTab::Live,
+            Some("playlists") => ChannelTab::Playlists,
+            Some("releases") => ChannelTab::Releases,
+            Some("podcasts") => ChannelTab::Podcasts,
+            _ => ChannelTab::Videos,
+        }
+    }
+
+    /// browse params selecting the tab
+    fn params(&self) -> &'static str {
+        match self {
+            ChannelTab::Videos => "EgZ2aWRlb3PyBgQKAjoA",
+            ChannelTab::Shorts => "EgZzaG9ydHPyBgUKA5oBAA%3D%3D",
+            ChannelTab::Live => "EgdzdHJlYW1z8gYECgJ6AA%3D%3D",
+            ChannelTab::Playlists => "EglwbGF5bGlzdHPyBgQKAkIA",
+            ChannelTab::Releases => "EghyZWxlYXNlc_IGBQoDsgEA",
+            ChannelTab::Podcasts => "Eghwb2RjYXN0c_IGBQoDugEA",
+        }
+    }
+}
+
 fn get_videos(renderer: Renderer) -> Option<ActualVideoListRenderer> {
     match renderer {
         Renderer::SingleColumnBrowseResultsRenderer { tabs }
@@ -143,40 +200,54 @@ fn get_videos(renderer: Renderer) -> Option<ActualVideoListRenderer> {
     return None;
 }
 
-#[async_trait]
-impl ListExtractor for YoutubeTabLE {
-    async fn extract_list_initial(
+impl YoutubeTabLE {
+    async fn extract_playlist(
         &self,
         ctx: &ExtractionContext,
-        url: &Url,
+        browse_id: String,
+        params: Option<String>,
     ) -> Result<ListExtraction> {
-        // let (browse_id, params) = pseudo_id_to_id_and_params(id.to_string());
-        let navigation_resolve = self
-            .yti_navigation_resolve(ctx, url.as_str(), &ANDROID)
-            .await
-            .unwrap();
-        let browse_end = navigation_resolve.endpoint.browse_endpoint.unwrap();
-        let vl: VideoList<Extraction> = {
-            let browse = self
-                .yti_browse(
-                    ctx,
-                    "first page",
-                    &browse_end.browse_id,
-                    &ANDROID,
-                    browse_end.params,
-                )
-                .await?;
-            get_videos(browse.contents.unwrap()).unwrap().into()
-        };
-        let breed = if browse_end.browse_id.starts_with("VL") {
-            ListBreed::Playlist
-        } else {
-            ListBreed::Channel
-        };
+        let browse: response::Browse = self
+            .yti_browse(ctx, "first page", &browse_id, &ANDROID, params)
+            .await?;
+        let vl: VideoList<Extraction> = get_videos(
+            browse
+                .contents
+                .ok_or_else(|| anyhow!("no contents in the playlist"))?,
+        )
+        .ok_or_else(|| anyhow!("no videos in the playlist"))?
+        .into();
+        let header = browse.header.and_then(|h| h.playlist_header_renderer);
+        let microformat = browse.microformat.and_then(|m| m.microformat_data_renderer);
+        let stat_texts = header.as_ref().map(|h| h.stat_texts()).unwrap_or_default();
 
         return Ok(ListExtraction {
-            id: browse_end.browse_id,
-            breed,
+            id: browse_id,
+            breed: ListBreed::Playlist,
+            title: header
+                .as_ref()
+                .and_then(|h| h.title.clone())
+                .map(String::from)
+                .or_else(|| microformat.as_ref().and_then(|m| m.title.clone()))
+                .unwrap_or_default(),
+            description: header
+                .as_ref()
+                .and_then(|h| h.description_text.clone())
+                .map(String::from)
+                .or_else(|| microformat.as_ref().and_then(|m| m.description.clone()))
+                .filter(|d| !d.is_empty()),
+            owner: header.as_ref().and_then(|h| {
+                h.owner_text.clone().map(|ot| ListOwner {
+                    id: h.owner_id(),
+                    name: ot.into(),
+                })
+            }),
+            item_count: stat_texts.first().and_then(|t| parse_count(t)),
+            modified_time: stat_texts.iter().find_map(|t| parse_updated_date(t)),
+            thumbnails: microformat
+                .and_then(|m| m.thumbnail)
+                .map(Vec::from)
+                .unwrap_or_default(),
             is_endless: false,
             entries: Some(Ok(vl
                 .videos
@@ -192,13 +263,74 @@ impl ListExtractor for YoutubeTabLE {
         });
     }
 
-    async fn extract_list_continuation(
+    async fn extract_channel(
+        &self,
+        ctx: &ExtractionContext,
+        browse_id: String,
+        tab: ChannelTab,
+    ) -> Result<ListExtraction> {
+        // richGridRenderer and others are only there on WEB
+        let browse: response::ChannelBrowse = self
+            .yti_browse(
+                ctx,
+                "first page",
+                &browse_id,
+                &WEB,
+                Some(tab.params().to_string()),
+            )
+            .await?;
+        let tab_content = browse
+            .contents
+            .and_then(|c| c.two_column_browse_results_renderer)
+            .into_iter()
+            .flat_map(|r| r.tabs)
+            .flat_map(|t| t.tab_renderer)
+            .find(|t| t.selected)
+            .and_then(|t| t.content)
+            .ok_or_else(|| anyhow!("channel tab {:?} not found", tab))?;
+        let vl: VideoList<AnyExtraction> = vec![tab_content].into();
+        let metadata = browse.metadata.and_then(|m| m.channel_metadata_renderer);
+
+        return Ok(ListExtraction {
+            breed: ListBreed::Channel,
+            title: metadata
+                .as_ref()
+                .map(|m| m.title.clone())
+                .unwrap_or_default(),
+            description: metadata
+                .as_ref()
+                .and_then(|m| m.description.clone())
+                .filter(|d| !d.is_empty()),
+            owner: metadata.as_ref().map(|m| ListOwner {
+                id: Some(m.external_id.clone().unwrap_or_else(|| browse_id.clone())),
+                name: m.title.clone(),
+            }),
+            id: browse_id,
+            thumbnails: metadata
+                .and_then(|m| m.avatar)
+                .or_else(|| {
+                    browse
+                        .microformat
+                        .and_then(|m| m.microformat_data_renderer)
+                        .and_then(|m| m.thumbnail)
+                })
+                .map(Vec::from)
+                .unwrap_or_default(),
+            banners: browse.header.map(|h| h.banners()).unwrap_or_default(),
+            is_endless: false,
+            entries: Some(Ok(vl.videos)),
+            continuation: vl.continuations.into_iter().next().map(|c| c.continuation),
+            ..Default::default()
+        });
+    }
+
+    async fn extract_playlist_continuation(
         &self,
         ctx: &ExtractionContext,
         browse_id: &str,
         continuation: &str,
     ) -> Result<ListContinuation> {
-        let browse = self
+        let browse: response::BrowseContinuation = self
             .yti_browse_cont(
                 ctx,
                 "continuation",
@@ -207,7 +339,10 @@ impl ListExtractor for YoutubeTabLE {
                 continuation.to_string(),
             )
             .await?;
-        let pvlr: VideoList<Extraction> = browse.continuation_contents.unwrap().into();
+        let pvlr: VideoList<Extraction> = browse
+            .continuation_contents
+            .ok_or_else(|| anyhow!("no contents in the continuation"))?
+            .into();
 
         return Ok(ListContinuation {
             id: browse_id.to_string(),
@@ -223,18 +358,100 @@ impl ListExtractor for YoutubeTabLE {
             },
         });
     }
+
+    async fn extract_channel_continuation(
+        &self,
+        ctx: &ExtractionContext,
+        browse_id: &str,
+        continuation: &str,
+    ) -> Result<ListContinuation> {
+        let browse: response::ChannelBrowseContinuation = self
+            .yti_browse_cont(
+                ctx,
+                "continuation",
+                browse_id,
+                &WEB,
+                continuation.to_string(),
+            )
+            .await?;
+        let vl: VideoList<AnyExtraction> = browse
+            .on_response_received_actions
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|a| {
+                a.append_continuation_items_action
+                    .or(a.reload_continuation_items_command)
+            })
+            .flat_map(|i| i.continuation_items)
+            .collect::<Vec<_>>()
+            .into();
+
+        return Ok(ListContinuation {
+            id: browse_id.to_string(),
+            entries: Some(Ok(vl.videos)),
+            continuation: vl.continuations.into_iter().next().map(|c| c.continuation),
+        });
+    }
+}
+
+#[async_trait]
+impl ListExtractor for YoutubeTabLE {
+    async fn extract_list_initial(
+        &self,
+        ctx: &ExtractionContext,
+        url: &Url,
+    ) -> Result<ListExtraction> {
+        // watch urls are not resolved to the playlist
+        if let Some(list_id) = get_list_id(url) {
+            return self
+                .extract_playlist(ctx, format!("VL{}", list_id), None)
+                .await;
+        }
+        let browse_end = self
+            .yti_navigation_resolve(ctx, url.as_str(), &ANDROID)
+            .await?
+            .endpoint
+            .browse_endpoint
+            .ok_or_else(|| anyhow!("url not resolved to a browsable page"))?;
+        if browse_end.browse_id.starts_with("VL") {
+            self.extract_playlist(ctx, browse_end.browse_id, browse_end.params)
+                .await
+        } else {
+            self.extract_channel(ctx, browse_end.browse_id, ChannelTab::from_url(url))
+                .await
+        }
+    }
+
+    async fn extract_list_continuation(
+        &self,
+        ctx: &ExtractionContext,
+        browse_id: &str,
+        continuation: &str,
+    ) -> Result<ListContinuation> {
+        if browse_id.starts_with("VL") {
+            self.extract_playlist_continuation(ctx, browse_id, continuation)
+                .await
+        } else {
+            self.extract_channel_continuation(ctx, browse_id, continuation)
+                .await
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use futures::prelude::*;
     use futures::stream;
     use reytan_extractor_api::url::Url;
     use reytan_extractor_api::{
-        AnyExtraction, ExtractionContext, ListBreed, ListExtractor, URLMatcher,
+        AnyExtraction, Availability, Extraction, ExtractionContext, ListBreed, ListExtractor,
+        URLMatcher,
     };
 
-    use super::YoutubeTabLE;
+    use super::{ChannelTab, YoutubeTabLE};
+    use crate::types::response::parts::PlaylistVideoRenderer;
 
     #[tokio::test]
     async fn do_extract_youtube_playlist() {
@@ -247,6 +464,9 @@ mod tests {
         assert_eq!(initial.id, "VLPLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr");
         assert_eq!(initial.breed, ListBreed::Playlist);
         assert_eq!(initial.is_endless, false);
+        assert!(initial.title.len() > 0);
+        assert!(initial.owner.is_some());
+        assert!(initial.item_count.unwrap() >= 74);
         let stream = stream::unfold(initial.continuation.clone(), |state| {
             let local = ctx.clone();
             let init_id = initial.id.clone();
@@ -283,6 +503,110 @@ mod tests {
             )
             .collect();
         assert!(extractions.len() >= 74);
+        match &extractions[0] {
+            AnyExtraction::Recording(r) => {
+                assert_eq!(r.metadata.list_index, Some(1));
+                assert!(r.metadata.duration.is_some());
+                assert!(r.metadata.uploader.is_some());
+                assert!(r.metadata.thumbnails.len() > 0);
+                assert!(r.metadata.availability.is_some());
+            }
+            _ => panic!("playlist should only contain recordings"),
+        }
+    }
+
+    #[test]
+    fn test_playlist_entry_availability() {
+        for (json, expected) in [
+            (
+                r#"{"binding":{"videoClientBindingData":{"videoId":"dQw4w9WgXcQ"}},"title":{"simpleText":"Never Gonna Give You Up"},"index":{"simpleText":"3"},"shortBylineText":{"runs":[{"text":"Rick Astley","navigationEndpoint":{"browseEndpoint":{"browseId":"UCuAXFkgsw1L7xaCfnd5JJOw"}}}]},"lengthSeconds":"213","isPlayable":true}"#,
+                Some(Availability::Playable),
+            ),
+            (
+                r#"{"binding":{"videoClientBindingData":{"videoId":"dQw4w9WgXcQ"}},"title":{"simpleText":"Never Gonna Give You Up"},"shortBylineText":{"runs":[{"text":"Rick Astley"}]},"isPlayable":false}"#,
+                Some(Availability::Unplayable),
+            ),
+            (
+                r#"{"binding":{"videoClientBindingData":{"videoId":"dQw4w9WgXcQ"}},"title":{"simpleText":"[Private video]"},"isPlayable":false}"#,
+                Some(Availability::Private),
+            ),
+            (
+                r#"{"binding":{"videoClientBindingData":{"videoId":"dQw4w9WgXcQ"}},"title":{"simpleText":"[Deleted video]"},"isPlayable":false}"#,
+                Some(Availability::Deleted),
+            ),
+            // private or deleted, but not in English
+            (
+                r#"{"binding":{"videoClientBindingData":{"videoId":"dQw4w9WgXcQ"}},"title":{"simpleText":"[Privates Video]"},"isPlayable":false}"#,
+                None,
+            ),
+        ] {
+            let pvr: PlaylistVideoRenderer = serde_json::from_str(json).unwrap();
+            let metadata = Extraction::from(pvr).metadata;
+            assert_eq!(metadata.availability, expected);
+        }
+
+        let pvr: PlaylistVideoRenderer = serde_json::from_str(
+            r#"{"binding":{"videoClientBindingData":{"videoId":"dQw4w9WgXcQ"}},"title":{"simpleText":"Never Gonna Give You Up"},"index":{"simpleText":"3"},"shortBylineText":{"runs":[{"text":"Rick Astley","navigationEndpoint":{"browseEndpoint":{"browseId":"UCuAXFkgsw1L7xaCfnd5JJOw"}}}]},"lengthSeconds":"213","isPlayable":true,"videoInfo":{"runs":[{"text":"1,234 views"},{"text":" • "},{"text":"3 years ago"}]}}"#,
+        )
+        .unwrap();
+        let metadata = Extraction::from(pvr).metadata;
+        assert_eq!(metadata.list_index, Some(3));
+        assert_eq!(metadata.duration, Some(Duration::from_secs(213)));
+        assert_eq!(metadata.view_count, Some(1234));
+        assert_eq!(metadata.published_time.is_some(), true);
+        assert_eq!(metadata.uploader.as_deref(), Some("Rick Astley"));
+        assert_eq!(
+            metadata.uploader_id.as_deref(),
+            Some("UCuAXFkgsw1L7xaCfnd5JJOw")
+        );
+    }
+
+    #[test]
+    fn test_url_match_table() {
+        let ytt = YoutubeTabLE {};
+        for (url, expected) in [
+            (
+                "https://www.youtube.com/playlist?list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr",
+                true,
+            ),
+            (
+                "https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr",
+                true,
+            ),
+            ("https://www.youtube.com/channel/UCWSC_-y9QsDmACXRY3rvtsQ", true),
+            ("https://www.youtube.com/c/Astrophysicsynth/videos", true),
+            ("https://www.youtube.com/user/LinusTechTips", true),
+            ("https://www.youtube.com/@LinusTechTips", true),
+            ("https://m.youtube.com/@LinusTechTips/videos", true),
+            ("https://www.youtube.com/@", false),
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ", false),
+            ("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=", false),
+            ("https://www.youtube.com/playlist?list=RDdQw4w9WgXcQ", false),
+            ("https://youtu.be/dQw4w9WgXcQ?list=PLpTn8onHfnD2QpCHU-llSG9hbQUwKIVFr", false),
+            ("https://example.com/@LinusTechTips", false),
+        ] {
+            assert_eq!(
+                ytt.match_extractor(&Url::parse(url).unwrap()),
+                expected,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn do_extract_youtube_handle() {
+        let ctx = ExtractionContext::new().unwrap();
+        let ytt = YoutubeTabLE {};
+        let initial = ytt
+            .extract_list_initial(
+                &ctx,
+                &Url::parse("https://www.youtube.com/@Astrophysicsynth").unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(initial.id, "UCWSC_-y9QsDmACXRY3rvtsQ");
+        assert_eq!(initial.breed, ListBreed::Channel);
     }
 
     #[tokio::test]
@@ -296,6 +620,12 @@ mod tests {
         assert_eq!(initial.id, "UCWSC_-y9QsDmACXRY3rvtsQ");
         assert_eq!(initial.breed, ListBreed::Channel);
         assert_eq!(initial.is_endless, false);
+        assert_eq!(initial.title, "Astrophysicsynth");
+        assert_eq!(
+            initial.owner.as_ref().unwrap().id.as_deref(),
+            Some("UCWSC_-y9QsDmACXRY3rvtsQ")
+        );
+        assert!(initial.thumbnails.len() > 0);
         let stream = stream::unfold(initial.continuation.clone(), |state| {
             let local = http.clone();
             let init_id = initial.id.clone();
@@ -333,4 +663,95 @@ mod tests {
             .collect();
         assert!(extractions.len() >= 50);
     }
+
+    #[test]
+    fn test_channel_tab_from_url() {
+        for (url, expected) in [
+            ("https://www.youtube.com/@LinusTechTips", ChannelTab::Videos),
+            (
+                "https://www.youtube.com/@LinusTechTips/videos",
+                ChannelTab::Videos,
+            ),
+            (
+                "https://www.youtube.com/@LinusTechTips/shorts",
+                ChannelTab::Shorts,
+            ),
+            (
+                "https://www.youtube.com/@LinusTechTips/streams",
+                ChannelTab::Live,
+            ),
+            (
+                "https://www.youtube.com/@LinusTechTips/playlists",
+                ChannelTab::Playlists,
+            ),
+            (
+                "https://www.youtube.com/channel/UCWSC_-y9QsDmACXRY3rvtsQ/releases",
+                ChannelTab::Releases,
+            ),
+            (
+                "https://www.youtube.com/c/Astrophysicsynth/podcasts",
+                ChannelTab::Podcasts,
+            ),
+            (
+                "https://www.youtube.com/user/LinusTechTips",
+                ChannelTab::Videos,
+            ),
+            (
+                "https://www.youtube.com/user/LinusTechTips/community",
+                ChannelTab::Videos,
+            ),
+        ] {
+            assert_eq!(
+                ChannelTab::from_url(&Url::parse(url).unwrap()),
+                expected,
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn do_extract_youtube_channel_shorts() {
+        let ctx = ExtractionContext::new().unwrap();
+        let ytt = YoutubeTabLE {};
+        let initial = ytt
+            .extract_list_initial(
+                &ctx,
+                &Url::parse("https://www.youtube.com/@LinusTechTips/shorts").unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(initial.breed, ListBreed::Channel);
+        let entries = initial.entries.unwrap().unwrap();
+        assert!(entries.len() > 0);
+        for entry in entries {
+            match entry {
+                AnyExtraction::Recording(_) => (),
+                _ => panic!("shorts tab should only contain recordings"),
+            }
+        }
+        assert!(initial.continuation.is_some());
+    }
+
+    #[tokio::test]
+    async fn do_extract_youtube_channel_playlists() {
+        let ctx = ExtractionContext::new().unwrap();
+        let ytt = YoutubeTabLE {};
+        let initial = ytt
+            .extract_list_initial(
+                &ctx,
+                &Url::parse("https://www.youtube.com/@LinusTechTips/playlists").unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(initial.breed, ListBreed::Channel);
+        let entries = initial.entries.unwrap().unwrap();
+        assert!(entries.len() > 0);
+        for entry in entries {
+            match entry {
+                AnyExtraction::List(l) => assert!(l.id.starts_with("VL")),
+                _ => panic!("playlists tab should only contain lists"),
+            }
+        }
+    }
 }
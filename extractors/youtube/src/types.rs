@@ -2,16 +2,24 @@ use self::response::parts::Continuation;
 
 pub mod response {
     pub mod parts {
-        use api::{HLSDownloadOptions, HTTPDownloadOptions};
+        use std::time::Duration;
+
+        use api::{Availability, HLSDownloadOptions, HTTPConnectionOptions, HTTPDownloadOptions};
         use once_cell::sync::Lazy;
+        use reytan_extractor_api::anyhow::{self, anyhow};
+        use reytan_extractor_api::parse::{now, parse_duration, parse_relative_date};
         use reytan_extractor_api::{
-            self as api, url::Url, Extraction, FormatBreed, MediaFormatDetails,
-            MediaFormatEstablished, MediaFormatURL, MediaMetadata, SubtitleExt,
+            self as api, url::Url, AnyExtraction, ExtractLevel, Extraction, FormatBreed,
+            FormatProtocol, ListBreed, ListExtraction, MediaFormatDetails, MediaFormatEstablished,
+            MediaFormatURL, MediaMetadata, SubtitleExt, TrackMetadata,
         };
         use serde::Deserialize;
         use serde_aux::prelude::*;
 
+        use super::super::request::clients;
         use super::super::VideoList;
+        use crate::common::{parse_count, watch_url};
+        use crate::manifest::split_codecs;
 
         #[derive(SmartDefault, Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
@@ -46,10 +54,89 @@ pub mod response {
             pub audio_channels: Option<u8>,
             /// dynamic range compression
             pub is_drc: Option<bool>,
+            /// extra tags distinguishing formats with the same itag (language, DRC),
+            /// encoded protobuf - only compared as is
+            pub xtags: Option<String>,
+            /// set on the audio formats if there is more than one audio track (dubs)
+            pub audio_track: Option<AudioTrack>,
+            /// name of the innertube client that returned the format, set after the request
+            #[serde(skip)]
+            pub source_client: Option<String>,
+        }
+
+        impl Format {
+            /// formats are only duplicates if all of these are the same
+            pub fn merge_key(&self) -> (u16, bool, Option<String>, Option<String>) {
+                (
+                    self.itag,
+                    self.is_drc == Some(true),
+                    self.xtags.clone(),
+                    self.audio_track.as_ref().map(|t| t.id.clone()),
+                )
+            }
+
+            /// Gets the value from the `xtags` URL param (`acont=dubbed:lang=de`),
+            /// which is the readable version of [Format::xtags]
+            fn url_xtag(&self, key: &str) -> Option<String> {
+                let url = Url::parse(self.url.as_ref()?).ok()?;
+                let xtags = url
+                    .query_pairs()
+                    .find(|(k, _)| k == "xtags")
+                    .map(|(_, v)| v.to_string())?;
+                xtags
+                    .split(':')
+                    .filter_map(|xtag| xtag.split_once('='))
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| v.to_string())
+            }
+
+            fn audio_track_role(&self) -> Option<api::AudioTrackRole> {
+                match self.url_xtag("acont").as_deref() {
+                    Some("original") => Some(api::AudioTrackRole::Original),
+                    Some("dubbed") | Some("dubbed-auto") => Some(api::AudioTrackRole::Dubbed),
+                    Some("descriptive") => Some(api::AudioTrackRole::Descriptive),
+                    // the display name is localized, so only works for english
+                    _ => self.audio_track.as_ref().and_then(|t| {
+                        let name = t.display_name.to_lowercase();
+                        if name.contains("original") {
+                            Some(api::AudioTrackRole::Original)
+                        } else if name.contains("descriptive") {
+                            Some(api::AudioTrackRole::Descriptive)
+                        } else {
+                            None
+                        }
+                    }),
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct AudioTrack {
+            /// human-readable, localized
+            pub display_name: String,
+            /// `{language}.{n}`, e.g. `en.4`
+            pub id: String,
+            #[serde(default)]
+            pub audio_is_default: bool,
         }
 
-        impl From<Format> for MediaFormatEstablished {
-            fn from(fmt: Format) -> MediaFormatEstablished {
+        impl AudioTrack {
+            pub fn language(&self) -> &str {
+                self.id.split('.').next().unwrap_or(&self.id)
+            }
+        }
+
+        impl TryFrom<Format> for MediaFormatEstablished {
+            type Error = anyhow::Error;
+
+            fn try_from(fmt: Format) -> Result<MediaFormatEstablished, Self::Error> {
+                // not set on the signatureCipher formats until they are deciphered
+                let url: Url = fmt
+                    .url
+                    .as_deref()
+                    .ok_or_else(|| anyhow!("format {} has no url", fmt.itag))?
+                    .parse()?;
                 let is_hls = fmt.mime_type.starts_with("application/x-mpegURL");
                 let breed = if fmt.mime_type.starts_with("audio/") {
                     FormatBreed::Audio
@@ -67,6 +154,12 @@ pub mod response {
                 } else {
                     FormatBreed::Video
                 };
+                let connection = fmt
+                    .source_client
+                    .as_deref()
+                    .and_then(clients::by_name)
+                    .map(HTTPConnectionOptions::from)
+                    .unwrap_or_default();
                 let mut format_attributes = Vec::new();
                 if is_hls {
                     format_attributes.push("-hls");
@@ -74,9 +167,24 @@ pub mod response {
                 if fmt.is_drc == Some(true) {
                     format_attributes.push("-drc");
                 }
-                MediaFormatEstablished {
+                // same itag for every audio track
+                let track_id = fmt.audio_track.as_ref().map(|t| format!("-{}", t.id));
+                // `video/mp4; codecs="avc1.640028"`
+                let (video_codec, audio_codec) = fmt
+                    .mime_type
+                    .split_once("codecs=")
+                    .map(|(_, codecs)| split_codecs(codecs.trim_matches('"')))
+                    .unwrap_or_default();
+                let audio_track = fmt.audio_track.as_ref().map(|t| api::AudioTrack {
+                    id: t.id.clone(),
+                    name: Some(t.display_name.clone()),
+                    role: fmt.audio_track_role(),
+                    is_default: t.audio_is_default,
+                });
+                Ok(MediaFormatEstablished {
                     details: MediaFormatDetails {
                         id: fmt.itag.to_string()
+                            + track_id.as_deref().unwrap_or_default()
                             + &format_attributes
                                 .into_iter()
                                 .fold(String::new(), |f, p| f + p),
@@ -86,7 +194,8 @@ pub mod response {
                             Some(api::VideoDetails {
                                 width: fmt.width,
                                 height: fmt.height,
-                                ..Default::default()
+                                fps: fmt.fps.map(u32::from),
+                                codec: video_codec,
                             })
                         } else {
                             None
@@ -96,25 +205,32 @@ pub mod response {
                         {
                             Some(api::AudioDetails {
                                 channels: fmt.audio_channels,
-                                ..Default::default()
+                                language: fmt
+                                    .audio_track
+                                    .as_ref()
+                                    .map(|t| t.language().to_string())
+                                    .or_else(|| fmt.url_xtag("lang")),
+                                track: audio_track,
+                                codec: audio_codec,
                             })
                         } else {
                             None
                         },
                         breed,
+                        source: fmt.source_client,
+                        bitrate: fmt.average_bitrate.or(fmt.bitrate),
+                        protocol: if is_hls {
+                            FormatProtocol::HLS
+                        } else {
+                            FormatProtocol::HTTP
+                        },
                     },
                     url: if is_hls {
-                        MediaFormatURL::HLS(
-                            fmt.url.unwrap().parse().unwrap(),
-                            HLSDownloadOptions::default(),
-                        )
+                        MediaFormatURL::HLS(url, HLSDownloadOptions { connection })
                     } else {
-                        MediaFormatURL::HTTP(
-                            fmt.url.unwrap().parse().unwrap(),
-                            HTTPDownloadOptions::default(),
-                        )
+                        MediaFormatURL::HTTP(url, HTTPDownloadOptions { connection })
                     },
-                }
+                })
             }
         }
 
@@ -127,6 +243,59 @@ pub mod response {
             pub adaptive_formats: Option<Vec<Format>>,
             // present in ios responses on livestreams
             pub hls_formats: Option<Vec<Format>>,
+            // live and post-live streams
+            pub dash_manifest_url: Option<ManifestUrl>,
+            pub hls_manifest_url: Option<ManifestUrl>,
+        }
+
+        /// The manifest URL, along with the client it was returned to
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(from = "String")]
+        pub struct ManifestUrl {
+            pub url: String,
+            pub source_client: Option<String>,
+        }
+
+        impl From<String> for ManifestUrl {
+            fn from(url: String) -> ManifestUrl {
+                ManifestUrl {
+                    url,
+                    source_client: None,
+                }
+            }
+        }
+
+        impl ManifestUrl {
+            pub fn connection(&self) -> HTTPConnectionOptions {
+                self.source_client
+                    .as_deref()
+                    .and_then(clients::by_name)
+                    .map(HTTPConnectionOptions::from)
+                    .unwrap_or_default()
+            }
+        }
+
+        impl StreamingData {
+            /// Marks all the formats as coming from the innertube client
+            pub fn set_source_client(&mut self, client_name: &str) {
+                for fmt in [
+                    &mut self.formats,
+                    &mut self.adaptive_formats,
+                    &mut self.hls_formats,
+                ]
+                .into_iter()
+                .flatten()
+                .flatten()
+                {
+                    fmt.source_client = Some(client_name.to_string());
+                }
+                for manifest in [&mut self.dash_manifest_url, &mut self.hls_manifest_url]
+                    .into_iter()
+                    .flatten()
+                {
+                    manifest.source_client = Some(client_name.to_string());
+                }
+            }
         }
 
         #[derive(Deserialize, PartialEq, Eq, Hash, Default, Clone, Debug)]
@@ -135,6 +304,48 @@ pub mod response {
             pub status: String,
             pub reason: Option<String>,
             pub reason_title: Option<String>,
+            /// present on the live streams and premieres
+            pub live_streamability: Option<LiveStreamabilityWrapper>,
+        }
+
+        impl PlayabilityStatus {
+            /// planned start of an upcoming stream, as UNIX timestamp
+            pub fn scheduled_start_time(&self) -> Option<i64> {
+                self.live_streamability
+                    .as_ref()?
+                    .live_streamability_renderer
+                    .offline_slate
+                    .as_ref()?
+                    .live_stream_offline_slate_renderer
+                    .scheduled_start_time
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct LiveStreamabilityWrapper {
+            pub live_streamability_renderer: LiveStreamabilityRenderer,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct LiveStreamabilityRenderer {
+            /// only before the stream starts
+            pub offline_slate: Option<LiveStreamOfflineSlateWrapper>,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct LiveStreamOfflineSlateWrapper {
+            pub live_stream_offline_slate_renderer: LiveStreamOfflineSlateRenderer,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct LiveStreamOfflineSlateRenderer {
+            #[serde(deserialize_with = "deserialize_option_number_from_string")]
+            #[serde(default)]
+            pub scheduled_start_time: Option<i64>,
         }
 
         #[derive(Deserialize, PartialEq, Eq, Hash, Default, Clone, Debug)]
@@ -152,10 +363,73 @@ pub mod response {
             pub view_count: Option<u64>,
             pub short_description: Option<String>,
             pub keywords: Option<Vec<String>>,
+            pub thumbnail: Option<ThumbnailsWrapper>,
             #[serde(default)]
             pub is_live: bool,
             #[serde(default)]
             pub is_live_content: bool,
+            #[serde(default)]
+            pub is_upcoming: bool,
+            /// the stream has ended, but the recording is not processed yet
+            #[serde(default)]
+            pub is_post_live_dvr: bool,
+            #[serde(default)]
+            pub is_live_dvr_enabled: bool,
+            /// `MDE_STREAM_OPTIMIZATIONS_RENDERER_LATENCY_{NORMAL,LOW,ULTRA_LOW}`
+            pub latency_class: Option<String>,
+        }
+
+        impl VideoDetails {
+            pub fn live_status(&self) -> api::LiveStatus {
+                if self.is_upcoming {
+                    api::LiveStatus::IsUpcoming
+                } else if self.is_live {
+                    api::LiveStatus::IsLive
+                } else if self.is_post_live_dvr {
+                    api::LiveStatus::PostLive
+                } else if self.is_live_content {
+                    api::LiveStatus::WasLive
+                } else {
+                    api::LiveStatus::NotLive
+                }
+            }
+
+            pub fn live_details(&self) -> Option<api::LiveDetails> {
+                if !(self.is_upcoming || self.is_live || self.is_post_live_dvr) {
+                    return None;
+                }
+                Some(api::LiveDetails {
+                    dvr_enabled: self.is_live_dvr_enabled,
+                    latency_class: match self
+                        .latency_class
+                        .as_deref()
+                        .and_then(|l| l.strip_prefix("MDE_STREAM_OPTIMIZATIONS_RENDERER_LATENCY_"))
+                    {
+                        Some("NORMAL") => Some(api::LatencyClass::Normal),
+                        Some("LOW") => Some(api::LatencyClass::Low),
+                        Some("ULTRA_LOW") => Some(api::LatencyClass::UltraLow),
+                        _ => None,
+                    },
+                })
+            }
+        }
+
+        /// `videoDetails`, when nothing else is guaranteed to be there
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct VideoIdOnly {
+            pub video_id: String,
+        }
+
+        /// Section of the video that the clip is made of, present on the clip webpages
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ClipConfig {
+            pub post_id: Option<String>,
+            #[serde(deserialize_with = "deserialize_number_from_string")]
+            pub start_time_ms: u64,
+            #[serde(deserialize_with = "deserialize_number_from_string")]
+            pub end_time_ms: u64,
         }
 
         #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
@@ -165,6 +439,28 @@ pub mod response {
             pub microformat_data_renderer: Option<MicroformatsMusic>,
         }
 
+        impl MicroformatsWrapper {
+            /// ISO 8601 date or timestamp, from whichever microformats are there
+            pub fn publish_date(&self) -> Option<&str> {
+                (self.player_microformat_renderer.as_ref())
+                    .and_then(|m| m.publish_date.as_deref())
+                    .or_else(|| {
+                        (self.microformat_data_renderer.as_ref())
+                            .and_then(|m| m.publish_date.as_deref())
+                    })
+            }
+
+            /// ISO 8601 date or timestamp, from whichever microformats are there
+            pub fn upload_date(&self) -> Option<&str> {
+                (self.player_microformat_renderer.as_ref())
+                    .and_then(|m| m.upload_date.as_deref())
+                    .or_else(|| {
+                        (self.microformat_data_renderer.as_ref())
+                            .and_then(|m| m.upload_date.as_deref())
+                    })
+            }
+        }
+
         /// Microformats for web and web_* EXCEPT web_music
         #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
@@ -182,13 +478,26 @@ pub mod response {
             #[serde(deserialize_with = "deserialize_option_number_from_string")]
             #[serde(default)]
             pub view_count: Option<u64>,
-            /// ISO 8601 date
+            /// ISO 8601 date (`2023-03-01`) or timestamp (`2023-03-01T09:12:45-08:00`)
             pub publish_date: Option<String>,
-            /// ISO 8601 date
+            /// ISO 8601 date (`2023-03-01`) or timestamp (`2023-03-01T09:12:45-08:00`)
             pub upload_date: Option<String>,
+            pub live_broadcast_details: Option<LiveBroadcastDetails>,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct LiveBroadcastDetails {
+            #[serde(default)]
+            pub is_live_now: bool,
+            /// ISO 8601 timestamp, the planned start if not started yet
+            pub start_timestamp: Option<String>,
+            /// ISO 8601 timestamp
+            pub end_timestamp: Option<String>,
         }
 
-        /// Very special microformats for very special web_music
+        /// Very special microformats for very special web_music,
+        /// also returned on the browse pages for all clients
         #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
         pub struct MicroformatsMusic {
@@ -202,11 +511,12 @@ pub mod response {
             #[serde(deserialize_with = "deserialize_option_number_from_string")]
             #[serde(default)]
             pub view_count: Option<u64>,
-            /// ISO 8601 date
+            /// ISO 8601 date (`2023-03-01`) or timestamp (`2023-03-01T09:12:45-08:00`)
             pub publish_date: Option<String>,
-            /// ISO 8601 date
+            /// ISO 8601 date (`2023-03-01`) or timestamp (`2023-03-01T09:12:45-08:00`)
             pub upload_date: Option<String>,
             pub video_details: Option<MicroformatsMusicVideoDetails>,
+            pub thumbnail: Option<ThumbnailsWrapper>,
         }
 
         #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
@@ -228,6 +538,10 @@ pub mod response {
         #[serde(rename_all = "camelCase")]
         pub struct PlayerCaptionsTracklistRenderer {
             pub caption_tracks: Option<Vec<CaptionTrack>>,
+            /// languages the translatable tracks can be auto-translated to
+            pub translation_languages: Option<Vec<TranslationLanguage>>,
+            pub audio_tracks: Option<Vec<CaptionsAudioTrack>>,
+            pub default_audio_track_index: Option<usize>,
         }
 
         #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
@@ -239,6 +553,29 @@ pub mod response {
             // Some("asr") - automatic captions
             // None - normal captions
             pub kind: Option<String>,
+            #[serde(default)]
+            pub is_translatable: bool,
+        }
+
+        impl CaptionTrack {
+            fn is_asr(&self) -> bool {
+                self.kind.as_deref() == Some("asr")
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct TranslationLanguage {
+            pub language_code: String,
+            pub language_name: RunsWrapper,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct CaptionsAudioTrack {
+            /// indices of [PlayerCaptionsTracklistRenderer::caption_tracks]
+            pub caption_track_indices: Option<Vec<usize>>,
+            pub default_caption_track_index: Option<usize>,
         }
 
         static SUBTITLE_EXTS: Lazy<Vec<(&'static str, SubtitleExt)>> = Lazy::new(|| {
@@ -252,41 +589,160 @@ pub mod response {
             ]
         });
 
-        impl From<PlayerCaptionsTracklistRenderer> for Vec<api::SubtitlePointerURL> {
-            fn from(r: PlayerCaptionsTracklistRenderer) -> Self {
-                if let Some(caption_tracks) = r.caption_tracks {
-                    caption_tracks
-                        .into_iter()
-                        .flat_map(|t| {
-                            let base_url = Url::parse(&t.base_url).unwrap();
-                            let base_query = qstring::QString::new(
-                                base_url.query_pairs().filter(|(k, _)| k != "fmt").collect(),
-                            );
-                            let mut result = vec![];
-                            for (e, se) in SUBTITLE_EXTS.iter() {
-                                let mut url = base_url.clone();
-                                let mut query = base_query.clone();
-                                query.add_pair(("fmt", *e));
-                                url.set_query(Some(&query.to_string()));
-                                result.push(api::SubtitlePointerURL {
-                                    details: api::SubtitleDetails {
-                                        lang: t.language_code.clone(),
-                                        is_original_lang: None,
-                                        is_machine_generated: Some(
-                                            t.kind == Some("asr".to_string()),
-                                        ),
-                                        is_machine_translated: Some(false),
-                                        ext: se.clone(),
-                                    },
-                                    url,
-                                });
-                            }
-                            result
-                        })
-                        .collect()
-                } else {
-                    Vec::new()
+        /// `en-US` and `en` are the same language here
+        fn same_language(a: &str, b: &str) -> bool {
+            a.split('-').next() == b.split('-').next()
+        }
+
+        /// The track URL in every format, translated to `tlang` if set
+        fn subtitle_urls(
+            base_url: &str,
+            tlang: Option<&str>,
+            level: &ExtractLevel,
+        ) -> Vec<(Url, SubtitleExt)> {
+            let base_url = match Url::parse(base_url) {
+                Ok(url) => url,
+                Err(_) => return Vec::new(),
+            };
+            let base_query = base_url
+                .query_pairs()
+                .filter(|(k, _)| k != "fmt" && k != "tlang")
+                .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                .collect::<Vec<_>>();
+            SUBTITLE_EXTS
+                .iter()
+                .filter(|(_, se)| {
+                    *level == ExtractLevel::Extended || !matches!(se, SubtitleExt::NonStandard(_))
+                })
+                .map(|(e, se)| {
+                    let mut url = base_url.clone();
+                    {
+                        let mut query = url.query_pairs_mut();
+                        query.clear().extend_pairs(&base_query);
+                        query.append_pair("fmt", e);
+                        if let Some(tlang) = tlang {
+                            query.append_pair("tlang", tlang);
+                        }
+                    }
+                    (url, se.clone())
+                })
+                .collect()
+        }
+
+        impl PlayerCaptionsTracklistRenderer {
+            /// Language of the audio. The automatic captions are generated from it,
+            /// otherwise it's the default captions of the default audio track
+            pub fn original_lang(&self) -> Option<&str> {
+                let caption_tracks = self.caption_tracks.as_ref()?;
+                caption_tracks
+                    .iter()
+                    .find(|t| t.is_asr())
+                    .or_else(|| {
+                        let audio_track = self
+                            .audio_tracks
+                            .as_ref()?
+                            .get(self.default_audio_track_index?)?;
+                        caption_tracks.get(audio_track.default_caption_track_index?)
+                    })
+                    .map(|t| t.language_code.as_str())
+            }
+
+            /// The track to auto-translate from: the one in the original language,
+            /// preferring the non-automatic captions
+            fn translation_source(&self) -> Option<&CaptionTrack> {
+                let original_lang = self.original_lang();
+                self.caption_tracks
+                    .as_ref()?
+                    .iter()
+                    .filter(|t| t.is_translatable)
+                    .min_by_key(|t| {
+                        (
+                            !original_lang.map_or(false, |ol| same_language(ol, &t.language_code)),
+                            t.is_asr(),
+                        )
+                    })
+            }
+
+            /// `ExtractLevel::Basic` leaves out the YouTube-specific formats
+            pub fn into_subtitles(self, level: &ExtractLevel) -> Vec<api::SubtitlePointerURL> {
+                if *level == ExtractLevel::None {
+                    return Vec::new();
+                }
+                let original_lang = self.original_lang();
+                let is_original_lang = |lang: &str| original_lang.map(|ol| same_language(ol, lang));
+                let mut result = vec![];
+                for t in self.caption_tracks.iter().flatten() {
+                    for (url, ext) in subtitle_urls(&t.base_url, None, level) {
+                        result.push(api::SubtitlePointerURL {
+                            details: api::SubtitleDetails {
+                                lang: t.language_code.clone(),
+                                is_original_lang: is_original_lang(&t.language_code),
+                                is_machine_generated: Some(t.is_asr()),
+                                is_machine_translated: Some(false),
+                                ext,
+                            },
+                            url,
+                        });
+                    }
+                }
+                if let Some(source) = self.translation_source() {
+                    let translation_languages = self
+                        .translation_languages
+                        .iter()
+                        .flatten()
+                        .filter(|tl| tl.language_code != source.language_code);
+                    for tl in translation_languages {
+                        let urls = subtitle_urls(&source.base_url, Some(&tl.language_code), level);
+                        for (url, ext) in urls {
+                            result.push(api::SubtitlePointerURL {
+                                details: api::SubtitleDetails {
+                                    lang: tl.language_code.clone(),
+                                    is_original_lang: is_original_lang(&tl.language_code),
+                                    is_machine_generated: Some(source.is_asr()),
+                                    is_machine_translated: Some(true),
+                                    ext,
+                                },
+                                url,
+                            });
+                        }
+                    }
                 }
+                result
+            }
+        }
+
+        /// `{ "thumbnails": [ { "url": "https://..", "width": 48, "height": 48 } ] }`
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ThumbnailsWrapper {
+            #[serde(alias = "sources")]
+            pub thumbnails: Vec<Thumbnail>,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct Thumbnail {
+            /// can be protocol-relative (`//yt3.ggpht.com/..`)
+            pub url: String,
+            pub width: Option<u32>,
+            pub height: Option<u32>,
+        }
+
+        impl From<ThumbnailsWrapper> for Vec<api::Thumbnail> {
+            fn from(tw: ThumbnailsWrapper) -> Self {
+                tw.thumbnails
+                    .into_iter()
+                    .flat_map(|t| {
+                        Url::parse("https://youtube.com/")
+                            .and_then(|base| base.join(&t.url))
+                            .ok()
+                            .map(|url| api::Thumbnail {
+                                url,
+                                width: t.width,
+                                height: t.height,
+                            })
+                    })
+                    .collect()
             }
         }
 
@@ -312,6 +768,19 @@ pub mod response {
             }
         }
 
+        impl RunsWrapper {
+            /// browse ID of the first link in the text (e.g. the channel in a byline)
+            pub fn browse_id(&self) -> Option<String> {
+                self.runs
+                    .as_ref()?
+                    .iter()
+                    .flat_map(|r| r.navigation_endpoint.as_ref())
+                    .flat_map(|ne| ne.browse_endpoint.as_ref())
+                    .map(|be| be.browse_id.clone())
+                    .next()
+            }
+        }
+
         #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
         pub struct RunsInner {
@@ -319,320 +788,1201 @@ pub mod response {
             pub navigation_endpoint: Option<NavigationEndpoint>,
         }
 
-        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct NavigationEndpoint {
+            pub browse_endpoint: Option<BrowseEndpoint>,
+            pub watch_endpoint: Option<WatchEndpoint>,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct BrowseEndpoint {
+            pub browse_id: String,
+            pub params: Option<String>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub enum Renderer {
+            /// { "twoColumnBrowseResultsRenderer": { "tabs": [ "tabRenderer": { .. } ] } }
+            TwoColumnBrowseResultsRenderer {
+                tabs: Vec<Renderer>,
+            },
+            SingleColumnBrowseResultsRenderer {
+                tabs: Vec<Renderer>,
+            },
+            TabRenderer {
+                content: Option<Box<Renderer>>,
+            },
+            SectionListRenderer {
+                contents: Option<Vec<ActualVideoListRenderer>>,
+            },
+            ItemSectionRenderer {
+                contents: Vec<Renderer>,
+            },
+            PlaylistVideoListRendererWrapper {
+                playlist_video_list_renderer: ActualVideoListRenderer,
+            },
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistVideoListRendererWrapper {
+            pub playlist_video_list_renderer: PlaylistVideoListRenderer,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub enum ActualVideoListRenderer {
+            PlaylistVideoListRenderer {
+                contents: Option<Vec<PlaylistVideoRendererWrapper>>,
+                continuations: Option<Vec<ContinuationWrapper>>,
+            },
+            PlaylistVideoListContinuation {
+                contents: Option<Vec<PlaylistVideoRendererWrapper>>,
+                continuations: Option<Vec<ContinuationWrapper>>,
+            },
+            ItemSectionRenderer {
+                contents: Option<Vec<ElementRendererWrapper>>,
+                continuations: Option<Vec<ContinuationWrapper>>,
+            },
+            ItemSectionContinuation {
+                contents: Option<Vec<ElementRendererWrapper>>,
+                continuations: Option<Vec<ContinuationWrapper>>,
+            },
+        }
+
+        impl From<ActualVideoListRenderer> for VideoList<Extraction> {
+            fn from(avlr: ActualVideoListRenderer) -> Self {
+                match avlr {
+                    ActualVideoListRenderer::PlaylistVideoListRenderer {
+                        contents,
+                        continuations,
+                    }
+                    | ActualVideoListRenderer::PlaylistVideoListContinuation {
+                        contents,
+                        continuations,
+                    } => VideoList::<Extraction> {
+                        videos: contents
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|pvrw| pvrw.playlist_video_renderer)
+                            .filter(|pvr| pvr.is_some())
+                            .map(|pvr| pvr.unwrap())
+                            .map(|pvr| pvr.into())
+                            .collect(),
+                        continuations: continuations
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|cw| cw.next_continuation_data)
+                            .collect(),
+                    },
+                    ActualVideoListRenderer::ItemSectionRenderer {
+                        contents,
+                        continuations,
+                    }
+                    | ActualVideoListRenderer::ItemSectionContinuation {
+                        contents,
+                        continuations,
+                    } => VideoList::<Extraction> {
+                        videos: contents
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|erw| {
+                                erw.element_renderer
+                                    .new_element
+                                    .typee
+                                    .component_type
+                                    .model
+                                    .compact_video_model
+                                    .into()
+                            })
+                            .collect(),
+                        continuations: continuations
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|cw| cw.next_continuation_data)
+                            .collect(),
+                    },
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistVideoListContinuationWrapper {
+            pub playlist_video_list_continuation: PlaylistVideoListRenderer,
+        }
+
+        impl From<PlaylistVideoListContinuationWrapper> for VideoList<PlaylistVideoRenderer> {
+            fn from(pvlcw: PlaylistVideoListContinuationWrapper) -> Self {
+                pvlcw.playlist_video_list_continuation.into()
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistVideoListRenderer {
+            pub contents: Option<Vec<PlaylistVideoRendererWrapper>>,
+            pub continuations: Option<Vec<ContinuationWrapper>>,
+        }
+
+        impl From<PlaylistVideoListRenderer> for VideoList<PlaylistVideoRenderer> {
+            fn from(plvr: PlaylistVideoListRenderer) -> Self {
+                VideoList::<PlaylistVideoRenderer> {
+                    videos: plvr
+                        .contents
+                        .unwrap()
+                        .into_iter()
+                        .map(|vrw| vrw.playlist_video_renderer)
+                        .filter(|vr| vr.is_some())
+                        .map(|vr| vr.unwrap())
+                        .collect(),
+                    continuations: plvr
+                        .continuations
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|cw| cw.next_continuation_data)
+                        .collect(),
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ContinuationWrapper {
+            pub next_continuation_data: Continuation,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct Continuation {
+            pub continuation: String,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistVideoRendererWrapper {
+            pub playlist_video_renderer: Option<PlaylistVideoRenderer>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistVideoRenderer {
+            // this piece of shit stores the video id in ANDROID (I fucking hate innertube)
+            pub binding: VideoClientBindingDataWrapper,
+            pub title: RunsWrapper,
+            pub index: Option<RunsWrapper>,
+            pub short_byline_text: Option<RunsWrapper>,
+            #[serde(deserialize_with = "deserialize_option_number_from_string")]
+            #[serde(default)]
+            pub length_seconds: Option<u64>,
+            pub is_playable: Option<bool>,
+            pub thumbnail: Option<ThumbnailsWrapper>,
+            /// "1,234 views • 3 years ago"
+            pub video_info: Option<RunsWrapper>,
+        }
+
+        impl From<PlaylistVideoRenderer> for Extraction {
+            fn from(vr: PlaylistVideoRenderer) -> Self {
+                let title: String = vr.title.into();
+                let availability = match vr.is_playable {
+                    Some(true) => Some(Availability::Playable),
+                    // deleted and private videos are listed without the uploader,
+                    // and can only be told apart by the placeholder title, known only in English
+                    Some(false) if vr.short_byline_text.is_none() => match title.as_str() {
+                        "[Private video]" => Some(Availability::Private),
+                        "[Deleted video]" => Some(Availability::Deleted),
+                        _ => None,
+                    },
+                    Some(false) => Some(Availability::Unplayable),
+                    None => None,
+                };
+                let video_info = vr.video_info.map(String::from);
+                Self {
+                    metadata: MediaMetadata {
+                        url: watch_url(&vr.binding.video_client_binding_data.video_id),
+                        id: vr.binding.video_client_binding_data.video_id,
+                        title,
+                        duration: vr.length_seconds.map(Duration::from_secs),
+                        view_count: video_info.as_deref().and_then(parse_count),
+                        published_time: video_info
+                            .as_deref()
+                            .and_then(|vi| parse_relative_date(vi, now())),
+                        uploader_id: vr.short_byline_text.as_ref().and_then(|s| s.browse_id()),
+                        uploader: vr.short_byline_text.map(String::from),
+                        list_index: vr
+                            .index
+                            .map(String::from)
+                            .and_then(|i| parse_count(&i))
+                            .map(|i| i as u32),
+                        thumbnails: vr.thumbnail.map(Vec::from).unwrap_or_default(),
+                        availability,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct VideoClientBindingDataWrapper {
+            pub video_client_binding_data: VideoClientBindingData,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct VideoClientBindingData {
+            pub video_id: String,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ElementRendererWrapper {
+            pub element_renderer: ElementRenderer,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ElementRenderer {
+            pub new_element: NewElement,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct NewElement {
+            #[serde(rename = "type")]
+            /// "type" from JSON, renamed due to "type" being a reserved keyword
+            pub typee: NewElementType,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct NewElementType {
+            pub component_type: ComponentType,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ComponentType {
+            pub model: ComponentModel,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ComponentModel {
+            pub compact_video_model: CompactVideoModel,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct CompactVideoModel {
+            pub compact_video_data: CompactVideoData,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct CompactVideoData {
+            pub video_data: VideoData,
+            pub on_tap: OnTap,
+        }
+
+        impl From<CompactVideoModel> for Extraction {
+            fn from(cvm: CompactVideoModel) -> Self {
+                let video_id = cvm
+                    .compact_video_data
+                    .on_tap
+                    .innertube_command
+                    .watch_endpoint
+                    .video_id;
+                let video_data = cvm.compact_video_data.video_data;
+                let thumbnail = video_data.thumbnail;
+                Extraction {
+                    metadata: MediaMetadata {
+                        url: watch_url(&video_id),
+                        id: video_id,
+                        title: video_data.metadata.title,
+                        uploader: video_data.metadata.byline,
+                        duration: thumbnail
+                            .as_ref()
+                            .and_then(|t| t.time_stamp_text.as_deref())
+                            .and_then(parse_duration),
+                        thumbnails: thumbnail
+                            .and_then(|t| t.image)
+                            .map(Vec::from)
+                            .unwrap_or_default(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct VideoData {
+            pub metadata: VideoMetadata,
+            pub thumbnail: Option<VideoDataThumbnail>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct VideoMetadata {
+            pub title: String,
+            /// uploader name
+            pub byline: Option<String>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct VideoDataThumbnail {
+            /// `{ "sources": [ .. ] }`
+            pub image: Option<ThumbnailsWrapper>,
+            /// video length, "4:13"
+            pub time_stamp_text: Option<String>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct OnTap {
+            pub innertube_command: InnertubeCommand,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct InnertubeCommand {
+            pub watch_endpoint: WatchEndpoint,
+        }
+
+        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct WatchEndpoint {
+            pub video_id: String,
+            pub playlist_id: Option<String>,
+            /// position in the playlist, if watching one
+            pub index: Option<u32>,
+            pub params: Option<String>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub enum WatchNextResults {
+            TwoColumnWatchNextResults {
+                playlist: Option<PlaylistPanelWrapper>,
+            },
+            SingleColumnWatchNextResults {
+                playlist: Option<PlaylistPanelWrapper>,
+            },
+        }
+
+        impl WatchNextResults {
+            pub fn playlist(self) -> Option<PlaylistPanel> {
+                match self {
+                    WatchNextResults::TwoColumnWatchNextResults { playlist }
+                    | WatchNextResults::SingleColumnWatchNextResults { playlist } => {
+                        playlist.map(|w| w.playlist)
+                    }
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistPanelWrapper {
+            pub playlist: PlaylistPanel,
+        }
+
+        /// The playlist displayed next to the video, when watching one
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistPanel {
+            pub playlist_id: String,
+            pub title: Option<String>,
+            pub contents: Option<Vec<PlaylistPanelVideoRendererWrapper>>,
+            /// true on mixes
+            pub is_infinite: Option<bool>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistPanelVideoRendererWrapper {
+            // might also be automixPreviewVideoRenderer or something
+            pub playlist_panel_video_renderer: Option<PlaylistPanelVideoRenderer>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistPanelVideoRenderer {
+            pub video_id: String,
+            pub title: Option<RunsWrapper>,
+            pub short_byline_text: Option<RunsWrapper>,
+            pub length_text: Option<RunsWrapper>,
+            pub navigation_endpoint: Option<NavigationEndpoint>,
+            pub thumbnail: Option<ThumbnailsWrapper>,
+        }
+
+        impl From<PlaylistPanelVideoRenderer> for Extraction {
+            fn from(ppvr: PlaylistPanelVideoRenderer) -> Self {
+                Extraction {
+                    metadata: MediaMetadata {
+                        url: watch_url(&ppvr.video_id),
+                        id: ppvr.video_id,
+                        title: ppvr.title.map(String::from).unwrap_or_default(),
+                        duration: ppvr
+                            .length_text
+                            .map(String::from)
+                            .and_then(|lt| parse_duration(&lt)),
+                        uploader_id: ppvr.short_byline_text.as_ref().and_then(|s| s.browse_id()),
+                        uploader: ppvr.short_byline_text.map(String::from),
+                        // 0-based in the endpoint
+                        list_index: ppvr
+                            .navigation_endpoint
+                            .and_then(|ne| ne.watch_endpoint)
+                            .and_then(|we| we.index)
+                            .map(|i| i + 1),
+                        thumbnails: ppvr.thumbnail.map(Vec::from).unwrap_or_default(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelContents {
+            pub two_column_browse_results_renderer: Option<ChannelTabsWrapper>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelTabsWrapper {
+            pub tabs: Vec<ChannelTabWrapper>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelTabWrapper {
+            /// None on expandableTabRenderer (search)
+            pub tab_renderer: Option<ChannelTabRenderer>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelTabRenderer {
+            pub title: Option<String>,
+            #[serde(default)]
+            pub selected: bool,
+            /// only present on the selected tab
+            pub content: Option<ChannelTabItem>,
+        }
+
+        /// Anything that can be found in the channel tabs (as WEB), containers or the actual items.
+        /// Exactly one of the fields is expected to be present.
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelTabItem {
+            // containers
+            pub rich_grid_renderer: Option<ChannelTabItemContents>,
+            pub section_list_renderer: Option<ChannelTabItemContents>,
+            pub item_section_renderer: Option<ChannelTabItemContents>,
+            pub grid_renderer: Option<ChannelTabItemItems>,
+            pub rich_item_renderer: Option<ChannelTabItemContent>,
+            // actual items
+            pub video_renderer: Option<VideoRenderer>,
+            pub grid_video_renderer: Option<VideoRenderer>,
+            pub reel_item_renderer: Option<ReelItemRenderer>,
+            pub grid_playlist_renderer: Option<GridPlaylistRenderer>,
+            pub playlist_renderer: Option<GridPlaylistRenderer>,
+            pub continuation_item_renderer: Option<ContinuationItemRenderer>,
+        }
+
+        impl ChannelTabItem {
+            /// Collects the items into the list, recursively going through the containers
+            pub fn flatten_into(self, list: &mut VideoList<AnyExtraction>) {
+                for items in [
+                    self.rich_grid_renderer,
+                    self.section_list_renderer,
+                    self.item_section_renderer,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    for item in items.contents {
+                        item.flatten_into(list);
+                    }
+                }
+                if let Some(grid) = self.grid_renderer {
+                    for item in grid.items {
+                        item.flatten_into(list);
+                    }
+                }
+                if let Some(rich_item) = self.rich_item_renderer {
+                    rich_item.content.flatten_into(list);
+                }
+                for video in [self.video_renderer, self.grid_video_renderer]
+                    .into_iter()
+                    .flatten()
+                {
+                    list.videos.push(AnyExtraction::Recording(video.into()));
+                }
+                if let Some(reel) = self.reel_item_renderer {
+                    list.videos.push(AnyExtraction::Recording(reel.into()));
+                }
+                for playlist in [self.grid_playlist_renderer, self.playlist_renderer]
+                    .into_iter()
+                    .flatten()
+                {
+                    list.videos.push(AnyExtraction::List(playlist.into()));
+                }
+                if let Some(continuation) = self.continuation_item_renderer {
+                    list.continuations.push(Continuation {
+                        continuation: continuation
+                            .continuation_endpoint
+                            .continuation_command
+                            .token,
+                    });
+                }
+            }
+        }
+
+        impl From<Vec<ChannelTabItem>> for VideoList<AnyExtraction> {
+            fn from(items: Vec<ChannelTabItem>) -> Self {
+                let mut list = VideoList {
+                    videos: vec![],
+                    continuations: vec![],
+                };
+                for item in items {
+                    item.flatten_into(&mut list);
+                }
+                list
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelTabItemContents {
+            #[serde(default)]
+            pub contents: Vec<ChannelTabItem>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelTabItemItems {
+            #[serde(default)]
+            pub items: Vec<ChannelTabItem>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelTabItemContent {
+            pub content: Box<ChannelTabItem>,
+        }
+
+        /// Video on the lists, as WEB
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct VideoRenderer {
+            pub video_id: String,
+            pub title: Option<RunsWrapper>,
+            pub thumbnail: Option<ThumbnailsWrapper>,
+            /// "4:13", missing on live streams
+            pub length_text: Option<RunsWrapper>,
+            /// "1,234 views"
+            pub view_count_text: Option<RunsWrapper>,
+            /// missing on the channel pages
+            pub owner_text: Option<RunsWrapper>,
+            /// "3 weeks ago", "Streamed 2 hours ago"
+            pub published_time_text: Option<RunsWrapper>,
+        }
+
+        impl From<VideoRenderer> for Extraction {
+            fn from(vr: VideoRenderer) -> Self {
+                Extraction {
+                    metadata: MediaMetadata {
+                        url: watch_url(&vr.video_id),
+                        id: vr.video_id,
+                        title: vr.title.map(String::from).unwrap_or_default(),
+                        duration: vr
+                            .length_text
+                            .map(String::from)
+                            .and_then(|lt| parse_duration(&lt)),
+                        view_count: vr
+                            .view_count_text
+                            .map(String::from)
+                            .and_then(|vc| parse_count(&vc)),
+                        uploader_id: vr.owner_text.as_ref().and_then(|s| s.browse_id()),
+                        uploader: vr.owner_text.map(String::from),
+                        published_time: vr
+                            .published_time_text
+                            .map(String::from)
+                            .and_then(|pt| parse_relative_date(&pt, now())),
+                        thumbnails: vr.thumbnail.map(Vec::from).unwrap_or_default(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            }
+        }
+
+        /// YouTube Shorts on the lists
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ReelItemRenderer {
+            pub video_id: String,
+            pub headline: Option<RunsWrapper>,
+            pub thumbnail: Option<ThumbnailsWrapper>,
+        }
+
+        impl From<ReelItemRenderer> for Extraction {
+            fn from(rir: ReelItemRenderer) -> Self {
+                Extraction {
+                    metadata: MediaMetadata {
+                        url: watch_url(&rir.video_id),
+                        id: rir.video_id,
+                        title: rir.headline.map(String::from).unwrap_or_default(),
+                        thumbnails: rir.thumbnail.map(Vec::from).unwrap_or_default(),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                }
+            }
+        }
+
+        /// Playlist on the lists (channel playlists, releases, podcasts)
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct GridPlaylistRenderer {
+            pub playlist_id: String,
+            pub title: Option<RunsWrapper>,
+            pub thumbnail: Option<ThumbnailsWrapper>,
+            /// "74 videos"
+            pub video_count_text: Option<RunsWrapper>,
+        }
+
+        impl From<GridPlaylistRenderer> for ListExtraction {
+            fn from(gpr: GridPlaylistRenderer) -> Self {
+                ListExtraction {
+                    // auto-generated album playlists
+                    breed: if gpr.playlist_id.starts_with("OLAK5uy_") {
+                        ListBreed::Album
+                    } else {
+                        ListBreed::Playlist
+                    },
+                    id: format!("VL{}", gpr.playlist_id),
+                    title: gpr.title.map(String::from).unwrap_or_default(),
+                    item_count: gpr
+                        .video_count_text
+                        .map(String::from)
+                        .and_then(|vc| parse_count(&vc)),
+                    thumbnails: gpr.thumbnail.map(Vec::from).unwrap_or_default(),
+                    is_endless: false,
+                    // not fetched yet
+                    entries: None,
+                    ..Default::default()
+                }
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ContinuationItemRenderer {
+            pub continuation_endpoint: ContinuationEndpoint,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ContinuationEndpoint {
+            pub continuation_command: ContinuationCommand,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ContinuationCommand {
+            pub token: String,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistHeaderWrapper {
+            pub playlist_header_renderer: Option<PlaylistHeaderRenderer>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistHeaderRenderer {
+            pub title: Option<RunsWrapper>,
+            pub description_text: Option<RunsWrapper>,
+            pub owner_text: Option<RunsWrapper>,
+            /// "74 videos"
+            pub num_videos_text: Option<RunsWrapper>,
+            /// "74 videos", "1,234 views", "Last updated on Mar 5, 2023"
+            pub stats: Option<Vec<RunsWrapper>>,
+            pub byline: Option<Vec<PlaylistBylineWrapper>>,
+        }
+
+        impl PlaylistHeaderRenderer {
+            /// all the texts with the counters and dates
+            pub fn stat_texts(&self) -> Vec<String> {
+                self.num_videos_text
+                    .iter()
+                    .chain(self.stats.iter().flatten())
+                    .chain(
+                        self.byline
+                            .iter()
+                            .flatten()
+                            .flat_map(|b| b.playlist_byline_renderer.as_ref())
+                            .map(|b| &b.text),
+                    )
+                    .cloned()
+                    .map(String::from)
+                    .collect()
+            }
+
+            /// browse ID (`UC...`) of the playlist owner
+            pub fn owner_id(&self) -> Option<String> {
+                self.owner_text.as_ref()?.browse_id()
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistBylineWrapper {
+            pub playlist_byline_renderer: Option<PlaylistBylineRenderer>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PlaylistBylineRenderer {
+            pub text: RunsWrapper,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelMetadataWrapper {
+            pub channel_metadata_renderer: Option<ChannelMetadataRenderer>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelMetadataRenderer {
+            pub title: String,
+            pub description: Option<String>,
+            /// `UC...`
+            pub external_id: Option<String>,
+            pub avatar: Option<ThumbnailsWrapper>,
+        }
+
+        /// Either `c4TabbedHeaderRenderer` (older layout) or `pageHeaderRenderer` (newer one)
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct ChannelHeader {
+            pub c4_tabbed_header_renderer: Option<C4TabbedHeaderRenderer>,
+            pub page_header_renderer: Option<PageHeaderRenderer>,
+        }
+
+        impl ChannelHeader {
+            pub fn banners(self) -> Vec<api::Thumbnail> {
+                self.c4_tabbed_header_renderer
+                    .and_then(|c4| c4.banner)
+                    .or_else(|| {
+                        self.page_header_renderer
+                            .and_then(|phr| phr.content)
+                            .and_then(|c| c.page_header_view_model)
+                            .and_then(|vm| vm.banner)
+                            .and_then(|b| b.image_banner_view_model)
+                            .map(|ibvm| ibvm.image)
+                    })
+                    .map(Vec::from)
+                    .unwrap_or_default()
+            }
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct C4TabbedHeaderRenderer {
+            pub banner: Option<ThumbnailsWrapper>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PageHeaderRenderer {
+            pub content: Option<PageHeaderContent>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PageHeaderContent {
+            pub page_header_view_model: Option<PageHeaderViewModel>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct PageHeaderViewModel {
+            pub banner: Option<PageHeaderBanner>,
+        }
+
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct NavigationEndpoint {
-            pub browse_endpoint: Option<BrowseEndpoint>,
+        pub struct PageHeaderBanner {
+            pub image_banner_view_model: Option<ImageBannerViewModel>,
         }
 
-        #[derive(Deserialize, PartialEq, Eq, Hash, Clone, Debug)]
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct BrowseEndpoint {
-            pub browse_id: String,
-            pub params: Option<String>,
+        pub struct ImageBannerViewModel {
+            /// `{ "sources": [ .. ] }`
+            pub image: ThumbnailsWrapper,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub enum Renderer {
-            /// { "twoColumnBrowseResultsRenderer": { "tabs": [ "tabRenderer": { .. } ] } }
-            TwoColumnBrowseResultsRenderer {
-                tabs: Vec<Renderer>,
-            },
-            SingleColumnBrowseResultsRenderer {
-                tabs: Vec<Renderer>,
-            },
-            TabRenderer {
-                content: Option<Box<Renderer>>,
-            },
-            SectionListRenderer {
-                contents: Option<Vec<ActualVideoListRenderer>>,
-            },
-            ItemSectionRenderer {
-                contents: Vec<Renderer>,
-            },
-            PlaylistVideoListRendererWrapper {
-                playlist_video_list_renderer: ActualVideoListRenderer,
-            },
+        pub struct ResponseReceivedAction {
+            pub append_continuation_items_action: Option<ContinuationItems>,
+            pub reload_continuation_items_command: Option<ContinuationItems>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct PlaylistVideoListRendererWrapper {
-            pub playlist_video_list_renderer: PlaylistVideoListRenderer,
+        pub struct ContinuationItems {
+            #[serde(default)]
+            pub continuation_items: Vec<ChannelTabItem>,
         }
 
+        /// YouTube Music (WEB_REMIX) browse pages: both the older single column layout,
+        /// and the newer one with the tracks in `secondaryContents`
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub enum ActualVideoListRenderer {
-            PlaylistVideoListRenderer {
-                contents: Option<Vec<PlaylistVideoRendererWrapper>>,
-                continuations: Option<Vec<ContinuationWrapper>>,
-            },
-            PlaylistVideoListContinuation {
-                contents: Option<Vec<PlaylistVideoRendererWrapper>>,
-                continuations: Option<Vec<ContinuationWrapper>>,
-            },
-            ItemSectionRenderer {
-                contents: Option<Vec<ElementRendererWrapper>>,
-                continuations: Option<Vec<ContinuationWrapper>>,
-            },
-            ItemSectionContinuation {
-                contents: Option<Vec<ElementRendererWrapper>>,
-                continuations: Option<Vec<ContinuationWrapper>>,
-            },
+        pub struct MusicBrowseContents {
+            pub single_column_browse_results_renderer: Option<MusicTabs>,
+            pub two_column_browse_results_renderer: Option<MusicTabs>,
         }
 
-        impl From<ActualVideoListRenderer> for VideoList<Extraction> {
-            fn from(avlr: ActualVideoListRenderer) -> Self {
-                match avlr {
-                    ActualVideoListRenderer::PlaylistVideoListRenderer {
-                        contents,
-                        continuations,
-                    }
-                    | ActualVideoListRenderer::PlaylistVideoListContinuation {
-                        contents,
-                        continuations,
-                    } => VideoList::<Extraction> {
-                        videos: contents
-                            .unwrap_or_default()
-                            .into_iter()
-                            .map(|pvrw| pvrw.playlist_video_renderer)
-                            .filter(|pvr| pvr.is_some())
-                            .map(|pvr| pvr.unwrap())
-                            .map(|pvr| pvr.into())
-                            .collect(),
-                        continuations: continuations
-                            .unwrap_or_default()
-                            .into_iter()
-                            .map(|cw| cw.next_continuation_data)
-                            .collect(),
-                    },
-                    ActualVideoListRenderer::ItemSectionRenderer {
-                        contents,
-                        continuations,
-                    }
-                    | ActualVideoListRenderer::ItemSectionContinuation {
-                        contents,
-                        continuations,
-                    } => VideoList::<Extraction> {
-                        videos: contents
-                            .unwrap_or_default()
-                            .into_iter()
-                            .map(|erw| {
-                                erw.element_renderer
-                                    .new_element
-                                    .typee
-                                    .component_type
-                                    .model
-                                    .compact_video_model
-                                    .into()
-                            })
-                            .collect(),
-                        continuations: continuations
-                            .unwrap_or_default()
+        impl MusicBrowseContents {
+            pub fn sections(self) -> Vec<MusicSection> {
+                self.single_column_browse_results_renderer
+                    .into_iter()
+                    .chain(self.two_column_browse_results_renderer)
+                    .flat_map(|t| {
+                        t.tabs
                             .into_iter()
-                            .map(|cw| cw.next_continuation_data)
-                            .collect(),
-                    },
-                }
+                            .flat_map(|t| t.tab_renderer)
+                            .flat_map(|t| t.content)
+                            .chain(t.secondary_contents)
+                    })
+                    .flat_map(|c| c.section_list_renderer)
+                    .flat_map(|sl| sl.contents)
+                    .collect()
             }
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct PlaylistVideoListContinuationWrapper {
-            pub playlist_video_list_continuation: PlaylistVideoListRenderer,
+        pub struct MusicTabs {
+            #[serde(default)]
+            pub tabs: Vec<MusicTabWrapper>,
+            pub secondary_contents: Option<MusicSectionListWrapper>,
         }
 
-        impl From<PlaylistVideoListContinuationWrapper> for VideoList<PlaylistVideoRenderer> {
-            fn from(pvlcw: PlaylistVideoListContinuationWrapper) -> Self {
-                pvlcw.playlist_video_list_continuation.into()
-            }
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct MusicTabWrapper {
+            pub tab_renderer: Option<MusicTabRenderer>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct PlaylistVideoListRenderer {
-            pub contents: Option<Vec<PlaylistVideoRendererWrapper>>,
-            pub continuations: Option<Vec<ContinuationWrapper>>,
+        pub struct MusicTabRenderer {
+            pub content: Option<MusicSectionListWrapper>,
         }
 
-        impl From<PlaylistVideoListRenderer> for VideoList<PlaylistVideoRenderer> {
-            fn from(plvr: PlaylistVideoListRenderer) -> Self {
-                VideoList::<PlaylistVideoRenderer> {
-                    videos: plvr
-                        .contents
-                        .unwrap()
-                        .into_iter()
-                        .map(|vrw| vrw.playlist_video_renderer)
-                        .filter(|vr| vr.is_some())
-                        .map(|vr| vr.unwrap())
-                        .collect(),
-                    continuations: plvr
-                        .continuations
-                        .unwrap_or_default()
-                        .into_iter()
-                        .map(|cw| cw.next_continuation_data)
-                        .collect(),
-                }
-            }
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct MusicSectionListWrapper {
+            pub section_list_renderer: Option<MusicSectionList>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct ContinuationWrapper {
-            pub next_continuation_data: Continuation,
+        pub struct MusicSectionList {
+            #[serde(default)]
+            pub contents: Vec<MusicSection>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct Continuation {
-            pub continuation: String,
+        pub struct MusicSection {
+            /// album tracks, artist's top songs
+            pub music_shelf_renderer: Option<MusicShelf>,
+            /// playlist tracks
+            pub music_playlist_shelf_renderer: Option<MusicShelf>,
+            /// artist's albums, singles, videos, etc.
+            pub music_carousel_shelf_renderer: Option<MusicCarouselShelf>,
+            /// album header in the newer layout
+            pub music_responsive_header_renderer: Option<MusicHeaderRenderer>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct PlaylistVideoRendererWrapper {
-            pub playlist_video_renderer: Option<PlaylistVideoRenderer>,
+        pub struct MusicShelf {
+            #[serde(default)]
+            pub contents: Vec<MusicShelfItem>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct PlaylistVideoRenderer {
-            // this piece of shit stores the video id in ANDROID (I fucking hate innertube)
-            pub binding: VideoClientBindingDataWrapper,
-            pub title: RunsWrapper,
-            pub index: Option<RunsWrapper>,
-            pub short_byline_text: Option<RunsWrapper>,
-            #[serde(deserialize_with = "deserialize_option_number_from_string")]
+        pub struct MusicShelfItem {
+            pub music_responsive_list_item_renderer: Option<MusicResponsiveListItemRenderer>,
+        }
+
+        /// A track on the lists, with the data in columns:
+        /// title, artists and album (on playlists), then the length in the fixed column
+        #[derive(Deserialize, PartialEq, Clone, Debug)]
+        #[serde(rename_all = "camelCase")]
+        pub struct MusicResponsiveListItemRenderer {
             #[serde(default)]
-            pub length_seconds: Option<u64>,
-            pub is_playable: Option<bool>,
+            pub flex_columns: Vec<MusicFlexColumnWrapper>,
+            pub fixed_columns: Option<Vec<MusicFixedColumnWrapper>>,
+            /// track number, only on albums
+            pub index: Option<RunsWrapper>,
+            /// missing on unavailable tracks
+            pub playlist_item_data: Option<VideoIdOnly>,
+            pub thumbnail: Option<MusicThumbnailWrapper>,
         }
 
-        impl From<PlaylistVideoRenderer> for Extraction {
-            fn from(vr: PlaylistVideoRenderer) -> Self {
-                Self {
+        impl MusicResponsiveListItemRenderer {
+            fn flex_column(&self, i: usize) -> Option<&RunsWrapper> {
+                self.flex_columns
+                    .get(i)
+                    .map(|c| &c.music_responsive_list_item_flex_column_renderer.text)
+            }
+
+            /// `album` holds the album-wide details, to be completed with the track details.
+            /// `position` is used as the track number, if the list does not say it.
+            pub fn into_track(self, album: &TrackMetadata, position: u32) -> Option<Extraction> {
+                let video_id = self.playlist_item_data.as_ref()?.video_id.clone();
+                let title = self
+                    .flex_column(0)
+                    .cloned()
+                    .map(String::from)
+                    .unwrap_or_default();
+                // separators (" • ", " & ") are in the runs as well
+                let artists: Vec<String> = self
+                    .flex_column(1)
+                    .and_then(|c| c.runs.as_ref())
+                    .map(|runs| {
+                        runs.iter()
+                            .filter(|r| r.navigation_endpoint.is_some())
+                            .map(|r| r.text.clone())
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let track_album = self
+                    .flex_column(2)
+                    .cloned()
+                    .map(String::from)
+                    .filter(|a| !a.is_empty());
+                let track_number = self
+                    .index
+                    .clone()
+                    .map(String::from)
+                    .and_then(|i| parse_count(&i))
+                    .map(|i| i as u32)
+                    .unwrap_or(position);
+                Some(Extraction {
                     metadata: MediaMetadata {
-                        id: vr.binding.video_client_binding_data.video_id,
-                        title: vr.title.into(),
+                        url: watch_url(&video_id),
+                        id: video_id,
+                        title,
+                        duration: self
+                            .fixed_columns
+                            .as_ref()
+                            .and_then(|c| c.first())
+                            .map(|c| {
+                                String::from(
+                                    c.music_responsive_list_item_fixed_column_renderer
+                                        .text
+                                        .clone(),
+                                )
+                            })
+                            .and_then(|d| parse_duration(&d)),
+                        uploader: artists.first().cloned(),
+                        list_index: Some(position),
+                        thumbnails: self.thumbnail.map(Vec::from).unwrap_or_default(),
+                        track: Some(TrackMetadata {
+                            artists: if artists.is_empty() {
+                                album.album_artists.clone()
+                            } else {
+                                artists
+                            },
+                            album: track_album.or_else(|| album.album.clone()),
+                            track_number: Some(track_number),
+                            ..album.clone()
+                        }),
                         ..Default::default()
                     },
                     ..Default::default()
-                }
+                })
             }
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct VideoClientBindingDataWrapper {
-            pub video_client_binding_data: VideoClientBindingData,
+        pub struct MusicFlexColumnWrapper {
+            pub music_responsive_list_item_flex_column_renderer: MusicColumn,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct VideoClientBindingData {
-            pub video_id: String,
+        pub struct MusicFixedColumnWrapper {
+            pub music_responsive_list_item_fixed_column_renderer: MusicColumn,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct ElementRendererWrapper {
-            pub element_renderer: ElementRenderer,
+        pub struct MusicColumn {
+            pub text: RunsWrapper,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct ElementRenderer {
-            pub new_element: NewElement,
+        pub struct MusicCarouselShelf {
+            #[serde(default)]
+            pub contents: Vec<MusicCarouselItem>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct NewElement {
-            #[serde(rename = "type")]
-            /// "type" from JSON, renamed due to "type" being a reserved keyword
-            pub typee: NewElementType,
+        pub struct MusicCarouselItem {
+            pub music_two_row_item_renderer: Option<MusicTwoRowItemRenderer>,
         }
 
+        /// Album, single, playlist, video, or another artist on the carousels
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct NewElementType {
-            pub component_type: ComponentType,
+        pub struct MusicTwoRowItemRenderer {
+            pub title: RunsWrapper,
+            /// "Album • 2020", "Single • 2021"
+            pub subtitle: Option<RunsWrapper>,
+            pub navigation_endpoint: NavigationEndpoint,
+            pub thumbnail_renderer: Option<MusicThumbnailWrapper>,
         }
 
-        #[derive(Deserialize, PartialEq, Clone, Debug)]
-        #[serde(rename_all = "camelCase")]
-        pub struct ComponentType {
-            pub model: ComponentModel,
+        impl MusicTwoRowItemRenderer {
+            /// browse ID, if this is an album or a single (`MPREb...`)
+            pub fn album_id(&self) -> Option<&str> {
+                self.navigation_endpoint
+                    .browse_endpoint
+                    .as_ref()
+                    .map(|be| be.browse_id.as_str())
+                    .filter(|id| id.starts_with("MPREb"))
+            }
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct ComponentModel {
-            pub compact_video_model: CompactVideoModel,
+        pub struct MusicThumbnailWrapper {
+            pub music_thumbnail_renderer: Option<MusicThumbnailRenderer>,
+            pub cropped_square_thumbnail_renderer: Option<MusicThumbnailRenderer>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct CompactVideoModel {
-            pub compact_video_data: CompactVideoData,
+        pub struct MusicThumbnailRenderer {
+            pub thumbnail: ThumbnailsWrapper,
+        }
+
+        impl From<MusicThumbnailWrapper> for Vec<api::Thumbnail> {
+            fn from(mtw: MusicThumbnailWrapper) -> Self {
+                mtw.music_thumbnail_renderer
+                    .or(mtw.cropped_square_thumbnail_renderer)
+                    .map(|mtr| mtr.thumbnail.into())
+                    .unwrap_or_default()
+            }
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct CompactVideoData {
-            pub video_data: VideoData,
-            pub on_tap: OnTap,
+        pub struct MusicHeader {
+            /// albums and playlists, older layout
+            pub music_detail_header_renderer: Option<MusicHeaderRenderer>,
+            /// artists
+            pub music_immersive_header_renderer: Option<MusicHeaderRenderer>,
+            /// artists without a banner
+            pub music_visual_header_renderer: Option<MusicHeaderRenderer>,
         }
 
-        impl From<CompactVideoModel> for Extraction {
-            fn from(cvm: CompactVideoModel) -> Self {
-                Extraction {
-                    metadata: MediaMetadata {
-                        id: cvm
-                            .compact_video_data
-                            .on_tap
-                            .innertube_command
-                            .watch_endpoint
-                            .video_id,
-                        title: cvm.compact_video_data.video_data.metadata.title,
-                        ..Default::default()
-                    },
-                    ..Default::default()
-                }
+        impl MusicHeader {
+            pub fn renderer(self) -> Option<MusicHeaderRenderer> {
+                self.music_detail_header_renderer
+                    .or(self.music_immersive_header_renderer)
+                    .or(self.music_visual_header_renderer)
             }
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct VideoData {
-            pub metadata: VideoMetadata,
+        pub struct MusicHeaderRenderer {
+            pub title: RunsWrapper,
+            /// "Album • Artist • 2020" (older layout), "Album • 2020" (newer one)
+            pub subtitle: Option<RunsWrapper>,
+            /// the artist, in the newer layout
+            pub strapline_text_one: Option<RunsWrapper>,
+            pub description: Option<MusicDescription>,
+            pub thumbnail: Option<MusicThumbnailWrapper>,
         }
 
-        #[derive(Deserialize, PartialEq, Clone, Debug)]
-        #[serde(rename_all = "camelCase")]
-        pub struct VideoMetadata {
-            pub title: String,
-        }
+        impl MusicHeaderRenderer {
+            /// artists (or other linked channels) in the header, as `(browse ID, name)`
+            pub fn artists(&self) -> Vec<(String, String)> {
+                self.subtitle
+                    .iter()
+                    .chain(self.strapline_text_one.iter())
+                    .flat_map(|rw| rw.runs.iter().flatten())
+                    .flat_map(|r| {
+                        r.navigation_endpoint
+                            .as_ref()
+                            .and_then(|ne| ne.browse_endpoint.as_ref())
+                            .filter(|be| be.browse_id.starts_with("UC"))
+                            .map(|be| (be.browse_id.clone(), r.text.clone()))
+                    })
+                    .collect()
+            }
 
-        #[derive(Deserialize, PartialEq, Clone, Debug)]
-        #[serde(rename_all = "camelCase")]
-        pub struct OnTap {
-            pub innertube_command: InnertubeCommand,
+            pub fn year(&self) -> Option<i32> {
+                self.subtitle
+                    .iter()
+                    .flat_map(|rw| rw.runs.iter().flatten())
+                    .flat_map(|r| Some(&r.text).filter(|t| t.len() == 4)?.parse().ok())
+                    .last()
+            }
+
+            pub fn description(&self) -> Option<String> {
+                self.description
+                    .clone()
+                    .map(|d| match d.music_description_shelf_renderer {
+                        Some(mdsr) => mdsr.description.into(),
+                        None => d.text.into(),
+                    })
+                    .filter(|d: &String| !d.is_empty())
+            }
         }
 
+        /// Either the text itself, or in a shelf (newer layout)
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct InnertubeCommand {
-            pub watch_endpoint: WatchEndpoint,
+        pub struct MusicDescription {
+            #[serde(flatten)]
+            pub text: RunsWrapper,
+            pub music_description_shelf_renderer: Option<MusicDescriptionShelf>,
         }
 
         #[derive(Deserialize, PartialEq, Clone, Debug)]
         #[serde(rename_all = "camelCase")]
-        pub struct WatchEndpoint {
-            pub video_id: String,
+        pub struct MusicDescriptionShelf {
+            pub description: RunsWrapper,
         }
     }
 
@@ -654,6 +2004,8 @@ pub mod response {
     /// `/youtubei/v1/browse`
     pub struct Browse {
         pub contents: Option<parts::Renderer>,
+        pub header: Option<parts::PlaylistHeaderWrapper>,
+        pub microformat: Option<parts::MicroformatsWrapper>,
     }
 
     #[derive(Deserialize, PartialEq, Debug)]
@@ -663,12 +2015,45 @@ pub mod response {
         pub continuation_contents: Option<parts::ActualVideoListRenderer>,
     }
 
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(rename_all = "camelCase")]
+    /// `/youtubei/v1/browse` (as WEB, on channel tabs)
+    pub struct ChannelBrowse {
+        pub contents: Option<parts::ChannelContents>,
+        pub header: Option<parts::ChannelHeader>,
+        pub metadata: Option<parts::ChannelMetadataWrapper>,
+        pub microformat: Option<parts::MicroformatsWrapper>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(rename_all = "camelCase")]
+    /// `/youtubei/v1/browse` (as WEB, on channel tabs)
+    pub struct ChannelBrowseContinuation {
+        pub on_response_received_actions: Option<Vec<parts::ResponseReceivedAction>>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(rename_all = "camelCase")]
+    /// `/youtubei/v1/browse` (as WEB_REMIX)
+    pub struct MusicBrowse {
+        pub contents: Option<parts::MusicBrowseContents>,
+        pub header: Option<parts::MusicHeader>,
+        pub microformat: Option<parts::MicroformatsWrapper>,
+    }
+
     #[derive(Deserialize, PartialEq, Debug)]
     #[serde(rename_all = "camelCase")]
     /// `/youtubei/v1/navigation/resolve_url`
     pub struct NavigationResolve {
         pub endpoint: parts::NavigationEndpoint,
     }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(rename_all = "camelCase")]
+    /// `/youtubei/v1/next`
+    pub struct Next {
+        pub contents: Option<parts::WatchNextResults>,
+    }
 }
 
 pub mod request {
@@ -717,9 +2102,12 @@ pub mod request {
         }
     }
 
+    use reytan_extractor_api::{HTTPConnectionOptions, HTTPImpersonationTarget};
     use serde::Serialize;
     use smart_default::SmartDefault;
 
+    use crate::common::DEFAULT_USER_AGENT;
+
     #[derive(Serialize, Clone, Copy, Debug)]
     pub struct ImpersonationTarget<'a> {
         /// curl-impersonate target name
@@ -748,6 +2136,30 @@ pub mod request {
         pub ff_target: Option<ImpersonationTarget<'a>>,
     }
 
+    impl From<&Client<'_>> for HTTPConnectionOptions {
+        /// Connection settings for downloading the formats returned to the client,
+        /// as the URLs may be checked against the client that requested them
+        fn from(client: &Client<'_>) -> HTTPConnectionOptions {
+            let impersonation_target = |t: ImpersonationTarget<'_>| HTTPImpersonationTarget {
+                target: t.target.to_string(),
+                user_agent: t.user_agent.map(String::from),
+            };
+            HTTPConnectionOptions {
+                chrome_target: client.chrome_target.map(impersonation_target),
+                ff_target: client.ff_target.map(impersonation_target),
+                user_agent: Some(client.user_agent.unwrap_or(DEFAULT_USER_AGENT).to_string()),
+                headers: if client.js_needed {
+                    vec![
+                        ("origin".to_string(), format!("https://{}", client.host)),
+                        ("referer".to_string(), format!("https://{}/", client.host)),
+                    ]
+                } else {
+                    vec![]
+                },
+            }
+        }
+    }
+
     /// INNERTUBE_CLIENTS from yt-dlp: https://github.com/yt-dlp/yt-dlp/blob/master/yt_dlp/extractor/youtube.py
     pub mod clients {
         use super::{
@@ -1084,6 +2496,29 @@ pub mod request {
             }),
             ff_target: None,
         };
+
+        /// every client above, to be looked up by name
+        pub static ALL: [&Client; 14] = [
+            &ANDROID_MUSIC,
+            &ANDROID,
+            &ANDROID_EMBEDDED,
+            &ANDROID_CREATOR,
+            &IOS,
+            &IOS_EMBEDDED,
+            &IOS_MUSIC,
+            &IOS_CREATOR,
+            &WEB,
+            &WEB_EMBEDDED,
+            &WEB_MUSIC,
+            &WEB_CREATOR,
+            &MWEB,
+            &TV_EMBEDDED,
+        ];
+
+        /// Finds the client by its `name` (e.g. "android", "web_embedded")
+        pub fn by_name(name: &str) -> Option<&'static Client<'static>> {
+            ALL.into_iter().find(|c| c.name == name)
+        }
     }
 
     #[derive(SmartDefault, Serialize, Debug)]
@@ -1118,6 +2553,18 @@ pub mod request {
         pub context: parts::Context<'a>,
         pub url: String,
     }
+
+    #[derive(SmartDefault, Serialize, Debug)]
+    #[serde(rename_all = "camelCase")]
+    /// `/youtubei/v1/next`
+    pub struct Next<'a> {
+        pub context: parts::Context<'a>,
+        pub video_id: Option<String>,
+        pub playlist_id: Option<String>,
+        /// position of `video_id` in the playlist
+        pub index: Option<u32>,
+        pub params: Option<String>,
+    }
 }
 
 pub struct VideoList<T> {
@@ -1,14 +1,23 @@
+pub use reytan_extractor_api::cache::lru::Lru;
 pub use reytan_extractor_api::{
-    anyhow, async_trait, Extraction, MediaFormatDetails, MediaMetadata, SubtitleDetails,
+    anyhow, async_trait, chrono, AudioTrackRole, Availability, EntryDetails, Extraction,
+    FormatBreed, FormatProtocol, ListExtraction, LiveStatus, MediaFormatDetails, MediaMetadata,
+    SubtitleDetails,
 };
-use reytan_extractor_api::{MediaFormatEstablished, SubtitlePointerURL};
+use std::fmt;
+
+use reytan_extractor_api::{MediaFormatEstablished, SubtitleExt, SubtitlePointerURL};
 use serde::{Deserialize, Serialize};
 
+pub mod sort;
+
+pub use sort::{FormatSorter, DEFAULT_SORT};
+
 /// Helper enum for choosing the formats to be downloaded/merged/played.
 /// String stands for the `id` field of the entity.
 ///
 /// Oversimplified, but should suit typical use cases.
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, PartialEq, Debug)]
 pub enum FormatSelection<T> {
     Full(T),
     ExtractVideo(T),
@@ -35,13 +44,63 @@ impl<T> FormatSelection<T> {
     }
 }
 
-#[derive(Deserialize, Default, Debug)]
+/// Which subtitles to download, the first ones matching all of the set fields are picked.
+/// The fields are the same as in [`SubtitleDetails`], so the details can be passed back as they are.
+#[derive(Deserialize, Serialize, Default, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct SubtitleSelector {
+    pub lang: Option<String>,
+    pub ext: Option<SubtitleExt>,
+    pub is_original_lang: Option<bool>,
+    pub is_machine_generated: Option<bool>,
+    pub is_machine_translated: Option<bool>,
+}
+
+impl SubtitleSelector {
+    pub fn matches(&self, details: &SubtitleDetails) -> bool {
+        fn field<T: PartialEq>(wanted: &Option<T>, actual: &T) -> bool {
+            wanted.as_ref().is_none_or(|w| w == actual)
+        }
+        // not known counts as false
+        fn flag(wanted: &Option<bool>, actual: &Option<bool>) -> bool {
+            wanted.is_none_or(|w| actual.unwrap_or(false) == w)
+        }
+        field(&self.lang, &details.lang)
+            && field(&self.ext, &details.ext)
+            && flag(&self.is_original_lang, &details.is_original_lang)
+            && flag(&self.is_machine_generated, &details.is_machine_generated)
+            && flag(&self.is_machine_translated, &details.is_machine_translated)
+    }
+}
+
+/// A subtitle selector, either just the language (`"en"`) or [`SubtitleSelector`]
+/// (`{"lang": "en", "ext": "VTT", "is_machine_generated": false}`)
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+#[serde(untagged)]
+pub enum SubtitleSelection {
+    Lang(String),
+    Matching(SubtitleSelector),
+}
+
+impl SubtitleSelection {
+    pub fn selector(&self) -> SubtitleSelector {
+        match self {
+            SubtitleSelection::Lang(lang) => SubtitleSelector {
+                lang: Some(lang.clone()),
+                ..Default::default()
+            },
+            SubtitleSelection::Matching(selector) => selector.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Default, Debug)]
 #[serde(default)]
 /// A helper type to be outputted by the format pickers.
 /// For actual stuff to use for download, see [`DownloadList`].
 pub struct DownloadSelection {
     pub formats: Option<Vec<FormatSelection<String>>>,
-    pub subtitles: Option<Vec<String>>,
+    pub subtitles: Option<Vec<SubtitleSelection>>,
 }
 
 #[derive(Serialize, Debug)]
@@ -50,53 +109,303 @@ pub struct DownloadList<'a> {
     pub subtitles: Vec<&'a SubtitlePointerURL>,
 }
 
+/// The picked formats and subtitles that the extraction doesn't have,
+/// with what's there to pick instead
+#[derive(PartialEq, Debug)]
+pub struct UnmatchedSelection {
+    /// The ids of the formats not found
+    pub formats: Vec<String>,
+    pub subtitles: Vec<SubtitleSelection>,
+    /// The ids of all of the formats
+    pub available_formats: Vec<String>,
+    pub available_subtitles: Vec<SubtitleDetails>,
+}
+
+impl fmt::Display for UnmatchedSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quoted = |ids: &[String]| {
+            ids.iter()
+                .map(|id| format!("{:?}", id))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut lines = vec![];
+        if !self.formats.is_empty() {
+            lines.push(format!(
+                "no formats with the ids {} (available: {})",
+                quoted(&self.formats),
+                quoted(&self.available_formats)
+            ));
+        }
+        if !self.subtitles.is_empty() {
+            let available = self
+                .available_subtitles
+                .iter()
+                .map(|s| {
+                    let auto = if s.is_machine_generated == Some(true) {
+                        " (auto)"
+                    } else {
+                        ""
+                    };
+                    format!("{} {:?}{}", s.lang, s.ext, auto)
+                })
+                .collect::<Vec<_>>();
+            lines.push(format!(
+                "no subtitles matching {} (available: {})",
+                self.subtitles
+                    .iter()
+                    .map(|s| format!("{:?}", s))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                available.join(", ")
+            ));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl std::error::Error for UnmatchedSelection {}
+
 impl<'a> DownloadList<'a> {
+    /// Finds the picked formats and subtitles in the extraction,
+    /// fails with [`UnmatchedSelection`] if some of them are not there
     pub fn from(
         selection: &DownloadSelection,
         extraction: &'a Extraction,
     ) -> anyhow::Result<DownloadList<'a>> {
-        Ok(DownloadList {
-            formats: selection
-                .formats
-                .as_ref()
-                .map(|fss| {
-                    fss.iter()
-                        .map(|fs| {
-                            fs.map_ref(|sid| {
-                                extraction
-                                    .established_formats
-                                    .iter()
-                                    .find(|ef| &ef.details.id == sid)
-                                    .unwrap()
-                            })
-                        })
-                        .collect()
-                })
-                .unwrap_or_default(),
-            subtitles: selection
-                .subtitles
-                .as_ref()
-                .map(|ses| {
-                    ses.iter()
-                        .map(|sid| {
-                            extraction
-                                .established_subtitles
-                                .iter()
-                                .find(|es| &es.details.lang == sid)
-                                .unwrap()
-                        })
-                        .collect()
-                })
-                .unwrap_or_default(),
-        })
+        let mut unmatched_formats = vec![];
+        let mut formats = vec![];
+        for format_sel in selection.formats.iter().flatten() {
+            let id = match format_sel {
+                FormatSelection::Full(id)
+                | FormatSelection::ExtractVideo(id)
+                | FormatSelection::ExtractAudio(id) => id,
+            };
+            match extraction
+                .established_formats
+                .iter()
+                .find(|ef| &ef.details.id == id)
+            {
+                Some(format) => formats.push(format_sel.map_ref(|_| format)),
+                None => unmatched_formats.push(id.clone()),
+            }
+        }
+
+        let mut unmatched_subtitles = vec![];
+        let mut subtitles = vec![];
+        for subtitle_sel in selection.subtitles.iter().flatten() {
+            let selector = subtitle_sel.selector();
+            match extraction
+                .established_subtitles
+                .iter()
+                .find(|es| selector.matches(&es.details))
+            {
+                Some(subtitle) => subtitles.push(subtitle),
+                None => unmatched_subtitles.push(subtitle_sel.clone()),
+            }
+        }
+
+        if !unmatched_formats.is_empty() || !unmatched_subtitles.is_empty() {
+            return Err(UnmatchedSelection {
+                formats: unmatched_formats,
+                subtitles: unmatched_subtitles,
+                available_formats: extraction
+                    .format_details()
+                    .iter()
+                    .map(|f| f.id.clone())
+                    .collect(),
+                available_subtitles: extraction.subtitle_details().into_iter().cloned().collect(),
+            }
+            .into());
+        }
+        Ok(DownloadList { formats, subtitles })
     }
 }
 
+/// What the picker would pick and why, without downloading anything
+#[derive(Serialize, Debug)]
+pub struct PickExplanation {
+    pub selection: DownloadSelection,
+    /// Why each of the formats was picked, in the same order as in the selection
+    pub reasons: Vec<String>,
+    /// The ids of all of the formats, the best first, as ranked for the picker
+    pub ranking: Vec<String>,
+}
+
 #[async_trait]
-pub trait FormatPicker {
+pub trait FormatPicker: Sync {
     async fn pick_formats(
         &self,
         selector: &str,
         extraction: &Extraction,
     ) -> anyhow::Result<DownloadSelection>;
+
+    /// Picks like [`FormatPicker::pick_formats`], also telling why.
+    /// The pickers that can't tell more just say the formats were picked
+    async fn explain_formats(
+        &self,
+        selector: &str,
+        extraction: &Extraction,
+    ) -> anyhow::Result<PickExplanation> {
+        let selection = self.pick_formats(selector, extraction).await?;
+        let reasons = selection
+            .formats
+            .iter()
+            .flatten()
+            .map(|_| "picked by the format picker".to_string())
+            .collect();
+        Ok(PickExplanation {
+            selection,
+            reasons,
+            ranking: extraction
+                .format_details()
+                .iter()
+                .map(|f| f.id.clone())
+                .collect(),
+        })
+    }
+
+    /// Which of the list entries to process, as indexes into [`ListExtraction::entry_details`].
+    /// Only the metadata given by the list is known, nothing is extracted before.
+    /// `filter` is in the picker's language, an empty one keeps every entry
+    async fn pick_entries(&self, filter: &str, list: &ListExtraction)
+        -> anyhow::Result<Vec<usize>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use reytan_extractor_api::{
+        Extraction, FormatBreed, FormatProtocol, HTTPDownloadOptions, MediaFormatDetails,
+        MediaFormatEstablished, MediaFormatURL, SubtitleDetails, SubtitleExt, SubtitlePointerURL,
+        Url,
+    };
+
+    use super::{
+        DownloadList, DownloadSelection, FormatSelection, SubtitleSelection, SubtitleSelector,
+        UnmatchedSelection,
+    };
+
+    fn format(id: &str) -> MediaFormatEstablished {
+        MediaFormatEstablished {
+            details: MediaFormatDetails {
+                id: id.to_string(),
+                breed: FormatBreed::AudioVideo,
+                video_details: None,
+                audio_details: None,
+                source: None,
+                bitrate: None,
+                protocol: FormatProtocol::HTTP,
+            },
+            url: MediaFormatURL::HTTP(
+                Url::parse("https://example.com/video").unwrap(),
+                HTTPDownloadOptions::default(),
+            ),
+        }
+    }
+
+    fn subtitle(lang: &str, ext: SubtitleExt, is_machine_generated: bool) -> SubtitlePointerURL {
+        SubtitlePointerURL {
+            details: SubtitleDetails {
+                lang: lang.to_string(),
+                is_original_lang: Some(true),
+                is_machine_generated: Some(is_machine_generated),
+                is_machine_translated: None,
+                ext,
+            },
+            url: Url::parse("https://example.com/subtitles").unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_subtitle_selection() {
+        let extraction = Extraction {
+            established_subtitles: vec![
+                subtitle("en", SubtitleExt::VTT, true),
+                subtitle("en", SubtitleExt::TTML, true),
+                subtitle("en", SubtitleExt::VTT, false),
+                subtitle("en", SubtitleExt::TTML, false),
+            ],
+            ..Default::default()
+        };
+        let picked = |subtitles: Vec<SubtitleSelection>| {
+            let selection = DownloadSelection {
+                formats: None,
+                subtitles: Some(subtitles),
+            };
+            DownloadList::from(&selection, &extraction).map(|list| {
+                list.subtitles
+                    .iter()
+                    .map(|s| {
+                        extraction
+                            .established_subtitles
+                            .iter()
+                            .position(|es| es == *s)
+                            .unwrap()
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        assert_eq!(
+            picked(vec![SubtitleSelection::Lang("en".to_string())]).unwrap(),
+            vec![0]
+        );
+        assert_eq!(
+            picked(vec![
+                SubtitleSelection::Matching(SubtitleSelector {
+                    lang: Some("en".to_string()),
+                    ext: Some(SubtitleExt::TTML),
+                    is_machine_generated: Some(false),
+                    ..Default::default()
+                }),
+                SubtitleSelection::Matching(SubtitleSelector {
+                    is_machine_generated: Some(true),
+                    ..Default::default()
+                }),
+            ])
+            .unwrap(),
+            vec![3, 0]
+        );
+        // not known counts as false
+        assert!(picked(vec![SubtitleSelection::Matching(SubtitleSelector {
+            is_machine_translated: Some(false),
+            ..Default::default()
+        })])
+        .is_ok());
+        assert!(picked(vec![SubtitleSelection::Lang("pl".to_string())]).is_err());
+        assert!(picked(vec![SubtitleSelection::Matching(SubtitleSelector {
+            ext: Some(SubtitleExt::SRT),
+            ..Default::default()
+        })])
+        .is_err());
+    }
+
+    #[test]
+    fn test_missing_format() {
+        let extraction = Extraction {
+            established_formats: vec![format("18"), format("137")],
+            established_subtitles: vec![subtitle("en", SubtitleExt::VTT, true)],
+            ..Default::default()
+        };
+        let selection = DownloadSelection {
+            formats: Some(vec![
+                FormatSelection::Full("137".to_string()),
+                FormatSelection::Full("140".to_string()),
+                FormatSelection::ExtractAudio("22".to_string()),
+            ]),
+            subtitles: Some(vec![SubtitleSelection::Lang("pl".to_string())]),
+        };
+        let error = DownloadList::from(&selection, &extraction).unwrap_err();
+        let unmatched = error.downcast_ref::<UnmatchedSelection>().unwrap();
+        assert_eq!(unmatched.formats, vec!["140", "22"]);
+        assert_eq!(
+            unmatched.subtitles,
+            vec![SubtitleSelection::Lang("pl".to_string())]
+        );
+        assert_eq!(
+            error.to_string(),
+            "no formats with the ids \"140\", \"22\" (available: \"18\", \"137\")\n\
+            no subtitles matching Lang(\"pl\") (available: en VTT (auto))"
+        );
+    }
 }
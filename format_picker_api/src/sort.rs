@@ -0,0 +1,445 @@
+//! Ordering the formats from the best one, by a list of criteria: `res<=1080,+codec:vp9,br`
+//!
+//! The criteria are compared in order, the next one is only used if the formats are equal so far.
+//! * `res`, `fps`, `br` (bitrate, with `k` and `M` allowed), `channels` - higher first.
+//!   With a limit (`res<=1080`, `br<1M`, `res:1080` being the same as `<=`),
+//!   the values within it go first, and the ones over it after, the closest first
+//! * `codec`, `vcodec`, `acodec` - `av1`, `vp9`, `hevc`, `avc`, `vp8` for video,
+//!   `flac`, `opus`, `vorbis`, `aac`, `mp3`, `eac3`, `ac3` for audio
+//!   (`codec` is the video one if there is video). With a value (`codec:vp9`), it goes first
+//! * `lang:en`, `source:ios` - the values starting with the given one first
+//! * `track` - the audio track the service would play (the default or the original one) first
+//! * `proto` - HTTP first, then HLS, then DASH (only the HTTP formats can be downloaded for now)
+//!
+//! `-` in front reverses the order (`-br` - the smallest first), `+` in front is allowed
+//! and changes nothing (`+codec:vp9`). The formats with unknown values always go last.
+
+use std::cmp::Ordering;
+
+use reytan_extractor_api::anyhow::{anyhow, bail, Result};
+use reytan_extractor_api::{AudioTrackRole, FormatProtocol, MediaFormatDetails};
+
+/// Used if no other order is given
+pub const DEFAULT_SORT: &str = "proto,track,res,fps,codec,br,channels";
+
+/// The codec families, the preferred ones first
+const VIDEO_CODECS: &[&str] = &["av1", "vp9", "hevc", "avc", "vp8"];
+const AUDIO_CODECS: &[&str] = &["flac", "opus", "vorbis", "aac", "mp3", "eac3", "ac3"];
+
+/// The codec family, from the codec as given in the RFC 6381 `codecs` parameter
+/// (`avc1.640028` -> `avc`, `mp4a.40.2` -> `aac`)
+pub fn codec_family(codec: &str) -> String {
+    let codec = codec.trim().to_lowercase();
+    let family = match codec.split('.').next().unwrap_or(&codec) {
+        "av01" | "av1" => "av1",
+        "vp09" | "vp9" => "vp9",
+        "vp08" | "vp8" => "vp8",
+        "hev1" | "hvc1" | "hevc" | "h265" => "hevc",
+        "avc1" | "avc3" | "avc" | "h264" => "avc",
+        // MPEG-1/2 audio in MP4
+        "mp4a" if codec == "mp4a.40.34" || codec == "mp4a.6b" => "mp3",
+        "mp4a" | "aac" => "aac",
+        "ec-3" | "eac3" => "eac3",
+        "ac-3" | "ac3" => "ac3",
+        other => other,
+    };
+    family.to_string()
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum SortField {
+    /// `res` - the height
+    Resolution,
+    Fps,
+    /// `br`
+    Bitrate,
+    Channels,
+    Codec,
+    VideoCodec,
+    AudioCodec,
+    Lang,
+    Source,
+    Track,
+    /// `proto`
+    Protocol,
+}
+
+impl SortField {
+    fn from_name(name: &str) -> Option<SortField> {
+        Some(match name {
+            "res" => SortField::Resolution,
+            "fps" => SortField::Fps,
+            "br" => SortField::Bitrate,
+            "channels" => SortField::Channels,
+            "codec" => SortField::Codec,
+            "vcodec" => SortField::VideoCodec,
+            "acodec" => SortField::AudioCodec,
+            "lang" => SortField::Lang,
+            "source" => SortField::Source,
+            "track" => SortField::Track,
+            "proto" => SortField::Protocol,
+            _ => return None,
+        })
+    }
+
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            SortField::Resolution | SortField::Fps | SortField::Bitrate | SortField::Channels
+        )
+    }
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SortCriterion {
+    pub field: SortField,
+    /// `-br`
+    pub reverse: bool,
+    /// `res<=1080` - the values over it go after the rest
+    pub limit: Option<u64>,
+    /// `codec:vp9`, `lang:en` - the matching values go first
+    pub preferred: Option<String>,
+}
+
+/// `128k`, `1.5M`
+fn parse_number(value: &str) -> Option<u64> {
+    let (number, multiplier) = match value.char_indices().last()? {
+        (i, 'k' | 'K') => (&value[..i], 1e3),
+        (i, 'M') => (&value[..i], 1e6),
+        _ => (value, 1.0),
+    };
+    let number = number.parse::<f64>().ok().filter(|n| *n >= 0.0)?;
+    Some((number * multiplier).round() as u64)
+}
+
+impl SortCriterion {
+    pub fn parse(input: &str) -> Result<SortCriterion> {
+        let input = input.trim();
+        let (reverse, rest) = match input.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, input.strip_prefix('+').unwrap_or(input)),
+        };
+        let (name, condition) = rest.split_at(rest.find([':', '<']).unwrap_or(rest.len()));
+        let field = SortField::from_name(name.trim())
+            .ok_or_else(|| anyhow!("unknown format sort field {:?}", name))?;
+        let mut criterion = SortCriterion {
+            field,
+            reverse,
+            limit: None,
+            preferred: None,
+        };
+        if condition.is_empty() {
+            if matches!(field, SortField::Lang | SortField::Source) {
+                bail!("{:?} needs a value, as in {}:...", input, name);
+            }
+        } else if field.is_numeric() {
+            let (value, exclusive) = match condition
+                .strip_prefix("<=")
+                .or_else(|| condition.strip_prefix(':'))
+            {
+                Some(value) => (value, false),
+                None => (&condition[1..], true),
+            };
+            let limit = parse_number(value.trim())
+                .ok_or_else(|| anyhow!("{:?} expects a number", input))?;
+            criterion.limit = Some(if exclusive {
+                limit.saturating_sub(1)
+            } else {
+                limit
+            });
+        } else {
+            let value = match condition.strip_prefix(':') {
+                Some(value)
+                    if !matches!(field, SortField::Track | SortField::Protocol)
+                        && !value.trim().is_empty() =>
+                {
+                    value
+                }
+                _ => bail!("{:?} can't be used like that", input),
+            };
+            criterion.preferred = Some(value.trim().to_lowercase());
+        }
+        Ok(criterion)
+    }
+
+    /// The value to compare, higher is better. `None` if not known
+    fn key(&self, format: &MediaFormatDetails) -> Option<(i64, i64)> {
+        let video = format.video_details.as_ref();
+        let audio = format.audio_details.as_ref();
+        let sign = if self.reverse { -1 } else { 1 };
+        let prefers = |value: &str| {
+            self.preferred
+                .as_ref()
+                .is_some_and(|p| value.to_lowercase().starts_with(p.as_str()))
+        };
+        match self.field {
+            SortField::Resolution | SortField::Fps | SortField::Bitrate | SortField::Channels => {
+                let value = match self.field {
+                    SortField::Resolution => video.and_then(|v| v.height).map(u64::from),
+                    SortField::Fps => video.and_then(|v| v.fps).map(u64::from),
+                    SortField::Bitrate => format.bitrate,
+                    _ => audio.and_then(|a| a.channels).map(u64::from),
+                }?;
+                Some(match self.limit {
+                    Some(limit) if value > limit => (0, -(value as i64)),
+                    Some(_) => (1, value as i64 * sign),
+                    None => (0, value as i64 * sign),
+                })
+            }
+            SortField::Codec | SortField::VideoCodec | SortField::AudioCodec => {
+                let video_codec = video.and_then(|v| v.codec.as_deref());
+                let audio_codec = audio.and_then(|a| a.codec.as_deref());
+                let codec = match self.field {
+                    SortField::VideoCodec => video_codec,
+                    SortField::AudioCodec => audio_codec,
+                    _ => video_codec.or(audio_codec),
+                }?;
+                let family = codec_family(codec);
+                let preferred = self
+                    .preferred
+                    .as_ref()
+                    .is_some_and(|p| codec_family(p) == family);
+                let order = [VIDEO_CODECS, AUDIO_CODECS]
+                    .into_iter()
+                    .find_map(|codecs| {
+                        let position = codecs.iter().position(|c| *c == family)?;
+                        Some((codecs.len() - position) as i64)
+                    })
+                    .unwrap_or(0);
+                Some((preferred as i64, order * sign))
+            }
+            SortField::Lang => {
+                let lang = audio.and_then(|a| a.language.as_deref())?;
+                Some((prefers(lang) as i64 * sign, 0))
+            }
+            SortField::Source => {
+                let source = format.source.as_deref()?;
+                Some((prefers(source) as i64 * sign, 0))
+            }
+            SortField::Track => {
+                let track = audio.and_then(|a| a.track.as_ref());
+                let is_main =
+                    track.is_none_or(|t| t.is_default || t.role == Some(AudioTrackRole::Original));
+                Some((is_main as i64 * sign, 0))
+            }
+            SortField::Protocol => {
+                let order = match format.protocol {
+                    FormatProtocol::HTTP => 2,
+                    FormatProtocol::HLS => 1,
+                    FormatProtocol::DASH => 0,
+                };
+                Some((order * sign, 0))
+            }
+        }
+    }
+}
+
+/// Orders the formats by the criteria, see the module docs
+#[derive(PartialEq, Clone, Debug)]
+pub struct FormatSorter {
+    pub criteria: Vec<SortCriterion>,
+}
+
+impl FormatSorter {
+    /// The comma-separated criteria, like `res<=1080,+codec:vp9,br`
+    pub fn parse(input: &str) -> Result<FormatSorter> {
+        let criteria = input
+            .split(',')
+            .filter(|c| !c.trim().is_empty())
+            .map(SortCriterion::parse)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(FormatSorter { criteria })
+    }
+
+    /// `Less` if `a` is the better one
+    pub fn compare(&self, a: &MediaFormatDetails, b: &MediaFormatDetails) -> Ordering {
+        for criterion in &self.criteria {
+            let ordering = match (criterion.key(a), criterion.key(b)) {
+                (Some(a), Some(b)) => b.cmp(&a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    }
+
+    /// The best first. The equal ones are kept in the order given by the extractor
+    pub fn sort<'a>(&self, formats: &[&'a MediaFormatDetails]) -> Vec<&'a MediaFormatDetails> {
+        let mut sorted = formats.to_vec();
+        sorted.sort_by(|a, b| self.compare(a, b));
+        sorted
+    }
+}
+
+impl Default for FormatSorter {
+    fn default() -> Self {
+        FormatSorter::parse(DEFAULT_SORT).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reytan_extractor_api::{
+        AudioDetails, FormatBreed, FormatProtocol, MediaFormatDetails, VideoDetails,
+    };
+
+    use super::{codec_family, FormatSorter, SortCriterion, SortField};
+
+    fn video(id: &str, height: u32, fps: u32, codec: &str, bitrate: u64) -> MediaFormatDetails {
+        MediaFormatDetails {
+            id: id.to_string(),
+            breed: FormatBreed::Video,
+            video_details: Some(VideoDetails {
+                width: None,
+                height: Some(height),
+                fps: Some(fps),
+                codec: Some(codec.to_string()),
+            }),
+            audio_details: None,
+            source: None,
+            bitrate: Some(bitrate),
+            protocol: FormatProtocol::HTTP,
+        }
+    }
+
+    fn audio(id: &str, codec: &str, language: &str, bitrate: Option<u64>) -> MediaFormatDetails {
+        MediaFormatDetails {
+            id: id.to_string(),
+            breed: FormatBreed::Audio,
+            video_details: None,
+            audio_details: Some(AudioDetails {
+                codec: Some(codec.to_string()),
+                language: Some(language.to_string()),
+                ..Default::default()
+            }),
+            source: None,
+            bitrate,
+            protocol: FormatProtocol::HTTP,
+        }
+    }
+
+    fn sorted(order: &str, formats: &[MediaFormatDetails]) -> Vec<String> {
+        let formats = formats.iter().collect::<Vec<_>>();
+        FormatSorter::parse(order)
+            .unwrap()
+            .sort(&formats)
+            .into_iter()
+            .map(|f| f.id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            SortCriterion::parse("-br<128k").unwrap(),
+            SortCriterion {
+                field: SortField::Bitrate,
+                reverse: true,
+                limit: Some(127_999),
+                preferred: None,
+            }
+        );
+        assert_eq!(
+            SortCriterion::parse("+codec:VP9").unwrap(),
+            SortCriterion {
+                field: SortField::Codec,
+                reverse: false,
+                limit: None,
+                preferred: Some("vp9".to_string()),
+            }
+        );
+        assert_eq!(
+            SortCriterion::parse("res:1080").unwrap(),
+            SortCriterion::parse("res<=1080").unwrap()
+        );
+        for invalid in [
+            "resolution",
+            "res<=hd",
+            "lang",
+            "lang<en",
+            "track:main",
+            "proto:http",
+            "codec:",
+        ] {
+            assert!(SortCriterion::parse(invalid).is_err(), "{:?}", invalid);
+        }
+        assert_eq!(FormatSorter::default().criteria.len(), 7);
+    }
+
+    #[test]
+    fn test_codec_family() {
+        assert_eq!(codec_family("avc1.640028"), "avc");
+        assert_eq!(codec_family("vp09.00.51.08"), "vp9");
+        assert_eq!(codec_family("av01.0.08M.08"), "av1");
+        assert_eq!(codec_family("mp4a.40.2"), "aac");
+        assert_eq!(codec_family("opus"), "opus");
+    }
+
+    #[test]
+    fn test_sort() {
+        let videos = [
+            video("137", 1080, 30, "avc1.640028", 4_000_000),
+            video("248", 1080, 30, "vp9", 2_500_000),
+            video("299", 1080, 60, "avc1.64002a", 6_000_000),
+            video("313", 2160, 30, "vp9", 18_000_000),
+            video("135", 480, 30, "avc1.4d401e", 1_000_000),
+        ];
+        assert_eq!(
+            sorted("res,fps,codec", &videos),
+            vec!["313", "299", "248", "137", "135"]
+        );
+        assert_eq!(
+            sorted("res<=1080,codec:avc,fps", &videos),
+            vec!["299", "137", "248", "135", "313"]
+        );
+        assert_eq!(
+            sorted("res<720,br", &videos),
+            vec!["135", "299", "137", "248", "313"]
+        );
+        assert_eq!(
+            sorted("-br", &videos),
+            vec!["135", "248", "137", "299", "313"]
+        );
+
+        let audios = [
+            audio("140", "mp4a.40.2", "en", Some(128_000)),
+            audio("251", "opus", "en", Some(130_000)),
+            audio("251-de", "opus", "de", None),
+            audio("mp3-128", "mp3", "en", Some(128_000)),
+        ];
+        assert_eq!(
+            sorted("lang:de,codec", &audios),
+            vec!["251-de", "251", "140", "mp3-128"]
+        );
+        // unknown last, also when reversed; equal ones in the given order
+        assert_eq!(
+            sorted("-br", &audios),
+            vec!["140", "mp3-128", "251", "251-de"]
+        );
+        // the manifest formats after the downloadable ones
+        let mut dash = video("299-dash", 1080, 60, "avc1.64002a", 9_000_000);
+        dash.protocol = FormatProtocol::DASH;
+        let mut hls = video("96-hls", 1080, 30, "avc1.640028", 5_000_000);
+        hls.protocol = FormatProtocol::HLS;
+        assert_eq!(
+            sorted(
+                super::DEFAULT_SORT,
+                &[dash.clone(), hls.clone(), videos[4].clone()]
+            ),
+            vec!["135", "96-hls", "299-dash"]
+        );
+        assert_eq!(
+            sorted("res,fps", &[dash, hls, videos[4].clone()]),
+            vec!["299-dash", "96-hls", "135"]
+        );
+        // no codec to sort the video by
+        assert_eq!(
+            sorted("acodec", &[videos[0].clone(), audios[3].clone()]),
+            vec!["mp3-128", "137"]
+        );
+    }
+}
@@ -0,0 +1,4 @@
+// imported from the library path in the tests
+{
+  fail(reason):: error reason,
+}
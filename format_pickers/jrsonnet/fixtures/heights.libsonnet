@@ -0,0 +1,5 @@
+// imported from the library path in the tests
+{
+  upTo(formats, max)::
+    std.filter(function(f) f.video_details != null && f.video_details.height <= max, formats),
+}
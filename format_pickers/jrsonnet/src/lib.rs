@@ -1,14 +1,217 @@
-use std::path::PathBuf;
+use std::any::Any;
+use std::cell::RefCell;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
-use jrsonnet_evaluator::{EvaluationState, Val};
-use reytan_format_picker_api::{anyhow, async_trait, DownloadSelection, Extraction, FormatPicker};
+use jrsonnet_evaluator::{
+    EvaluationState, ExprLocation, FileImportResolver, IStr, ImportResolver, LocError, Val,
+};
+use reytan_format_picker_api::{
+    anyhow, async_trait, DownloadSelection, Extraction, FormatPicker, FormatSorter, ListExtraction,
+    Lru, PickExplanation,
+};
 use serde_json::json;
 
-pub struct JrsonnetFormatPicker {}
+/// The helpers bundled with reytan, see the file for what's there
+const REYTAN_LIBSONNET_NAME: &str = "reytan.libsonnet";
+const REYTAN_LIBSONNET: &str = include_str!("reytan.libsonnet");
+
+/// What the picker code is evaluated as, in the error positions
+const SNIPPET_NAME: &str = "jsonnet_format";
+
+/// An error from the Jsonnet code, with where it happened
+#[derive(Debug)]
+pub struct JsonnetError {
+    pub message: String,
+    /// The innermost first
+    pub trace: Vec<JsonnetLocation>,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct JsonnetLocation {
+    /// `jsonnet_format` for the picker code itself
+    pub file: PathBuf,
+    /// Starting from 1
+    pub line: usize,
+    /// Starting from 1, in characters
+    pub column: usize,
+    pub description: String,
+}
+
+impl fmt::Display for JsonnetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        for location in &self.trace {
+            write!(
+                f,
+                "\n    at {}:{}:{}",
+                location.file.display(),
+                location.line,
+                location.column
+            )?;
+            if !location.description.is_empty() {
+                write!(f, ": {}", location.description)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for JsonnetError {}
+
+/// The line and column of the byte offset, both starting from 1
+fn line_column(source: &str, offset: usize) -> Option<(usize, usize)> {
+    let before = source.get(..offset)?;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Some((
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    ))
+}
+
+impl JsonnetError {
+    /// With the positions in the sources the state evaluated
+    fn new(error: LocError, vm: &EvaluationState) -> Self {
+        let trace = error
+            .trace()
+            .0
+            .iter()
+            .filter_map(|element| {
+                let ExprLocation(path, begin, _) = element.location.as_ref()?;
+                let (line, column) = line_column(&vm.get_source(path)?, *begin)?;
+                Some(JsonnetLocation {
+                    file: path.to_path_buf(),
+                    line,
+                    column,
+                    description: element.desc.clone(),
+                })
+            })
+            .collect();
+        JsonnetError {
+            message: error.error().to_string(),
+            trace,
+        }
+    }
+}
+
+/// Serves `reytan.libsonnet`, and the other imports from the library paths
+struct ReytanImportResolver {
+    files: FileImportResolver,
+}
+
+impl ImportResolver for ReytanImportResolver {
+    fn resolve_file(&self, from: &Path, path: &Path) -> jrsonnet_evaluator::Result<Rc<Path>> {
+        if path == Path::new(REYTAN_LIBSONNET_NAME) {
+            return Ok(PathBuf::from(REYTAN_LIBSONNET_NAME).into());
+        }
+        self.files.resolve_file(from, path)
+    }
+
+    fn load_file_contents(&self, resolved: &Path) -> jrsonnet_evaluator::Result<IStr> {
+        if resolved == Path::new(REYTAN_LIBSONNET_NAME) {
+            return Ok(REYTAN_LIBSONNET.into());
+        }
+        self.files.load_file_contents(resolved)
+    }
+
+    unsafe fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// What the evaluation state is set up with
+#[derive(PartialEq, Clone, Default, Debug)]
+struct StateConfig {
+    library_paths: Vec<PathBuf>,
+    ext_strs: Vec<(String, String)>,
+    ext_codes: Vec<(String, String)>,
+}
+
+impl StateConfig {
+    fn new_state(&self) -> anyhow::Result<EvaluationState> {
+        let vm = EvaluationState::default();
+        vm.with_stdlib();
+        vm.set_import_resolver(Box::new(ReytanImportResolver {
+            files: FileImportResolver {
+                library_paths: self.library_paths.clone(),
+            },
+        }));
+        for (name, value) in &self.ext_strs {
+            vm.add_ext_str(name.as_str().into(), value.as_str().into());
+        }
+        for (name, code) in &self.ext_codes {
+            vm.add_ext_code(name.as_str().into(), code.as_str().into())
+                .map_err(|e| anyhow::anyhow!("in the external variable {}: {}", name, e.error()))?;
+        }
+        Ok(vm)
+    }
+}
+
+/// How many evaluation states are kept per thread, for the pickers set up differently
+const STATES_PER_THREAD: usize = 4;
+
+thread_local! {
+    /// Evaluation states are not Send. Reusing them keeps the imported files parsed between the picks
+    static STATES: RefCell<Lru<StateConfig, EvaluationState>> =
+        const { RefCell::new(Lru::new(STATES_PER_THREAD)) };
+}
+
+fn with_state<T>(
+    config: &StateConfig,
+    f: impl FnOnce(&EvaluationState) -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    STATES.with(|states| {
+        states
+            .borrow_mut()
+            .with(config, || config.new_state(), |vm| f(vm))
+    })
+}
+
+/// Picks the formats with Jsonnet code, a function called with the extraction:
+/// `function(input) {formats: [{Full: input.formats[0].id}]}`.
+/// The list entries are picked the same way, returning their indexes:
+/// `function(input) [i for i in std.range(0, std.length(input.entries) - 1) if i % 2 == 0]`.
+/// The helpers from `import 'reytan.libsonnet'` are always available
+pub struct JrsonnetFormatPicker {
+    sorter: FormatSorter,
+    config: StateConfig,
+}
 
 impl JrsonnetFormatPicker {
     pub fn new() -> Self {
-        JrsonnetFormatPicker {}
+        JrsonnetFormatPicker {
+            sorter: FormatSorter::default(),
+            config: StateConfig::default(),
+        }
+    }
+
+    /// The order of `formats` in the input, the best first
+    pub fn with_sorter(mut self, sorter: FormatSorter) -> Self {
+        self.sorter = sorter;
+        self
+    }
+
+    /// A directory to look for the imported files in, after the current one
+    pub fn with_library_path(mut self, path: PathBuf) -> Self {
+        self.config.library_paths.push(path);
+        self
+    }
+
+    /// Available as `std.extVar(name)`
+    pub fn with_ext_str(mut self, name: &str, value: &str) -> Self {
+        self.config
+            .ext_strs
+            .push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /// Available as `std.extVar(name)`, evaluated from the Jsonnet code
+    pub fn with_ext_code(mut self, name: &str, code: &str) -> Self {
+        self.config
+            .ext_codes
+            .push((name.to_string(), code.to_string()));
+        self
     }
 }
 
@@ -21,6 +224,33 @@ impl FormatPicker for JrsonnetFormatPicker {
     ) -> anyhow::Result<DownloadSelection> {
         self.pick_formats_real(selector, extraction)
     }
+
+    async fn pick_entries(
+        &self,
+        filter: &str,
+        list: &ListExtraction,
+    ) -> anyhow::Result<Vec<usize>> {
+        self.pick_entries_real(filter, list)
+    }
+
+    async fn explain_formats(
+        &self,
+        selector: &str,
+        extraction: &Extraction,
+    ) -> anyhow::Result<PickExplanation> {
+        let selection = self.pick_formats_real(selector, extraction)?;
+        let picked = selection.formats.as_ref().map_or(0, Vec::len);
+        Ok(PickExplanation {
+            selection,
+            reasons: vec!["returned by the Jsonnet code".to_string(); picked],
+            ranking: self
+                .sorter
+                .sort(&extraction.format_details())
+                .iter()
+                .map(|f| f.id.clone())
+                .collect(),
+        })
+    }
 }
 impl JrsonnetFormatPicker {
     fn pick_formats_real(
@@ -28,20 +258,389 @@ impl JrsonnetFormatPicker {
         selector: &str,
         extraction: &Extraction,
     ) -> anyhow::Result<DownloadSelection> {
-        let vm = EvaluationState::default();
-        let input = json!({
+        let input = self.formats_input(extraction);
+        Ok(serde_json::from_str(&self.evaluate(selector, &input)?)?)
+    }
+
+    fn formats_input(&self, extraction: &Extraction) -> serde_json::Value {
+        json!({
             "media": &extraction.metadata,
-            "formats": extraction.format_details(),
+            "formats": self.sorter.sort(&extraction.format_details()),
             "subtitles": extraction.subtitle_details(),
+        })
+    }
+
+    fn pick_entries_real(&self, filter: &str, list: &ListExtraction) -> anyhow::Result<Vec<usize>> {
+        let entries = list.entry_details();
+        if filter.trim().is_empty() {
+            return Ok((0..entries.len()).collect());
+        }
+        let input = json!({
+            "list": {
+                "id": &list.id,
+                "title": &list.title,
+                "description": &list.description,
+                "owner": &list.owner,
+                "item_count": list.item_count,
+                "modified_time": list.modified_time,
+            },
+            "entries": entries,
         });
-        vm.add_tla("input".into(), Val::from(&input));
-        Ok(serde_json::from_str(
-            &vm.with_stdlib()
-                .evaluate_snippet_raw(PathBuf::from("jsonnet_format").into(), selector.into())
+        Ok(serde_json::from_str(&self.evaluate(filter, &input)?)?)
+    }
+
+    /// Calls the code with the input, returns the JSON output
+    fn evaluate(&self, code: &str, input: &serde_json::Value) -> anyhow::Result<String> {
+        with_state(&self.config, |vm| {
+            vm.add_tla("input".into(), Val::from(input));
+            let output = vm
+                .evaluate_snippet_raw(PathBuf::from(SNIPPET_NAME).into(), code.into())
                 .and_then(|v| vm.with_tla(v))
                 .and_then(|v| vm.manifest(v))
-                .map_err(|e| anyhow::Error::msg(e.error().to_string()))?
-                .to_string(),
-        )?)
+                .map_err(|e| JsonnetError::new(e, vm));
+            // not to keep the extraction in the cached state until the next pick
+            vm.add_tla("input".into(), Val::Null);
+            Ok(output?.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use reytan_extractor_api::{
+        AnyExtraction, AudioDetails, DateTime, Extraction, FormatBreed, FormatProtocol,
+        HTTPDownloadOptions, ListExtraction, MediaFormatDetails, MediaFormatEstablished,
+        MediaFormatURL, MediaMetadata, SubtitleDetails, SubtitleExt, SubtitlePointerURL, Url,
+        VideoDetails,
+    };
+    use reytan_format_picker_api::{FormatSelection, Lru, SubtitleSelection, SubtitleSelector};
+    use serde_json::json;
+
+    use super::{line_column, JrsonnetFormatPicker, JsonnetError, JsonnetLocation};
+
+    fn format(
+        id: &str,
+        height: Option<u32>,
+        video_codec: Option<&str>,
+        audio_codec: Option<&str>,
+        language: Option<&str>,
+    ) -> MediaFormatEstablished {
+        MediaFormatEstablished {
+            details: MediaFormatDetails {
+                id: id.to_string(),
+                breed: match (height, audio_codec) {
+                    (Some(_), Some(_)) => FormatBreed::AudioVideo,
+                    (Some(_), None) => FormatBreed::Video,
+                    _ => FormatBreed::Audio,
+                },
+                video_details: height.map(|height| VideoDetails {
+                    height: Some(height),
+                    codec: video_codec.map(str::to_string),
+                    ..Default::default()
+                }),
+                audio_details: audio_codec.map(|codec| AudioDetails {
+                    codec: Some(codec.to_string()),
+                    language: language.map(str::to_string),
+                    ..Default::default()
+                }),
+                source: None,
+                bitrate: None,
+                protocol: FormatProtocol::HTTP,
+            },
+            url: MediaFormatURL::HTTP(
+                Url::parse("https://example.com/video").unwrap(),
+                HTTPDownloadOptions::default(),
+            ),
+        }
+    }
+
+    fn subtitle(lang: &str, is_machine_generated: bool) -> SubtitlePointerURL {
+        SubtitlePointerURL {
+            details: SubtitleDetails {
+                lang: lang.to_string(),
+                is_original_lang: Some(true),
+                is_machine_generated: Some(is_machine_generated),
+                is_machine_translated: None,
+                ext: SubtitleExt::VTT,
+            },
+            url: Url::parse("https://example.com/subtitles").unwrap(),
+        }
+    }
+
+    /// Sorted as 137, 136, 18, 251, 140
+    fn extraction() -> Extraction {
+        Extraction {
+            established_formats: vec![
+                format(
+                    "18",
+                    Some(360),
+                    Some("avc1.42001E"),
+                    Some("mp4a.40.2"),
+                    Some("en"),
+                ),
+                format("140", None, None, Some("mp4a.40.2"), Some("de")),
+                format("251", None, None, Some("opus"), Some("en-US")),
+                format("136", Some(720), Some("avc1.4d401f"), None, None),
+                format("137", Some(1080), Some("vp09.00.40.08"), None, None),
+            ],
+            established_subtitles: vec![
+                subtitle("en", true),
+                subtitle("en", false),
+                subtitle("pl", false),
+            ],
+            ..Default::default()
+        }
+    }
+
+    fn evaluate(picker: &JrsonnetFormatPicker, code: &str) -> serde_json::Value {
+        let input = picker.formats_input(&extraction());
+        serde_json::from_str(&picker.evaluate(code, &input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_reytan_libsonnet() {
+        let picker = JrsonnetFormatPicker::new();
+        let ids = |formats: &str| {
+            evaluate(
+                &picker,
+                &format!(
+                    "local r = import 'reytan.libsonnet';
+                    function(input) [f.id for f in {}]",
+                    formats
+                ),
+            )
+        };
+        assert_eq!(ids("r.byHeight(input.formats, 720)"), json!(["136", "18"]));
+        assert_eq!(
+            ids("r.byCodec(input.formats, 'avc1')"),
+            json!(["136", "18"])
+        );
+        assert_eq!(
+            ids("r.byCodec(input.formats, 'mp4a')"),
+            json!(["18", "140"])
+        );
+        assert_eq!(ids("r.byLang(input.formats, 'en')"), json!(["18", "251"]));
+        assert_eq!(ids("r.byLang(input.formats, 'de')"), json!(["140"]));
+
+        let merged = |formats: &str| {
+            evaluate(
+                &picker,
+                &format!(
+                    "local r = import 'reytan.libsonnet';
+                    function(input) r.bestMerged({})",
+                    formats
+                ),
+            )
+        };
+        assert_eq!(
+            merged("input.formats"),
+            json!([{"Full": "137"}, {"Full": "251"}])
+        );
+        assert_eq!(merged("r.combined(input.formats)"), json!([{"Full": "18"}]));
+        assert_eq!(merged("[]"), json!([]));
+
+        assert_eq!(
+            evaluate(
+                &picker,
+                "local r = import 'reytan.libsonnet';
+                function(input) [s.is_machine_generated
+                    for s in r.subtitlesByLang(input.subtitles, ['de', 'en', 'pl'])]"
+            ),
+            json!([false, false])
+        );
+
+        let selection = picker
+            .pick_formats_real(
+                "local r = import 'reytan.libsonnet';
+                function(input) r.pick(input.formats, input.subtitles, ['en'])",
+                &extraction(),
+            )
+            .unwrap();
+        assert_eq!(
+            selection.formats,
+            Some(vec![
+                FormatSelection::Full("137".to_string()),
+                FormatSelection::Full("251".to_string()),
+            ])
+        );
+        assert_eq!(
+            selection.subtitles,
+            Some(vec![SubtitleSelection::Matching(SubtitleSelector {
+                lang: Some("en".to_string()),
+                ext: Some(SubtitleExt::VTT),
+                is_original_lang: Some(true),
+                is_machine_generated: Some(false),
+                is_machine_translated: None,
+            })])
+        );
+    }
+
+    #[test]
+    fn test_library_path_and_ext_vars() {
+        let picker = JrsonnetFormatPicker::new()
+            .with_library_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures"))
+            .with_ext_str("lang", "en")
+            .with_ext_code("max_height", "360 + 360");
+        let selection = picker
+            .pick_formats_real(
+                "local heights = import 'heights.libsonnet';
+                function(input) {
+                    formats: [{Full: heights.upTo(input.formats, std.extVar('max_height'))[0].id}],
+                    subtitles: [std.extVar('lang')],
+                }",
+                &extraction(),
+            )
+            .unwrap();
+        assert_eq!(
+            selection.formats,
+            Some(vec![FormatSelection::Full("136".to_string())])
+        );
+        assert_eq!(
+            selection.subtitles,
+            Some(vec![SubtitleSelection::Lang("en".to_string())])
+        );
+
+        // not importable without the library path
+        assert!(JrsonnetFormatPicker::new()
+            .pick_formats_real(
+                "local heights = import 'heights.libsonnet'; function(input) {}",
+                &extraction(),
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_cached_states() {
+        let lang =
+            |picker: &JrsonnetFormatPicker| evaluate(picker, "function(input) std.extVar('lang')");
+        super::STATES.with(|states| *states.borrow_mut() = Lru::new(super::STATES_PER_THREAD));
+        let en = JrsonnetFormatPicker::new().with_ext_str("lang", "en");
+        let de = JrsonnetFormatPicker::new().with_ext_str("lang", "de");
+        assert_eq!(lang(&en), json!("en"));
+        assert_eq!(lang(&de), json!("de"));
+        assert_eq!(lang(&en), json!("en"));
+        // the same setup, the same state
+        assert_eq!(
+            lang(&JrsonnetFormatPicker::new().with_ext_str("lang", "de")),
+            json!("de")
+        );
+        super::STATES.with(|states| assert_eq!(states.borrow().len(), 2));
+    }
+
+    fn list() -> ListExtraction {
+        let recording = |id: &str, secs: u64, published: &str| {
+            AnyExtraction::Recording(Extraction {
+                metadata: MediaMetadata {
+                    id: id.to_string(),
+                    duration: Some(Duration::from_secs(secs)),
+                    published_time: Some(DateTime::parse_from_rfc3339(published).unwrap().into()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+        };
+        ListExtraction {
+            id: "PL1".to_string(),
+            title: "List".to_string(),
+            entries: Some(Ok(vec![
+                // 2023-01-06 in UTC
+                recording("a", 600, "2023-01-05T23:30:00-02:00"),
+                recording("b", 7200, "2023-01-04T12:00:00.123Z"),
+                AnyExtraction::List(ListExtraction {
+                    id: "PL2".to_string(),
+                    title: "Nested".to_string(),
+                    item_count: Some(3),
+                    ..Default::default()
+                }),
+                recording("c", 60, "2022-12-31T00:00:00Z"),
+            ])),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_pick_entries() {
+        let picker = JrsonnetFormatPicker::new();
+        let picked = |filter: &str| picker.pick_entries_real(filter, &list()).unwrap();
+        let matching = |predicate: &str| {
+            picked(&format!(
+                "local r = import 'reytan.libsonnet';
+                function(input) r.entryIndexes(input.entries, function(e) {})",
+                predicate
+            ))
+        };
+        assert_eq!(picked(""), vec![0, 1, 2, 3]);
+        assert_eq!(
+            picked("function(input) if input.list.id == 'PL1' then [1] else []"),
+            vec![1]
+        );
+        assert_eq!(
+            matching("r.isRecording(e) && r.durationSecs(e) < 3600"),
+            vec![0, 3]
+        );
+        assert_eq!(matching("!r.isRecording(e)"), vec![2]);
+        assert_eq!(matching("r.publishedSince(e, '2023-01-05')"), vec![0]);
+        assert_eq!(matching("r.publishedSince(e, '2023-01-04')"), vec![0, 1]);
+        assert_eq!(matching("e.kind == 'list' && e.item_count == 3"), vec![2]);
+    }
+
+    #[test]
+    fn test_line_column() {
+        let source = "local a = 1;\nlocal ż = 2;\nż + b";
+        assert_eq!(line_column(source, 0), Some((1, 1)));
+        assert_eq!(line_column(source, 13), Some((2, 1)));
+        assert_eq!(line_column(source, 32), Some((3, 5)));
+        assert_eq!(line_column(source, 100), None);
+    }
+
+    #[test]
+    fn test_error_positions() {
+        let error = |picker: &JrsonnetFormatPicker, code: &str| {
+            picker
+                .pick_formats_real(code, &extraction())
+                .unwrap_err()
+                .downcast::<JsonnetError>()
+                .unwrap()
+        };
+
+        let snippet = error(
+            &JrsonnetFormatPicker::new(),
+            "function(input)\n  error 'no formats'",
+        );
+        assert!(snippet.message.contains("no formats"));
+        assert_eq!(snippet.trace[0].file, PathBuf::from("jsonnet_format"));
+        assert_eq!((snippet.trace[0].line, snippet.trace[0].column), (2, 3));
+
+        let imported = error(
+            &JrsonnetFormatPicker::new()
+                .with_library_path(Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures")),
+            "local errors = import 'errors.libsonnet';\nfunction(input)\n  errors.fail('no formats')",
+        );
+        assert!(imported.trace[0].file.ends_with("errors.libsonnet"));
+        assert_eq!((imported.trace[0].line, imported.trace[0].column), (3, 18));
+        assert!(imported
+            .trace
+            .iter()
+            .any(|l| l.file == Path::new("jsonnet_format") && (l.line, l.column) == (3, 3)));
+    }
+
+    #[test]
+    fn test_error_display() {
+        let error = JsonnetError {
+            message: "variable is not defined: b".to_string(),
+            trace: vec![JsonnetLocation {
+                file: PathBuf::from("jsonnet_format"),
+                line: 3,
+                column: 5,
+                description: "variable <b>".to_string(),
+            }],
+        };
+        assert_eq!(
+            error.to_string(),
+            "variable is not defined: b\n    at jsonnet_format:3:5: variable <b>"
+        );
     }
 }
@@ -0,0 +1,87 @@
+// Helpers for the Jsonnet format pickers, bundled with reytan:
+//
+//   local reytan = import 'reytan.libsonnet';
+//   function(input) reytan.pick(reytan.byHeight(input.formats, 1080), input.subtitles, ['en'])
+//
+// `input.formats` are sorted, the best first, so the first matching format is the best one.
+{
+  local first(list) = if std.length(list) > 0 then list[0] else null,
+  // 'YYYY-MM-DD', or an RFC 3339 time starting with it
+  local day(time) =
+    std.parseInt(std.substr(time, 0, 4)) * 10000
+    + std.parseInt(std.substr(time, 5, 2)) * 100
+    + std.parseInt(std.substr(time, 8, 2)),
+
+  isVideo(format):: format.breed == 'Video' || format.breed == 'AudioVideo',
+  isAudio(format):: format.breed == 'Audio' || format.breed == 'AudioVideo',
+
+  videoOnly(formats):: std.filter(function(f) f.breed == 'Video', formats),
+  audioOnly(formats):: std.filter(function(f) f.breed == 'Audio', formats),
+  combined(formats):: std.filter(function(f) f.breed == 'AudioVideo', formats),
+
+  height(format)::
+    if format.video_details != null then format.video_details.height else null,
+  videoCodec(format)::
+    if format.video_details != null then format.video_details.codec else null,
+  audioCodec(format)::
+    if format.audio_details != null then format.audio_details.codec else null,
+  lang(format)::
+    if format.audio_details != null then format.audio_details.language else null,
+
+  // with the height known and not over `max`
+  byHeight(formats, max)::
+    std.filter(function(f) $.height(f) != null && $.height(f) <= max, formats),
+  // with the video or audio codec starting with `codec`, e.g. 'vp09', 'avc1', 'opus'
+  byCodec(formats, codec)::
+    local matches(c) = c != null && std.startsWith(c, codec);
+    std.filter(function(f) matches($.videoCodec(f)) || matches($.audioCodec(f)), formats),
+  // with the audio language `lang` or its variant ('en' matches 'en-US')
+  byLang(formats, lang)::
+    local matches(l) = l != null && (l == lang || std.startsWith(l, lang + '-'));
+    std.filter(function(f) matches($.lang(f)), formats),
+
+  bestVideo(formats):: first($.videoOnly(formats)),
+  bestAudio(formats):: first($.audioOnly(formats)),
+  best(formats):: first($.combined(formats)),
+
+  full(format):: { Full: format.id },
+  extractVideo(format):: { ExtractVideo: format.id },
+  extractAudio(format):: { ExtractAudio: format.id },
+
+  // the best video with the best audio, or the best format with both (`bv+ba/b`)
+  bestMerged(formats)::
+    local video = $.bestVideo(formats);
+    local audio = $.bestAudio(formats);
+    local both = $.best(formats);
+    if video != null && audio != null then [$.full(video), $.full(audio)]
+    else if both != null then [$.full(both)]
+    else [],
+
+  // the first subtitles for each of the languages that has them,
+  // the human-made ones before the machine generated ones
+  subtitlesByLang(subtitles, langs)::
+    local human = std.filter(function(s) s.is_machine_generated != true, subtitles);
+    local machine = std.filter(function(s) s.is_machine_generated == true, subtitles);
+    local forLang(lang) = first(std.filter(function(s) s.lang == lang, human + machine));
+    std.filter(function(s) s != null, [forLang(lang) for lang in langs]),
+
+  // the indexes of the entries matching the predicate, the output of the list entry filters:
+  //   function(input) reytan.entryIndexes(input.entries, function(e) reytan.durationSecs(e) < 3600)
+  entryIndexes(entries, predicate)::
+    [i for i in std.range(0, std.length(entries) - 1) if predicate(entries[i])],
+  isRecording(entry):: entry.kind == 'recording',
+  durationSecs(entry)::
+    if std.objectHas(entry, 'duration') && entry.duration != null then entry.duration.secs else null,
+  // published (or created) on the day or later, `date` as 'YYYY-MM-DD'.
+  // The times in the input are in UTC, so the days are compared as numbers (20230105)
+  publishedSince(entry, date)::
+    local time(field) = if std.objectHas(entry, field) then entry[field] else null;
+    local published = if time('published_time') != null then time('published_time') else time('created_time');
+    published != null && day(published) >= day(date),
+
+  // the whole output: `bestMerged` of the formats, and the subtitles for the languages
+  pick(formats, subtitles=[], langs=[]):: {
+    formats: $.bestMerged(formats),
+    subtitles: $.subtitlesByLang(subtitles, langs),
+  },
+}
@@ -0,0 +1,731 @@
+//! Format picker with a compact selector language, available without any scripting engine.
+//!
+//! * `bv+ba/b` - the best video with the best audio, or the best format with both
+//! * `bv[height<=1080]+ba[lang^=en]` - filters: `height`, `width`, `channels` (numbers),
+//!   `id`, `source`, `lang` (audio language), `track` (audio track id), `role`
+//!   (`original`, `dubbed`, `descriptive`), `proto` (`http`, `hls`, `dash`);
+//!   operators: `=`, `!=`, `<`, `<=`, `>`, `>=`, `^=` (starts with).
+//!   With `?` after the operator (`[height<=?1080]`), unknown values match too
+//! * `b`/`best`, `w`/`worst` - a format with both audio and video,
+//!   `bv`, `ba`, `wv`, `wa` - video or audio only, `bv*`, `ba*`, `wv*`, `wa*` - with or without the other
+//! * anything else is a format id
+//!
+//! The best and the worst are decided by the [`FormatSorter`] order.
+//! The subtitles are not a part of the selector, see [`NativeFormatPicker::with_subtitles`]
+//!
+//! The list entries are picked with the same filters, all of them have to match:
+//! `[duration<=3600][date>=20220101]`. Fields: `duration` (seconds), `views`,
+//! `date` (published or created, `YYYYMMDD`), `index` (1-based), `age_limit` (numbers),
+//! `kind` (`recording`, `list`), `id`, `title`, `uploader`, `uploader_id`,
+//! `live` (`not_live`, `is_live`, `was_live`, `is_upcoming`, `post_live`),
+//! `availability` (`playable`, `unplayable`, `private`, `deleted`)
+
+use std::cmp::Ordering;
+
+use reytan_format_picker_api::anyhow::{anyhow, Error, Result};
+use reytan_format_picker_api::chrono::Datelike;
+use reytan_format_picker_api::{
+    async_trait, AudioTrackRole, Availability, DownloadSelection, EntryDetails, Extraction,
+    FormatBreed, FormatPicker, FormatProtocol, FormatSelection, FormatSorter, ListExtraction,
+    LiveStatus, MediaFormatDetails, PickExplanation, SubtitleSelection,
+};
+
+mod parse;
+
+pub use parse::{parse, parse_entry_filters};
+use parse::{Alternative, Filter, Item, ItemKind, Op, Order, Selector, Value, Wants};
+
+/// Used if no selector is given. Only the HTTP formats, as the HLS and DASH ones
+/// can't be downloaded yet
+pub const DEFAULT_SELECTOR: &str = "bv[proto=http]+ba[proto=http]/b[proto=http]";
+
+pub struct NativeFormatPicker {
+    sorter: FormatSorter,
+    subtitles: Vec<SubtitleSelection>,
+}
+
+impl NativeFormatPicker {
+    pub fn new() -> Self {
+        NativeFormatPicker {
+            sorter: FormatSorter::default(),
+            subtitles: Vec::new(),
+        }
+    }
+
+    pub fn with_sorter(mut self, sorter: FormatSorter) -> Self {
+        self.sorter = sorter;
+        self
+    }
+
+    /// The subtitles picked along with the formats, none by default
+    pub fn with_subtitles(mut self, subtitles: Vec<SubtitleSelection>) -> Self {
+        self.subtitles = subtitles;
+        self
+    }
+
+    fn subtitle_selection(&self) -> Option<Vec<SubtitleSelection>> {
+        Some(self.subtitles.clone()).filter(|s| !s.is_empty())
+    }
+}
+
+#[async_trait]
+impl FormatPicker for NativeFormatPicker {
+    async fn pick_formats(
+        &self,
+        selector: &str,
+        extraction: &Extraction,
+    ) -> Result<DownloadSelection> {
+        let details = extraction.format_details();
+        let formats = select(&parse(selector)?, &details, &self.sorter)
+            .ok_or_else(|| no_match_error(selector, &details))?;
+        Ok(DownloadSelection {
+            formats: Some(formats),
+            subtitles: self.subtitle_selection(),
+        })
+    }
+
+    async fn explain_formats(
+        &self,
+        selector: &str,
+        extraction: &Extraction,
+    ) -> Result<PickExplanation> {
+        let formats = extraction.format_details();
+        let (selections, reasons) = explain(&parse(selector)?, &formats, &self.sorter)
+            .ok_or_else(|| no_match_error(selector, &formats))?
+            .into_iter()
+            .unzip();
+        Ok(PickExplanation {
+            selection: DownloadSelection {
+                formats: Some(selections),
+                subtitles: self.subtitle_selection(),
+            },
+            reasons,
+            ranking: self
+                .sorter
+                .sort(&formats)
+                .iter()
+                .map(|f| f.id.clone())
+                .collect(),
+        })
+    }
+
+    async fn pick_entries(&self, filter: &str, list: &ListExtraction) -> Result<Vec<usize>> {
+        Ok(select_entries(
+            &parse_entry_filters(filter)?,
+            &list.entry_details(),
+        ))
+    }
+}
+
+fn no_match_error(selector: &str, formats: &[&MediaFormatDetails]) -> Error {
+    if !formats.is_empty() && formats.iter().all(|f| f.protocol != FormatProtocol::HTTP) {
+        anyhow!(
+            "no formats matching {:?}, there are only HLS and DASH formats (not downloadable yet)",
+            selector
+        )
+    } else {
+        anyhow!("no formats matching {:?}", selector)
+    }
+}
+
+fn role_name(role: &AudioTrackRole) -> &'static str {
+    match role {
+        AudioTrackRole::Original => "original",
+        AudioTrackRole::Dubbed => "dubbed",
+        AudioTrackRole::Descriptive => "descriptive",
+    }
+}
+
+fn live_status_name(status: &LiveStatus) -> &'static str {
+    match status {
+        LiveStatus::NotLive => "not_live",
+        LiveStatus::IsLive => "is_live",
+        LiveStatus::WasLive => "was_live",
+        LiveStatus::IsUpcoming => "is_upcoming",
+        LiveStatus::PostLive => "post_live",
+    }
+}
+
+fn protocol_name(protocol: &FormatProtocol) -> &'static str {
+    match protocol {
+        FormatProtocol::HTTP => "http",
+        FormatProtocol::HLS => "hls",
+        FormatProtocol::DASH => "dash",
+    }
+}
+
+fn availability_name(availability: &Availability) -> &'static str {
+    match availability {
+        Availability::Playable => "playable",
+        Availability::Unplayable => "unplayable",
+        Availability::Private => "private",
+        Availability::Deleted => "deleted",
+    }
+}
+
+fn entry_field_value(entry: &EntryDetails, field: &str) -> Option<Value> {
+    let text = |value: &str| Some(Value::Text(value.to_string()));
+    let metadata = match entry {
+        EntryDetails::Recording(metadata) => metadata,
+        EntryDetails::List { id, title, .. } => {
+            return match field {
+                "kind" => text("list"),
+                "id" => text(id),
+                "title" => text(title),
+                _ => None,
+            }
+        }
+    };
+    match field {
+        "duration" => metadata.duration.map(|d| Value::Number(d.as_secs())),
+        "views" => metadata.view_count.map(Value::Number),
+        "date" => metadata.published_time.or(metadata.created_time).map(|t| {
+            Value::Number(t.year() as u64 * 10000 + t.month() as u64 * 100 + t.day() as u64)
+        }),
+        "index" => metadata.list_index.map(|i| Value::Number(i.into())),
+        "age_limit" => metadata.age_limit.map(|a| Value::Number(a.into())),
+        "kind" => text("recording"),
+        "id" => text(&metadata.id),
+        "title" => text(&metadata.title),
+        "uploader" => metadata.uploader.as_deref().and_then(text),
+        "uploader_id" => metadata.uploader_id.as_deref().and_then(text),
+        "live" => metadata
+            .live_status
+            .as_ref()
+            .and_then(|s| text(live_status_name(s))),
+        "availability" => metadata
+            .availability
+            .as_ref()
+            .and_then(|a| text(availability_name(a))),
+        // checked when parsing
+        _ => None,
+    }
+}
+
+fn field_value(format: &MediaFormatDetails, field: &str) -> Option<Value> {
+    let video = format.video_details.as_ref();
+    let audio = format.audio_details.as_ref();
+    let track = audio.and_then(|a| a.track.as_ref());
+    match field {
+        "height" => video
+            .and_then(|v| v.height)
+            .map(|h| Value::Number(h.into())),
+        "width" => video.and_then(|v| v.width).map(|w| Value::Number(w.into())),
+        "channels" => audio
+            .and_then(|a| a.channels)
+            .map(|c| Value::Number(c.into())),
+        "id" => Some(Value::Text(format.id.clone())),
+        "source" => format.source.clone().map(Value::Text),
+        "lang" => audio.and_then(|a| a.language.clone()).map(Value::Text),
+        "track" => track.map(|t| Value::Text(t.id.clone())),
+        "role" => track
+            .and_then(|t| t.role.as_ref())
+            .map(|r| Value::Text(role_name(r).to_string())),
+        "proto" => Some(Value::Text(protocol_name(&format.protocol).to_string())),
+        // checked when parsing
+        _ => None,
+    }
+}
+
+fn value_matches(value: Option<Value>, filter: &Filter) -> bool {
+    match (value, &filter.value) {
+        (None, _) => filter.allow_unknown,
+        (Some(Value::Number(value)), Value::Number(wanted)) => match filter.op {
+            Op::Eq => value == *wanted,
+            Op::NotEq => value != *wanted,
+            Op::Lt => value < *wanted,
+            Op::LtEq => value <= *wanted,
+            Op::Gt => value > *wanted,
+            Op::GtEq => value >= *wanted,
+            Op::Prefix => false,
+        },
+        (Some(Value::Text(value)), Value::Text(wanted)) => match filter.op {
+            Op::Eq => &value == wanted,
+            Op::NotEq => &value != wanted,
+            Op::Prefix => value.starts_with(wanted.as_str()),
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn has_wanted_kind(format: &MediaFormatDetails, wants: Wants) -> bool {
+    match wants {
+        Wants::Combined => format.breed == FormatBreed::AudioVideo,
+        Wants::VideoOnly => format.breed == FormatBreed::Video,
+        Wants::AudioOnly => format.breed == FormatBreed::Audio,
+        Wants::Video => format.breed != FormatBreed::Audio,
+        Wants::Audio => format.breed != FormatBreed::Video,
+    }
+}
+
+fn order_name(order: Order) -> &'static str {
+    match order {
+        Order::Best => "best",
+        Order::Worst => "worst",
+    }
+}
+
+fn wants_name(wants: Wants) -> &'static str {
+    match wants {
+        Wants::Combined => "audio+video",
+        Wants::VideoOnly => "video-only",
+        Wants::AudioOnly => "audio-only",
+        Wants::Video => "video",
+        Wants::Audio => "audio",
+    }
+}
+
+/// Why the format was picked, like `best video-only format out of 2 matching [height<=1080]`
+fn item_reason(item: &Item, candidates: usize) -> String {
+    let filters = item
+        .filters
+        .iter()
+        .map(|filter| filter.to_string())
+        .collect::<String>();
+    match &item.kind {
+        ItemKind::Id(id) if filters.is_empty() => format!("format {:?}", id),
+        ItemKind::Id(id) => format!("format {:?} matching {}", id, filters),
+        ItemKind::Ranked { order, wants } => format!(
+            "{} {} format out of {}{}",
+            order_name(*order),
+            wants_name(*wants),
+            candidates,
+            if filters.is_empty() {
+                filters
+            } else {
+                format!(" matching {}", filters)
+            }
+        ),
+    }
+}
+
+/// The format for the item, and how many formats there were to choose from
+fn select_item<'a>(
+    item: &Item,
+    formats: &[&'a MediaFormatDetails],
+    sorter: &FormatSorter,
+) -> Option<(&'a MediaFormatDetails, usize)> {
+    let candidates = formats
+        .iter()
+        .copied()
+        .filter(|format| {
+            let kind_matches = match &item.kind {
+                ItemKind::Id(id) => &format.id == id,
+                ItemKind::Ranked { wants, .. } => has_wanted_kind(format, *wants),
+            };
+            kind_matches
+                && item
+                    .filters
+                    .iter()
+                    .all(|filter| value_matches(field_value(format, &filter.field), filter))
+        })
+        .collect::<Vec<_>>();
+    let order = match item.kind {
+        ItemKind::Id(_) => return Some((candidates.first()?, candidates.len())),
+        ItemKind::Ranked { order, .. } => order,
+    };
+    let sorted = sorter.sort(&candidates);
+    let format = match order {
+        Order::Best => sorted.first().copied(),
+        // with equal ranks, the one listed first by the extractor wins
+        Order::Worst => {
+            let worst = sorted.last()?;
+            sorted
+                .iter()
+                .copied()
+                .find(|f| sorter.compare(f, worst) == Ordering::Equal)
+        }
+    }?;
+    Some((format, candidates.len()))
+}
+
+/// The formats for the items, with why they were picked
+fn select_alternative(
+    alternative: &Alternative,
+    formats: &[&MediaFormatDetails],
+    sorter: &FormatSorter,
+) -> Option<Vec<(FormatSelection<String>, String)>> {
+    let is_merged = alternative.items.len() > 1;
+    alternative
+        .items
+        .iter()
+        .map(|item| {
+            let (format, candidates) = select_item(item, formats, sorter)?;
+            let id = format.id.clone();
+            let reason = item_reason(item, candidates);
+            let wants = match item.kind {
+                ItemKind::Ranked { wants, .. } => Some(wants),
+                ItemKind::Id(_) => None,
+            };
+            // when merging, only the wanted part of a format with both is used
+            Some(match (is_merged, &format.breed, wants) {
+                (true, FormatBreed::AudioVideo, Some(Wants::Video)) => (
+                    FormatSelection::ExtractVideo(id),
+                    reason + ", only its video",
+                ),
+                (true, FormatBreed::AudioVideo, Some(Wants::Audio)) => (
+                    FormatSelection::ExtractAudio(id),
+                    reason + ", only its audio",
+                ),
+                _ => (FormatSelection::Full(id), reason),
+            })
+        })
+        .collect()
+}
+
+/// The formats for the first alternative that can be satisfied
+pub fn select(
+    selector: &Selector,
+    formats: &[&MediaFormatDetails],
+    sorter: &FormatSorter,
+) -> Option<Vec<FormatSelection<String>>> {
+    explain(selector, formats, sorter)
+        .map(|picked| picked.into_iter().map(|(selection, _)| selection).collect())
+}
+
+/// Like [`select`], with why each of the formats was picked
+pub fn explain(
+    selector: &Selector,
+    formats: &[&MediaFormatDetails],
+    sorter: &FormatSorter,
+) -> Option<Vec<(FormatSelection<String>, String)>> {
+    let count = selector.alternatives.len();
+    selector
+        .alternatives
+        .iter()
+        .enumerate()
+        .find_map(|(index, alternative)| {
+            let picked = select_alternative(alternative, formats, sorter)?;
+            Some(
+                picked
+                    .into_iter()
+                    .map(|(selection, reason)| match count {
+                        1 => (selection, reason),
+                        _ => (
+                            selection,
+                            format!(
+                                "{}, from the alternative {} of {}",
+                                reason,
+                                index + 1,
+                                count
+                            ),
+                        ),
+                    })
+                    .collect(),
+            )
+        })
+}
+
+/// The indexes of the entries matching all of the filters
+pub fn select_entries(filters: &[Filter], entries: &[EntryDetails]) -> Vec<usize> {
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| {
+            filters
+                .iter()
+                .all(|filter| value_matches(entry_field_value(entry, &filter.field), filter))
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reytan_format_picker_api::chrono::DateTime;
+    use reytan_format_picker_api::{
+        EntryDetails, FormatBreed, FormatProtocol, FormatSelection, FormatSorter,
+        MediaFormatDetails, MediaMetadata,
+    };
+
+    use reytan_extractor_api::{
+        Extraction, HTTPDownloadOptions, MediaFormatEstablished, MediaFormatURL, SubtitleDetails,
+        SubtitleExt, SubtitlePointerURL, Url,
+    };
+    use reytan_format_picker_api::{DownloadList, FormatPicker, SubtitleSelection};
+
+    use super::{
+        explain, parse, parse_entry_filters, select, select_entries, NativeFormatPicker,
+        DEFAULT_SELECTOR,
+    };
+
+    fn video(id: &str, height: u32) -> MediaFormatDetails {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "breed": "Video",
+            "video_details": {"width": height * 16 / 9, "height": height},
+            "audio_details": null,
+            "source": null,
+        }))
+        .unwrap()
+    }
+
+    fn audio(id: &str, language: &str, role: &str, channels: u8) -> MediaFormatDetails {
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "breed": "Audio",
+            "video_details": null,
+            "audio_details": {
+                "channels": channels,
+                "language": language,
+                "track": {"id": language, "name": null, "role": role, "is_default": false},
+            },
+            "source": null,
+        }))
+        .unwrap()
+    }
+
+    fn formats() -> Vec<MediaFormatDetails> {
+        let mut combined = video("18", 360);
+        combined.breed = FormatBreed::AudioVideo;
+        vec![
+            combined,
+            video("137", 1080),
+            video("313", 2160),
+            video("135", 480),
+            audio("140", "en", "Original", 2),
+            audio("140-dub", "de", "Dubbed", 2),
+            audio("140-51", "en-US", "Original", 6),
+        ]
+    }
+
+    fn picked_sorted(selector: &str, sort: &str) -> Option<Vec<FormatSelection<String>>> {
+        let formats = formats();
+        let details = formats.iter().collect::<Vec<_>>();
+        select(
+            &parse(selector).unwrap(),
+            &details,
+            &FormatSorter::parse(sort).unwrap(),
+        )
+    }
+
+    fn picked(selector: &str) -> Option<Vec<FormatSelection<String>>> {
+        picked_sorted(selector, reytan_format_picker_api::DEFAULT_SORT)
+    }
+
+    fn full(ids: &[&str]) -> Option<Vec<FormatSelection<String>>> {
+        Some(
+            ids.iter()
+                .map(|id| FormatSelection::Full(id.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_select() {
+        assert_eq!(picked(DEFAULT_SELECTOR), full(&["313", "140-51"]));
+        assert_eq!(
+            picked("bv[height<=1080]+ba[channels=2]"),
+            full(&["137", "140"])
+        );
+        assert_eq!(picked("wv+ba[lang^=de]"), full(&["135", "140-dub"]));
+        assert_eq!(picked("ba[role!=original]"), full(&["140-dub"]));
+        assert_eq!(picked("b"), full(&["18"]));
+        assert_eq!(picked("bv[height>2160]+ba/b"), full(&["18"]));
+        assert_eq!(picked("bv[height>2160]"), None);
+        assert_eq!(picked("137+140"), full(&["137", "140"]));
+        // combined formats are split when merged
+        assert_eq!(
+            picked("bv*[height<1000]+ba*[channels<=?6]"),
+            Some(vec![
+                FormatSelection::Full("135".to_string()),
+                FormatSelection::ExtractAudio("18".to_string()),
+            ])
+        );
+        assert_eq!(
+            picked("wv*+wa"),
+            Some(vec![
+                FormatSelection::ExtractVideo("18".to_string()),
+                FormatSelection::Full("140-dub".to_string()),
+            ])
+        );
+        assert_eq!(
+            picked("bv+ba*[channels<=?1]"),
+            Some(vec![
+                FormatSelection::Full("313".to_string()),
+                FormatSelection::ExtractAudio("18".to_string()),
+            ])
+        );
+        // unknown values
+        assert_eq!(picked("ba[height<=1080]"), None);
+        assert_eq!(picked("ba[height<=?1080]"), full(&["140-51"]));
+        assert_eq!(picked("ba[lang=pl]/w"), full(&["18"]));
+        // the order
+        assert_eq!(
+            picked_sorted(DEFAULT_SELECTOR, "res<=1080,lang:de"),
+            full(&["137", "140-dub"])
+        );
+        assert_eq!(
+            picked_sorted("bv+wa", "-res,-channels"),
+            full(&["135", "140-51"])
+        );
+    }
+
+    #[test]
+    fn test_select_downloadable() {
+        // the post-live videos list the same formats in the DASH manifest, with the peak bitrate
+        let dash = |format: MediaFormatDetails| MediaFormatDetails {
+            id: format!("{}-dash", format.id),
+            bitrate: Some(20_000_000),
+            protocol: FormatProtocol::DASH,
+            ..format
+        };
+        let manifest = vec![
+            dash(video("313", 2160)),
+            dash(audio("140-51", "en-US", "Original", 6)),
+        ];
+        let mut formats = formats();
+        formats.extend(manifest.iter().cloned());
+        let details = formats.iter().collect::<Vec<_>>();
+        let picked = |selector: &str, sorter: &FormatSorter| {
+            select(&parse(selector).unwrap(), &details, sorter)
+        };
+        let by_bitrate = FormatSorter::parse("res,br,channels").unwrap();
+        assert_eq!(
+            picked(DEFAULT_SELECTOR, &FormatSorter::default()),
+            full(&["313", "140-51"])
+        );
+        assert_eq!(
+            picked(DEFAULT_SELECTOR, &by_bitrate),
+            full(&["313", "140-51"])
+        );
+        assert_eq!(
+            picked("bv+ba", &by_bitrate),
+            full(&["313-dash", "140-51-dash"])
+        );
+        assert_eq!(
+            picked("ba[proto=dash]", &by_bitrate),
+            full(&["140-51-dash"])
+        );
+
+        // the live streams may only have the manifest formats
+        let details = manifest.iter().collect::<Vec<_>>();
+        let selector = parse(DEFAULT_SELECTOR).unwrap();
+        assert_eq!(select(&selector, &details, &FormatSorter::default()), None);
+        assert_eq!(
+            super::no_match_error(DEFAULT_SELECTOR, &details).to_string(),
+            format!(
+                "no formats matching {:?}, there are only HLS and DASH formats (not downloadable yet)",
+                DEFAULT_SELECTOR
+            )
+        );
+        assert_eq!(
+            select(&parse("bv+ba").unwrap(), &details, &FormatSorter::default()),
+            full(&["313-dash", "140-51-dash"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pick_subtitles() {
+        let subtitle = |lang: &str| SubtitlePointerURL {
+            details: SubtitleDetails {
+                lang: lang.to_string(),
+                is_original_lang: None,
+                is_machine_generated: Some(false),
+                is_machine_translated: None,
+                ext: SubtitleExt::VTT,
+            },
+            url: Url::parse(&format!("https://example.com/subtitles/{}", lang)).unwrap(),
+        };
+        let extraction = Extraction {
+            established_formats: formats()
+                .into_iter()
+                .map(|details| MediaFormatEstablished {
+                    url: MediaFormatURL::HTTP(
+                        Url::parse(&format!("https://example.com/{}", details.id)).unwrap(),
+                        HTTPDownloadOptions::default(),
+                    ),
+                    details,
+                })
+                .collect(),
+            established_subtitles: vec![subtitle("en"), subtitle("de"), subtitle("pl")],
+            ..Default::default()
+        };
+        let extraction = &extraction;
+        let picked_subtitles = |picker: NativeFormatPicker| async move {
+            let selection = picker
+                .pick_formats(DEFAULT_SELECTOR, extraction)
+                .await
+                .unwrap();
+            DownloadList::from(&selection, extraction)
+                .unwrap()
+                .subtitles
+                .iter()
+                .map(|s| s.details.lang.clone())
+                .collect::<Vec<_>>()
+        };
+
+        assert!(picked_subtitles(NativeFormatPicker::new()).await.is_empty());
+        let picker = NativeFormatPicker::new().with_subtitles(vec![
+            SubtitleSelection::Lang("pl".to_string()),
+            SubtitleSelection::Lang("en".to_string()),
+        ]);
+        assert_eq!(picked_subtitles(picker).await, vec!["pl", "en"]);
+    }
+
+    #[test]
+    fn test_explain() {
+        let formats = formats();
+        let details = formats.iter().collect::<Vec<_>>();
+        let reasons = |selector: &str| {
+            explain(
+                &parse(selector).unwrap(),
+                &details,
+                &FormatSorter::default(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(_, reason)| reason)
+            .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            reasons("bv[height<=1080]+ba/b"),
+            vec![
+                "best video-only format out of 2 matching [height<=1080], from the alternative 1 of 2",
+                "best audio-only format out of 3, from the alternative 1 of 2",
+            ]
+        );
+        assert_eq!(
+            reasons("bv[height>2160]/wv*+137"),
+            vec![
+                "worst video format out of 4, only its video, from the alternative 2 of 2",
+                "format \"137\", from the alternative 2 of 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_entries() {
+        let recording = |id: &str, duration: u64, published: &str| MediaMetadata {
+            id: id.to_string(),
+            title: format!("video {}", id),
+            duration: Some(Duration::from_secs(duration)),
+            published_time: Some(DateTime::parse_from_rfc3339(published).unwrap().into()),
+            ..Default::default()
+        };
+        let metadata = [
+            recording("a", 600, "2021-12-31T23:00:00Z"),
+            recording("b", 7200, "2022-03-01T00:00:00Z"),
+            recording("c", 1800, "2022-01-01T00:00:00Z"),
+        ];
+        let mut entries = metadata
+            .iter()
+            .map(EntryDetails::Recording)
+            .collect::<Vec<_>>();
+        entries.push(EntryDetails::List {
+            id: "PL1",
+            title: "playlist",
+            item_count: None,
+        });
+        let picked = |filter: &str| select_entries(&parse_entry_filters(filter).unwrap(), &entries);
+
+        assert_eq!(picked(""), vec![0, 1, 2, 3]);
+        assert_eq!(picked("[duration<=3600][date>=20220101]"), vec![2]);
+        assert_eq!(picked("[duration<=?3600]"), vec![0, 2, 3]);
+        assert_eq!(picked("[kind=list]"), vec![3]);
+        assert_eq!(picked("[title^=video][id!=b]"), vec![0, 2]);
+    }
+}
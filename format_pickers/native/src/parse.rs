@@ -0,0 +1,456 @@
+use std::fmt;
+
+use reytan_format_picker_api::anyhow::{anyhow, bail, Result};
+
+/// `alternative / alternative / ...`, the first one that can be satisfied is used
+#[derive(PartialEq, Clone, Debug)]
+pub struct Selector {
+    pub alternatives: Vec<Alternative>,
+}
+
+/// `item + item + ...`, the formats to be merged
+#[derive(PartialEq, Clone, Debug)]
+pub struct Alternative {
+    pub items: Vec<Item>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub filters: Vec<Filter>,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum ItemKind {
+    /// `best`, `bv`, `wa*`, ...
+    Ranked { order: Order, wants: Wants },
+    /// a format id, as given by the extractor
+    Id(String),
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Order {
+    Best,
+    Worst,
+}
+
+/// What the format has to contain
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Wants {
+    /// both audio and video (`b`)
+    Combined,
+    /// only video (`bv`)
+    VideoOnly,
+    /// only audio (`ba`)
+    AudioOnly,
+    /// video, with or without audio (`bv*`)
+    Video,
+    /// audio, with or without video (`ba*`)
+    Audio,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Filter {
+    pub field: String,
+    pub op: Op,
+    /// `[height<=?1080]` - also matches if the value is not known
+    pub allow_unknown: bool,
+    pub value: Value,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Value {
+    Number(u64),
+    Text(String),
+}
+
+/// The fields that can be filtered on
+pub struct Fields {
+    /// What is parsed, for the errors
+    pub context: &'static str,
+    /// Compared as numbers
+    pub numbers: &'static [&'static str],
+    /// Compared as text, only with `=`, `!=` and `^=`
+    pub texts: &'static [&'static str],
+}
+
+/// In the format selectors
+pub const FORMAT_FIELDS: Fields = Fields {
+    context: "format selector",
+    numbers: &["height", "width", "channels"],
+    texts: &["id", "source", "lang", "track", "role", "proto"],
+};
+
+/// In the list entry filters
+pub const ENTRY_FIELDS: Fields = Fields {
+    context: "entry filter",
+    numbers: &["duration", "views", "date", "index", "age_limit"],
+    texts: &[
+        "kind",
+        "id",
+        "title",
+        "uploader",
+        "uploader_id",
+        "live",
+        "availability",
+    ],
+};
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Op {
+    Eq,
+    NotEq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+    /// starts with
+    Prefix,
+}
+
+/// The operators, longer ones first (not to match `<` in `<=`)
+const OPS: &[(&str, Op)] = &[
+    ("<=", Op::LtEq),
+    (">=", Op::GtEq),
+    ("!=", Op::NotEq),
+    ("^=", Op::Prefix),
+    ("<", Op::Lt),
+    (">", Op::Gt),
+    ("=", Op::Eq),
+];
+
+/// As it would be written in the selector
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let op = OPS
+            .iter()
+            .find(|(_, op)| *op == self.op)
+            .map_or("", |(token, _)| token);
+        let unknown = if self.allow_unknown { "?" } else { "" };
+        match &self.value {
+            Value::Number(value) => write!(f, "[{}{}{}{}]", self.field, op, unknown, value),
+            Value::Text(value) => write!(f, "[{}{}{}{}]", self.field, op, unknown, value),
+        }
+    }
+}
+
+fn ranked_kind(word: &str) -> Option<ItemKind> {
+    let (order, wants) = match word {
+        "b" | "best" => (Order::Best, Wants::Combined),
+        "w" | "worst" => (Order::Worst, Wants::Combined),
+        "bv" | "bestvideo" => (Order::Best, Wants::VideoOnly),
+        "wv" | "worstvideo" => (Order::Worst, Wants::VideoOnly),
+        "ba" | "bestaudio" => (Order::Best, Wants::AudioOnly),
+        "wa" | "worstaudio" => (Order::Worst, Wants::AudioOnly),
+        "bv*" | "bestvideo*" => (Order::Best, Wants::Video),
+        "wv*" | "worstvideo*" => (Order::Worst, Wants::Video),
+        "ba*" | "bestaudio*" => (Order::Best, Wants::Audio),
+        "wa*" | "worstaudio*" => (Order::Worst, Wants::Audio),
+        _ => return None,
+    };
+    Some(ItemKind::Ranked { order, wants })
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    position: usize,
+    fields: &'static Fields,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.input[self.position..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// Consumes `token` if the input continues with it
+    fn eat(&mut self, token: &str) -> bool {
+        self.skip_whitespace();
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let rest = self.rest();
+        let end = rest.find(|c| !f(c)).unwrap_or(rest.len());
+        self.position += end;
+        &rest[..end]
+    }
+
+    fn error(&self, message: &str) -> reytan_format_picker_api::anyhow::Error {
+        anyhow!(
+            "{} at position {} in {} {:?}",
+            message,
+            self.position,
+            self.fields.context,
+            self.input
+        )
+    }
+
+    fn parse_selector(&mut self) -> Result<Selector> {
+        let mut alternatives = vec![self.parse_alternative()?];
+        while self.eat("/") {
+            alternatives.push(self.parse_alternative()?);
+        }
+        self.skip_whitespace();
+        if !self.rest().is_empty() {
+            return Err(self.error("unexpected input"));
+        }
+        Ok(Selector { alternatives })
+    }
+
+    fn parse_alternative(&mut self) -> Result<Alternative> {
+        let mut items = vec![self.parse_item()?];
+        while self.eat("+") {
+            items.push(self.parse_item()?);
+        }
+        Ok(Alternative { items })
+    }
+
+    fn parse_item(&mut self) -> Result<Item> {
+        self.skip_whitespace();
+        let word = self
+            .take_while(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.' || c == '*');
+        if word.is_empty() {
+            return Err(self.error("expected a format"));
+        }
+        let kind = match ranked_kind(word) {
+            Some(kind) => kind,
+            None if word.contains('*') => return Err(self.error("unknown format keyword")),
+            None => ItemKind::Id(word.to_string()),
+        };
+        let mut filters = vec![];
+        while self.rest().starts_with('[') {
+            self.position += 1;
+            filters.push(self.parse_filter()?);
+        }
+        Ok(Item { kind, filters })
+    }
+
+    fn parse_filters(&mut self) -> Result<Vec<Filter>> {
+        let mut filters = vec![];
+        while self.eat("[") {
+            filters.push(self.parse_filter()?);
+        }
+        self.skip_whitespace();
+        if !self.rest().is_empty() {
+            return Err(self.error("expected '['"));
+        }
+        Ok(filters)
+    }
+
+    fn parse_filter(&mut self) -> Result<Filter> {
+        self.skip_whitespace();
+        let field = self.take_while(|c| c.is_ascii_alphanumeric() || c == '_');
+        if field.is_empty() {
+            return Err(self.error("expected a field name"));
+        }
+        self.skip_whitespace();
+        let op = match OPS.iter().find(|(token, _)| self.rest().starts_with(token)) {
+            Some((token, op)) => {
+                self.position += token.len();
+                *op
+            }
+            None => return Err(self.error("expected a comparison operator")),
+        };
+        let allow_unknown = self.eat("?");
+        let value = self.take_while(|c| c != ']').trim();
+        if !self.eat("]") {
+            return Err(self.error("expected ']'"));
+        }
+        if value.is_empty() {
+            return Err(self.error("expected a value"));
+        }
+        let value = if self.fields.numbers.contains(&field) {
+            match (op, value.parse()) {
+                (Op::Prefix, _) => return Err(self.error("^= can't be used with numbers")),
+                (_, Ok(number)) => Value::Number(number),
+                (_, Err(_)) => return Err(self.error(&format!("{} expects a number", field))),
+            }
+        } else if self.fields.texts.contains(&field) {
+            match op {
+                Op::Eq | Op::NotEq | Op::Prefix => Value::Text(value.to_string()),
+                _ => return Err(self.error(&format!("{} can't be compared by order", field))),
+            }
+        } else {
+            return Err(self.error(&format!("unknown field {:?}", field)));
+        };
+        Ok(Filter {
+            field: field.to_string(),
+            op,
+            allow_unknown,
+            value,
+        })
+    }
+}
+
+pub fn parse(input: &str) -> Result<Selector> {
+    if input.trim().is_empty() {
+        bail!("empty format selector");
+    }
+    Parser {
+        input,
+        position: 0,
+        fields: &FORMAT_FIELDS,
+    }
+    .parse_selector()
+}
+
+/// The list entry filters, `[duration<=3600][date>=20220101]`, all of them have to match
+pub fn parse_entry_filters(input: &str) -> Result<Vec<Filter>> {
+    Parser {
+        input,
+        position: 0,
+        fields: &ENTRY_FIELDS,
+    }
+    .parse_filters()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        parse, parse_entry_filters, Alternative, Filter, Item, ItemKind, Op, Order, Selector,
+        Value, Wants,
+    };
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("bv[height<=?1080][lang^=en] + ba / 18 / best").unwrap(),
+            Selector {
+                alternatives: vec![
+                    Alternative {
+                        items: vec![
+                            Item {
+                                kind: ItemKind::Ranked {
+                                    order: Order::Best,
+                                    wants: Wants::VideoOnly
+                                },
+                                filters: vec![
+                                    Filter {
+                                        field: "height".to_string(),
+                                        op: Op::LtEq,
+                                        allow_unknown: true,
+                                        value: Value::Number(1080),
+                                    },
+                                    Filter {
+                                        field: "lang".to_string(),
+                                        op: Op::Prefix,
+                                        allow_unknown: false,
+                                        value: Value::Text("en".to_string()),
+                                    },
+                                ],
+                            },
+                            Item {
+                                kind: ItemKind::Ranked {
+                                    order: Order::Best,
+                                    wants: Wants::AudioOnly
+                                },
+                                filters: vec![],
+                            },
+                        ]
+                    },
+                    Alternative {
+                        items: vec![Item {
+                            kind: ItemKind::Id("18".to_string()),
+                            filters: vec![],
+                        }]
+                    },
+                    Alternative {
+                        items: vec![Item {
+                            kind: ItemKind::Ranked {
+                                order: Order::Best,
+                                wants: Wants::Combined
+                            },
+                            filters: vec![],
+                        }]
+                    },
+                ]
+            }
+        );
+        assert_eq!(
+            parse("b[height<=?1080][lang^=en]").unwrap().alternatives[0].items[0]
+                .filters
+                .iter()
+                .map(|f| f.to_string())
+                .collect::<String>(),
+            "[height<=?1080][lang^=en]"
+        );
+        assert_eq!(
+            parse("wa*").unwrap().alternatives[0].items[0].kind,
+            ItemKind::Ranked {
+                order: Order::Worst,
+                wants: Wants::Audio
+            }
+        );
+        assert_eq!(
+            parse("hls-1080p").unwrap().alternatives[0].items[0].kind,
+            ItemKind::Id("hls-1080p".to_string())
+        );
+
+        for invalid in [
+            "",
+            "bv+",
+            "/b",
+            "b[height]",
+            "b[height<=]",
+            "b[height<=1080",
+            "b[<=1080]",
+            "b b",
+            "x*",
+            "b[height~1080]",
+            "b[codec=vp9]",
+            "b[height=hd]",
+            "b[height^=1]",
+            "b[lang>en]",
+        ] {
+            assert!(parse(invalid).is_err(), "{:?}", invalid);
+        }
+        assert_eq!(
+            parse("bv+(ba)").unwrap_err().to_string(),
+            "expected a format at position 3 in format selector \"bv+(ba)\""
+        );
+    }
+
+    #[test]
+    fn test_parse_entry_filters() {
+        assert_eq!(parse_entry_filters(" ").unwrap(), vec![]);
+        assert_eq!(
+            parse_entry_filters("[duration<=3600] [live!=is_live]").unwrap(),
+            vec![
+                Filter {
+                    field: "duration".to_string(),
+                    op: Op::LtEq,
+                    allow_unknown: false,
+                    value: Value::Number(3600),
+                },
+                Filter {
+                    field: "live".to_string(),
+                    op: Op::NotEq,
+                    allow_unknown: false,
+                    value: Value::Text("is_live".to_string()),
+                },
+            ]
+        );
+        for invalid in [
+            "duration<=3600",
+            "[height<=1080]",
+            "[date>=2022-01-01]",
+            "[id=x]b",
+        ] {
+            assert!(parse_entry_filters(invalid).is_err(), "{:?}", invalid);
+        }
+        assert_eq!(
+            parse_entry_filters("[views>1k]").unwrap_err().to_string(),
+            "views expects a number at position 10 in entry filter \"[views>1k]\""
+        );
+    }
+}
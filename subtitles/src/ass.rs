@@ -0,0 +1,180 @@
+//! Advanced SubStation Alpha - http://www.tcax.org/docs/ass-specs.htm
+
+use std::time::Duration;
+
+use crate::{Color, Position, Style, Subtitles};
+
+/// The script resolution, the positions are scaled to it
+const PLAY_RES: (f32, f32) = (1280.0, 720.0);
+
+/// `H:MM:SS.cc`, centiseconds
+fn format_time(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    format!(
+        "{}:{:02}:{:02}.{:02}",
+        centis / 360_000,
+        centis / 6000 % 60,
+        centis / 100 % 60,
+        centis % 100
+    )
+}
+
+/// `&HBBGGRR&`, the alpha is set separately
+fn format_color(color: Color) -> String {
+    format!("&H{:02X}{:02X}{:02X}&", color.b, color.g, color.r)
+}
+
+/// The alpha is inverted in ASS, 0 is opaque
+fn format_alpha(color: Color) -> String {
+    format!("&H{:02X}&", 255 - color.a)
+}
+
+/// Override tags for the span style. The background is left out,
+/// ASS has the box only for the whole line (`BorderStyle`)
+fn style_tags(style: &Style) -> String {
+    let mut tags = String::from("\\r");
+    for (enabled, tag) in [
+        (style.bold, "\\b1"),
+        (style.italic, "\\i1"),
+        (style.underline, "\\u1"),
+    ] {
+        if enabled {
+            tags.push_str(tag);
+        }
+    }
+    if let Some(color) = style.color {
+        tags.push_str(&format!(
+            "\\c{}\\1a{}",
+            format_color(color),
+            format_alpha(color)
+        ));
+    }
+    tags
+}
+
+/// `\an` alignment (numpad layout) and `\pos`
+fn position_tags(position: &Position) -> String {
+    let (horizontal, vertical) = position.anchor.grid();
+    let alignment = (2 - vertical) * 3 + horizontal + 1;
+    format!(
+        "\\an{}\\pos({},{})",
+        alignment,
+        (position.x * PLAY_RES.0 / 100.0).round(),
+        (position.y * PLAY_RES.1 / 100.0).round()
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('{', "\\{")
+        .replace('}', "\\}")
+        .replace('\n', "\\N")
+}
+
+pub fn write(subtitles: &Subtitles) -> String {
+    let mut output = format!(
+        "[Script Info]\n\
+        ScriptType: v4.00+\n\
+        PlayResX: {}\n\
+        PlayResY: {}\n\
+        WrapStyle: 0\n\
+        ScaledBorderAndShadow: yes\n\
+        \n\
+        [V4+ Styles]\n\
+        Format: Name, Fontname, Fontsize, PrimaryColour, SecondaryColour, OutlineColour, BackColour, Bold, Italic, Underline, StrikeOut, ScaleX, ScaleY, Spacing, Angle, BorderStyle, Outline, Shadow, Alignment, MarginL, MarginR, MarginV, Encoding\n\
+        Style: Default,Arial,48,&H00FFFFFF,&H000000FF,&H00000000,&H80000000,0,0,0,0,100,100,0,0,1,2,1,2,40,40,40,1\n\
+        \n\
+        [Events]\n\
+        Format: Layer, Start, End, Style, Name, MarginL, MarginR, MarginV, Effect, Text\n",
+        PLAY_RES.0, PLAY_RES.1
+    );
+    for cue in &subtitles.cues {
+        if cue.text().trim().is_empty() {
+            continue;
+        }
+        let mut text = String::new();
+        if let Some(position) = &cue.position {
+            text.push_str(&format!("{{{}}}", position_tags(position)));
+        }
+        let mut current = Style::default();
+        for span in &cue.spans {
+            if span.style != current {
+                text.push_str(&format!("{{{}}}", style_tags(&span.style)));
+                current = span.style.clone();
+            }
+            text.push_str(&escape(&span.text));
+        }
+        output.push_str(&format!(
+            "Dialogue: 0,{},{},Default,,0,0,0,,{}\n",
+            format_time(cue.start),
+            format_time(cue.end),
+            text
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Anchor, Color, Cue, Position, Span, Style, Subtitles};
+
+    use super::write;
+
+    #[test]
+    fn test_write() {
+        let subtitles = Subtitles {
+            cues: vec![
+                Cue {
+                    start: Duration::from_millis(1005),
+                    end: Duration::from_millis(3_723_459),
+                    spans: vec![
+                        Span {
+                            text: "plain {braces}\n".to_string(),
+                            style: Style::default(),
+                        },
+                        Span {
+                            text: "bold red".to_string(),
+                            style: Style {
+                                bold: true,
+                                color: Some(Color {
+                                    a: 128,
+                                    ..Color::rgb(255, 0, 16)
+                                }),
+                                ..Default::default()
+                            },
+                        },
+                        Span {
+                            text: " plain".to_string(),
+                            style: Style::default(),
+                        },
+                    ],
+                    position: Some(Position {
+                        anchor: Anchor::TopLeft,
+                        x: 10.0,
+                        y: 5.0,
+                    }),
+                },
+                Cue {
+                    start: Duration::from_secs(4),
+                    end: Duration::from_secs(5),
+                    spans: vec![],
+                    position: None,
+                },
+            ],
+        };
+        let written = write(&subtitles);
+        assert!(written.starts_with("[Script Info]\n"));
+        assert!(written.contains("PlayResX: 1280\nPlayResY: 720\n"));
+        let dialogues = written
+            .lines()
+            .filter(|l| l.starts_with("Dialogue:"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            dialogues,
+            vec![
+                "Dialogue: 0,0:00:01.00,1:02:03.45,Default,,0,0,0,,{\\an7\\pos(128,36)}plain \\{braces\\}\\N{\\r\\b1\\c&H1000FF&\\1a&H7F&}bold red{\\r} plain"
+            ]
+        );
+    }
+}
@@ -0,0 +1,185 @@
+//! YouTube's JSON timed text (`fmt=json3`)
+
+use std::time::Duration;
+
+use reytan_extractor_api::anyhow::Result;
+use serde::Deserialize;
+
+use crate::srv3::{youtube_position, Pen};
+use crate::{trim_spans, Color, Cue, Span, Subtitles};
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TimedText {
+    #[serde(default)]
+    pens: Vec<JsonPen>,
+    #[serde(default)]
+    wp_win_positions: Vec<WinPosition>,
+    #[serde(default)]
+    events: Vec<Event>,
+}
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "camelCase")]
+struct JsonPen {
+    b_attr: Option<u8>,
+    i_attr: Option<u8>,
+    u_attr: Option<u8>,
+    /// 0xRRGGBB
+    fc_fore_color: Option<u32>,
+    fo_fore_alpha: Option<u8>,
+    bc_back_color: Option<u32>,
+    bo_back_alpha: Option<u8>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct WinPosition {
+    ap_point: Option<u8>,
+    ah_hor_pos: Option<f32>,
+    av_ver_pos: Option<f32>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Event {
+    t_start_ms: u64,
+    d_duration_ms: Option<u64>,
+    p_pen_id: Option<usize>,
+    wp_win_pos_id: Option<usize>,
+    a_append: Option<u8>,
+    /// missing in the events setting up the windows
+    segs: Option<Vec<Segment>>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct Segment {
+    utf8: String,
+    p_pen_id: Option<usize>,
+}
+
+fn color(rgb: u32) -> Color {
+    Color::rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8)
+}
+
+impl JsonPen {
+    fn pen(&self) -> Pen {
+        Pen {
+            bold: self.b_attr == Some(1),
+            italic: self.i_attr == Some(1),
+            underline: self.u_attr == Some(1),
+            fore_color: self.fc_fore_color.map(color),
+            fore_alpha: self.fo_fore_alpha,
+            back_color: self.bc_back_color.map(color),
+            back_alpha: self.bo_back_alpha,
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Subtitles> {
+    let timed_text: TimedText = serde_json::from_str(input)?;
+    let style = |id: Option<usize>| {
+        id.and_then(|id| timed_text.pens.get(id))
+            .map(|pen| pen.pen().style())
+            .unwrap_or_default()
+    };
+    let mut cues = Vec::new();
+    for event in &timed_text.events {
+        // appended events repeat the text of the rolling automatic captions
+        if event.a_append == Some(1) {
+            continue;
+        }
+        let segments = match &event.segs {
+            Some(segments) => segments,
+            None => continue,
+        };
+        let spans = trim_spans(
+            segments
+                .iter()
+                .map(|segment| Span {
+                    text: segment.utf8.clone(),
+                    style: style(segment.p_pen_id.or(event.p_pen_id)),
+                })
+                .collect(),
+        );
+        // the cues ending past the representable time are broken
+        let end = match event
+            .t_start_ms
+            .checked_add(event.d_duration_ms.unwrap_or(0))
+        {
+            Some(end) if !spans.is_empty() => end,
+            _ => continue,
+        };
+        cues.push(Cue {
+            start: Duration::from_millis(event.t_start_ms),
+            end: Duration::from_millis(end),
+            spans,
+            position: event
+                .wp_win_pos_id
+                .and_then(|id| timed_text.wp_win_positions.get(id))
+                .and_then(|wp| youtube_position(wp.ap_point, wp.ah_hor_pos, wp.av_ver_pos)),
+        });
+    }
+    Ok(Subtitles { cues })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Anchor, Color, Position, Style};
+
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        let subtitles = parse(
+            r#"{
+                "wireMagic": "pb3",
+                "pens": [{}, {"bAttr": 1, "fcForeColor": 16711680, "foForeAlpha": 254}],
+                "wsWinStyles": [{}],
+                "wpWinPositions": [{}, {"apPoint": 8, "ahHorPos": 90, "avVerPos": 95}],
+                "events": [
+                    {"tStartMs": 0, "dDurationMs": 5000, "id": 1, "wpWinPosId": 1, "wsWinStyleId": 0},
+                    {"tStartMs": 1000, "dDurationMs": 1500, "wWinId": 1, "wpWinPosId": 1, "segs": [
+                        {"utf8": "hello", "acAsrConf": 0},
+                        {"utf8": " world", "tOffsetMs": 400, "pPenId": 1}
+                    ]},
+                    {"tStartMs": 2400, "dDurationMs": 100, "wWinId": 1, "aAppend": 1, "segs": [{"utf8": "\n"}]},
+                    {"tStartMs": 2500, "dDurationMs": 1000, "wpWinPosId": 0, "segs": [{"utf8": "second\n"}]},
+                    {"tStartMs": 3500, "segs": [{"utf8": "\n"}]},
+                    {"tStartMs": 18446744073709551615, "dDurationMs": 1, "segs": [{"utf8": "overflowing"}]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(subtitles.cues.len(), 2);
+        let first = &subtitles.cues[0];
+        assert_eq!(first.start, Duration::from_millis(1000));
+        assert_eq!(first.end, Duration::from_millis(2500));
+        assert_eq!(first.text(), "hello world");
+        assert_eq!(first.spans[0].style, Style::default());
+        assert_eq!(
+            first.spans[1].style,
+            Style {
+                bold: true,
+                color: Some(Color::rgb(255, 0, 0)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            first.position,
+            Some(Position {
+                anchor: Anchor::BottomRight,
+                x: 90.0,
+                y: 95.0,
+            })
+        );
+        let second = &subtitles.cues[1];
+        assert_eq!(second.text(), "second");
+        assert_eq!(second.position, None);
+
+        assert!(parse("WEBVTT\n").is_err());
+    }
+}
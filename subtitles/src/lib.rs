@@ -0,0 +1,464 @@
+//! Parsing the subtitles into a common cue model, and writing them in the other formats.
+//!
+//! Reads VTT, TTML and YouTube's SRV3 and JSON3, writes SRT, VTT, ASS and TTML.
+
+use std::time::Duration;
+
+use reytan_extractor_api::anyhow::{bail, Result};
+use reytan_extractor_api::SubtitleExt;
+
+pub mod ass;
+pub mod json3;
+pub mod srt;
+pub mod srv3;
+pub mod ttml;
+pub mod vtt;
+
+#[derive(Default, PartialEq, Clone, Debug)]
+pub struct Subtitles {
+    pub cues: Vec<Cue>,
+}
+
+#[derive(Default, PartialEq, Clone, Debug)]
+pub struct Cue {
+    pub start: Duration,
+    pub end: Duration,
+    /// the text, line breaks as `\n`
+    pub spans: Vec<Span>,
+    /// `None` leaves the placement to the player (usually bottom center)
+    pub position: Option<Position>,
+}
+
+#[derive(Default, PartialEq, Clone, Debug)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+#[derive(Default, PartialEq, Clone, Debug)]
+pub struct Style {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub color: Option<Color>,
+    pub background: Option<Color>,
+}
+
+/// RGBA, alpha 255 is opaque
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+/// Where the cue is placed on the video
+#[derive(PartialEq, Clone, Debug)]
+pub struct Position {
+    /// the point of the cue box placed at (x, y)
+    pub anchor: Anchor,
+    /// percent of the video width, from the left
+    pub x: f32,
+    /// percent of the video height, from the top
+    pub y: f32,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Anchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    CenterLeft,
+    Center,
+    CenterRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+impl Anchor {
+    /// YouTube's `ap`/`apPoint`: 0-8, left to right, top to bottom
+    pub fn from_youtube(point: u8) -> Option<Anchor> {
+        Some(match point {
+            0 => Anchor::TopLeft,
+            1 => Anchor::TopCenter,
+            2 => Anchor::TopRight,
+            3 => Anchor::CenterLeft,
+            4 => Anchor::Center,
+            5 => Anchor::CenterRight,
+            6 => Anchor::BottomLeft,
+            7 => Anchor::BottomCenter,
+            8 => Anchor::BottomRight,
+            _ => return None,
+        })
+    }
+
+    /// (horizontal, vertical): 0 - top/left, 1 - center, 2 - bottom/right
+    pub(crate) fn from_grid(horizontal: u8, vertical: u8) -> Anchor {
+        match (horizontal.min(2), vertical.min(2)) {
+            (0, 0) => Anchor::TopLeft,
+            (1, 0) => Anchor::TopCenter,
+            (_, 0) => Anchor::TopRight,
+            (0, 1) => Anchor::CenterLeft,
+            (1, 1) => Anchor::Center,
+            (_, 1) => Anchor::CenterRight,
+            (0, _) => Anchor::BottomLeft,
+            (1, _) => Anchor::BottomCenter,
+            (_, _) => Anchor::BottomRight,
+        }
+    }
+
+    /// (horizontal, vertical): 0 - top/left, 1 - center, 2 - bottom/right
+    pub(crate) fn grid(self) -> (u8, u8) {
+        match self {
+            Anchor::TopLeft => (0, 0),
+            Anchor::TopCenter => (1, 0),
+            Anchor::TopRight => (2, 0),
+            Anchor::CenterLeft => (0, 1),
+            Anchor::Center => (1, 1),
+            Anchor::CenterRight => (2, 1),
+            Anchor::BottomLeft => (0, 2),
+            Anchor::BottomCenter => (1, 2),
+            Anchor::BottomRight => (2, 2),
+        }
+    }
+}
+
+impl Cue {
+    /// The text without styling
+    pub fn text(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(255, 255, 255);
+    pub const BLACK: Color = Color::rgb(0, 0, 0);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Color {
+        Color { r, g, b, a: 255 }
+    }
+
+    /// Parses `#RGB`, `#RRGGBB` and `#RRGGBBAA`
+    pub fn from_hex(hex: &str) -> Option<Color> {
+        let hex = hex.trim().strip_prefix('#')?;
+        if !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+        match hex.len() {
+            3 => {
+                let short = |i: usize| {
+                    u8::from_str_radix(hex.get(i..i + 1)?, 16)
+                        .ok()
+                        .map(|c| c * 17)
+                };
+                Some(Color::rgb(short(0)?, short(1)?, short(2)?))
+            }
+            6 => Some(Color::rgb(channel(0)?, channel(2)?, channel(4)?)),
+            8 => Some(Color {
+                a: channel(6)?,
+                ..Color::rgb(channel(0)?, channel(2)?, channel(4)?)
+            }),
+            _ => None,
+        }
+    }
+
+    /// `#RRGGBB`, without the alpha
+    pub fn to_hex(self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+}
+
+/// Whether the format can be read with [parse]
+pub fn can_parse(ext: &SubtitleExt) -> bool {
+    match ext {
+        SubtitleExt::VTT | SubtitleExt::TTML => true,
+        SubtitleExt::NonStandard(name) => name == "srv3" || name == "json3",
+        _ => false,
+    }
+}
+
+/// Whether the format can be written with [write]
+pub fn can_write(ext: &SubtitleExt) -> bool {
+    matches!(
+        ext,
+        SubtitleExt::SRT | SubtitleExt::VTT | SubtitleExt::ASS | SubtitleExt::TTML
+    )
+}
+
+pub fn parse(input: &str, ext: &SubtitleExt) -> Result<Subtitles> {
+    match ext {
+        SubtitleExt::VTT => vtt::parse(input),
+        SubtitleExt::TTML => ttml::parse(input),
+        SubtitleExt::NonStandard(name) if name == "srv3" => srv3::parse(input),
+        SubtitleExt::NonStandard(name) if name == "json3" => json3::parse(input),
+        _ => bail!("reading {:?} subtitles is not supported", ext),
+    }
+}
+
+pub fn write(subtitles: &Subtitles, ext: &SubtitleExt) -> Result<String> {
+    Ok(match ext {
+        SubtitleExt::SRT => srt::write(subtitles),
+        SubtitleExt::VTT => vtt::write(subtitles),
+        SubtitleExt::ASS => ass::write(subtitles),
+        SubtitleExt::TTML => ttml::write(subtitles),
+        _ => bail!("writing {:?} subtitles is not supported", ext),
+    })
+}
+
+/// Converts the subtitles from one format to another
+pub fn convert(input: &str, from: &SubtitleExt, to: &SubtitleExt) -> Result<String> {
+    write(&parse(input, from)?, to)
+}
+
+/// File extension for the format
+pub fn file_extension(ext: &SubtitleExt) -> &str {
+    match ext {
+        SubtitleExt::VTT => "vtt",
+        SubtitleExt::SRT => "srt",
+        SubtitleExt::TTML => "ttml",
+        SubtitleExt::ASS => "ass",
+        SubtitleExt::SSA => "ssa",
+        SubtitleExt::NonStandard(name) => name,
+    }
+}
+
+/// `HH:MM:SS{separator}mmm`
+pub(crate) fn format_timestamp(time: Duration, separator: char) -> String {
+    let millis = time.as_millis();
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        separator,
+        millis % 1000
+    )
+}
+
+/// `[[HH:]MM:]SS[.fraction]`, as in VTT and TTML clock times
+pub(crate) fn parse_clock_time(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    let (clock, fraction) = match text.split_once(['.', ',']) {
+        Some((clock, fraction)) => (clock, Some(fraction)),
+        None => (text, None),
+    };
+    let parts = clock.split(':').collect::<Vec<_>>();
+    if parts.len() > 3 || parts.iter().any(|p| p.is_empty()) {
+        return None;
+    }
+    let seconds = parts.iter().try_fold(0u64, |acc, part| {
+        if !part.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        acc.checked_mul(60)?.checked_add(part.parse::<u64>().ok()?)
+    })?;
+    let nanos = match fraction {
+        Some(fraction) if !fraction.is_empty() && fraction.bytes().all(|b| b.is_ascii_digit()) => {
+            // only the nanosecond precision
+            let digits = &fraction[..fraction.len().min(9)];
+            digits.parse::<u32>().ok()? * 10u32.pow(9 - digits.len() as u32)
+        }
+        Some(_) => return None,
+        None => 0,
+    };
+    Some(Duration::new(seconds, nanos))
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Text with the line breaks, but no empty lines (which end the cue in SRT and VTT)
+pub(crate) fn cue_lines(text: &str) -> String {
+    text.split('\n')
+        .filter(|l| !l.trim().is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Merges the adjacent spans with the same style and drops the empty ones
+pub(crate) fn normalize_spans(spans: Vec<Span>) -> Vec<Span> {
+    let mut result: Vec<Span> = Vec::with_capacity(spans.len());
+    for span in spans.into_iter().filter(|s| !s.text.is_empty()) {
+        match result.last_mut() {
+            Some(last) if last.style == span.style => last.text.push_str(&span.text),
+            _ => result.push(span),
+        }
+    }
+    result
+}
+
+/// Trims the whitespace at the start and the end of the cue
+pub(crate) fn trim_spans(spans: Vec<Span>) -> Vec<Span> {
+    let mut spans = normalize_spans(spans);
+    while let Some(first) = spans.first_mut() {
+        first.text = first.text.trim_start().to_string();
+        if !first.text.is_empty() {
+            break;
+        }
+        spans.remove(0);
+    }
+    while let Some(last) = spans.last_mut() {
+        last.text = last.text.trim_end().to_string();
+        if !last.text.is_empty() {
+            break;
+        }
+        spans.pop();
+    }
+    spans
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use reytan_extractor_api::SubtitleExt;
+
+    use super::{convert, format_timestamp, parse, parse_clock_time, Color, Subtitles};
+
+    fn fixture(ext: &SubtitleExt) -> Subtitles {
+        let input = match ext {
+            SubtitleExt::VTT => include_str!("../fixtures/sample.vtt"),
+            SubtitleExt::TTML => include_str!("../fixtures/sample.ttml"),
+            SubtitleExt::NonStandard(name) if name == "srv3" => {
+                include_str!("../fixtures/sample.srv3")
+            }
+            SubtitleExt::NonStandard(name) if name == "json3" => {
+                include_str!("../fixtures/sample.json3")
+            }
+            _ => unreachable!(),
+        };
+        parse(input, ext).unwrap()
+    }
+
+    #[test]
+    fn test_same_cues_in_every_format() {
+        let exts = [
+            SubtitleExt::VTT,
+            SubtitleExt::TTML,
+            SubtitleExt::NonStandard("srv3".to_string()),
+            SubtitleExt::NonStandard("json3".to_string()),
+        ];
+        let expected = [
+            (1000, 3500, "Never gonna give you up"),
+            (
+                3500,
+                6000,
+                "Never gonna let you down\nNever gonna run around",
+            ),
+            (6000, 9250, "and desert you"),
+        ];
+        for ext in &exts {
+            let subtitles = fixture(ext);
+            assert_eq!(
+                subtitles
+                    .cues
+                    .iter()
+                    .map(|c| (
+                        c.start.as_millis() as u64,
+                        c.end.as_millis() as u64,
+                        c.text()
+                    ))
+                    .collect::<Vec<_>>(),
+                expected
+                    .iter()
+                    .map(|(s, e, t)| (*s, *e, t.to_string()))
+                    .collect::<Vec<_>>(),
+                "{:?}",
+                ext
+            );
+            // "you" is bold in all of them
+            let bold = subtitles.cues[0]
+                .spans
+                .iter()
+                .find(|s| s.style.bold)
+                .unwrap();
+            assert_eq!(bold.text, "you", "{:?}", ext);
+        }
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let original = fixture(&SubtitleExt::NonStandard("srv3".to_string()));
+        for ext in [SubtitleExt::VTT, SubtitleExt::TTML] {
+            let written = super::write(&original, &ext).unwrap();
+            let parsed = parse(&written, &ext).unwrap();
+            assert_eq!(parsed.cues.len(), original.cues.len(), "{:?}", ext);
+            for (parsed, original) in parsed.cues.iter().zip(&original.cues) {
+                assert_eq!(parsed.start, original.start, "{:?}", ext);
+                assert_eq!(parsed.end, original.end, "{:?}", ext);
+                assert_eq!(parsed.text(), original.text(), "{:?}", ext);
+                assert_eq!(
+                    parsed
+                        .spans
+                        .iter()
+                        .map(|s| s.style.bold)
+                        .collect::<Vec<_>>(),
+                    original
+                        .spans
+                        .iter()
+                        .map(|s| s.style.bold)
+                        .collect::<Vec<_>>(),
+                    "{:?}",
+                    ext
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_convert_unsupported() {
+        let srv1 = SubtitleExt::NonStandard("srv1".to_string());
+        assert!(convert("", &srv1, &SubtitleExt::SRT).is_err());
+        assert!(convert("WEBVTT\n", &SubtitleExt::VTT, &SubtitleExt::SSA).is_err());
+        assert_eq!(
+            convert("WEBVTT\n", &SubtitleExt::VTT, &SubtitleExt::SRT).unwrap(),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_clock_time() {
+        for (text, expected) in [
+            ("00:00:01.500", Some(Duration::from_millis(1500))),
+            ("01:02:03.004", Some(Duration::from_millis(3_723_004))),
+            ("02:03.1", Some(Duration::from_millis(123_100))),
+            ("00:00:01,250", Some(Duration::from_millis(1250))),
+            ("5", Some(Duration::from_secs(5))),
+            ("00:00:01.", None),
+            ("00::01", None),
+            ("1:2:3:4", None),
+            ("-00:01", None),
+            ("", None),
+            ("9999999999999999:00:00.000", None),
+        ] {
+            assert_eq!(parse_clock_time(text), expected, "{:?}", text);
+        }
+        assert_eq!(
+            format_timestamp(Duration::from_millis(3_723_004), ','),
+            "01:02:03,004"
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        assert_eq!(Color::from_hex("#fff"), Some(Color::WHITE));
+        assert_eq!(Color::from_hex("#FF8000"), Some(Color::rgb(255, 128, 0)));
+        assert_eq!(
+            Color::from_hex("#00000080"),
+            Some(Color {
+                a: 128,
+                ..Color::BLACK
+            })
+        );
+        assert_eq!(Color::from_hex("FF8000"), None);
+        assert_eq!(Color::from_hex("#FF80"), None);
+        assert_eq!(Color::rgb(255, 128, 0).to_hex(), "#FF8000");
+    }
+}
@@ -0,0 +1,78 @@
+//! SubRip Text - https://www.matroska.org/technical/subtitles.html#srt-subtitles
+
+use crate::vtt::write_cue_text;
+use crate::{format_timestamp, Subtitles};
+
+/// Positions are left out, there's no common way to set them in SRT
+pub fn write(subtitles: &Subtitles) -> String {
+    let mut output = String::new();
+    let mut index = 0;
+    for cue in &subtitles.cues {
+        let text = write_cue_text(&cue.spans, false);
+        if text.is_empty() {
+            continue;
+        }
+        index += 1;
+        output.push_str(&format!(
+            "{}\n{} --> {}\n{}\n\n",
+            index,
+            format_timestamp(cue.start, ','),
+            format_timestamp(cue.end, ','),
+            text
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Cue, Span, Style, Subtitles};
+
+    use super::write;
+
+    #[test]
+    fn test_write() {
+        let cue = |start, end, spans| Cue {
+            start: Duration::from_millis(start),
+            end: Duration::from_millis(end),
+            spans,
+            position: None,
+        };
+        let subtitles = Subtitles {
+            cues: vec![
+                cue(
+                    500,
+                    3_723_004,
+                    vec![
+                        Span {
+                            text: "first\n".to_string(),
+                            style: Style::default(),
+                        },
+                        Span {
+                            text: "bold".to_string(),
+                            style: Style {
+                                bold: true,
+                                ..Default::default()
+                            },
+                        },
+                    ],
+                ),
+                cue(4_000_000, 4_000_500, vec![]),
+                cue(
+                    4_000_500,
+                    4_001_000,
+                    vec![Span {
+                        text: "a <tag> & b".to_string(),
+                        style: Style::default(),
+                    }],
+                ),
+            ],
+        };
+        assert_eq!(
+            write(&subtitles),
+            "1\n00:00:00,500 --> 01:02:03,004\nfirst\n<b>bold</b>\n\n2\n01:06:40,500 --> 01:06:41,000\na <tag> & b\n\n"
+        );
+    }
+}
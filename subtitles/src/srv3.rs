@@ -0,0 +1,239 @@
+//! YouTube's timed text, format 3 (`fmt=srv3`)
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reytan_extractor_api::anyhow::{bail, Result};
+
+use crate::{trim_spans, Anchor, Color, Cue, Position, Span, Style, Subtitles};
+
+/// YouTube's opacity is 0-254
+fn youtube_alpha(opacity: u8) -> u8 {
+    (u16::from(opacity.min(254)) * 255 / 254) as u8
+}
+
+/// The pen attributes, shared with json3
+#[derive(Default, Clone, Debug)]
+pub(crate) struct Pen {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub fore_color: Option<Color>,
+    pub fore_alpha: Option<u8>,
+    pub back_color: Option<Color>,
+    pub back_alpha: Option<u8>,
+}
+
+impl Pen {
+    pub fn style(&self) -> Style {
+        let color = match (self.fore_color, self.fore_alpha) {
+            (None, None) => None,
+            (color, alpha) => Some(Color {
+                a: alpha.map(youtube_alpha).unwrap_or(255),
+                ..color.unwrap_or(Color::WHITE)
+            }),
+        };
+        let background = match (self.back_color, self.back_alpha) {
+            (None, None) | (_, Some(0)) => None,
+            (color, alpha) => Some(Color {
+                a: alpha.map(youtube_alpha).unwrap_or(255),
+                ..color.unwrap_or(Color::BLACK)
+            }),
+        };
+        Style {
+            bold: self.bold,
+            italic: self.italic,
+            underline: self.underline,
+            color,
+            background,
+        }
+    }
+}
+
+/// The window position (`ap`, `ah`, `av`), shared with json3.
+/// `None` if nothing is set
+pub(crate) fn youtube_position(
+    point: Option<u8>,
+    horizontal: Option<f32>,
+    vertical: Option<f32>,
+) -> Option<Position> {
+    if point.is_none() && horizontal.is_none() && vertical.is_none() {
+        return None;
+    }
+    Some(Position {
+        anchor: point
+            .and_then(Anchor::from_youtube)
+            .unwrap_or(Anchor::BottomCenter),
+        x: horizontal.unwrap_or(50.0),
+        y: vertical.unwrap_or(100.0),
+    })
+}
+
+fn attr<T: std::str::FromStr>(node: roxmltree::Node, name: &str) -> Option<T> {
+    node.attribute(name)?.trim().parse().ok()
+}
+
+fn parse_pen(node: roxmltree::Node) -> Pen {
+    let flag = |name| attr::<u8>(node, name) == Some(1);
+    let color = |name| node.attribute(name).and_then(Color::from_hex);
+    Pen {
+        bold: flag("b"),
+        italic: flag("i"),
+        underline: flag("u"),
+        fore_color: color("fc"),
+        fore_alpha: attr(node, "fo"),
+        back_color: color("bc"),
+        back_alpha: attr(node, "bo"),
+    }
+}
+
+pub fn parse(input: &str) -> Result<Subtitles> {
+    let document = roxmltree::Document::parse(input)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "timedtext" || root.attribute("format") != Some("3") {
+        bail!("not a srv3 file");
+    }
+    let mut pens = HashMap::new();
+    let mut positions = HashMap::new();
+    let head = root.children().find(|n| n.has_tag_name("head"));
+    for node in head.iter().flat_map(|h| h.children()) {
+        let id = match node.attribute("id") {
+            Some(id) => id,
+            None => continue,
+        };
+        match node.tag_name().name() {
+            "pen" => {
+                pens.insert(id, parse_pen(node));
+            }
+            "wp" => {
+                positions.insert(
+                    id,
+                    youtube_position(attr(node, "ap"), attr(node, "ah"), attr(node, "av")),
+                );
+            }
+            _ => (),
+        }
+    }
+    let style = |id: Option<&str>| {
+        id.and_then(|id| pens.get(id))
+            .map(Pen::style)
+            .unwrap_or_default()
+    };
+
+    let mut cues = Vec::new();
+    let body = root.children().find(|n| n.has_tag_name("body"));
+    for p in body.iter().flat_map(|b| b.children()) {
+        // appended paragraphs repeat the text of the rolling automatic captions
+        if !p.has_tag_name("p") || p.attribute("a") == Some("1") {
+            continue;
+        }
+        let start = match attr::<u64>(p, "t") {
+            Some(start) => start,
+            None => continue,
+        };
+        let duration = attr::<u64>(p, "d").unwrap_or(0);
+        let paragraph_pen = p.attribute("p");
+        let spans = p
+            .children()
+            .filter_map(|node| {
+                if node.is_text() {
+                    Some(Span {
+                        text: node.text()?.to_string(),
+                        style: style(paragraph_pen),
+                    })
+                } else if node.has_tag_name("s") {
+                    Some(Span {
+                        text: node.text().unwrap_or_default().to_string(),
+                        style: style(node.attribute("p").or(paragraph_pen)),
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect();
+        let spans = trim_spans(spans);
+        // the cues ending past the representable time are broken
+        let end = match start.checked_add(duration) {
+            Some(end) if !spans.is_empty() => end,
+            _ => continue,
+        };
+        cues.push(Cue {
+            start: Duration::from_millis(start),
+            end: Duration::from_millis(end),
+            spans,
+            position: p
+                .attribute("wp")
+                .and_then(|id| positions.get(id).cloned().flatten()),
+        });
+    }
+    Ok(Subtitles { cues })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Anchor, Color, Position, Style};
+
+    use super::parse;
+
+    #[test]
+    fn test_parse() {
+        let subtitles = parse(
+            r##"<?xml version="1.0" encoding="utf-8" ?><timedtext format="3">
+<head>
+<pen id="1" b="1" fc="#FF0000" fo="127" bc="#000000" bo="0"/>
+<pen id="2" i="1" bo="254"/>
+<wp id="0"/>
+<wp id="1" ap="0" ah="10" av="5"/>
+</head>
+<body>
+<p t="0" d="500"> </p>
+<p t="1000" d="1500" wp="1" p="2">
+<s>hello</s> <s p="1">world</s>
+</p>
+<p t="1000" d="1500" a="1">hello world</p>
+<p t="2500" d="1000" wp="0">second</p>
+<p t="18446744073709551615" d="1">overflowing</p>
+</body>
+</timedtext>"##,
+        )
+        .unwrap();
+        assert_eq!(subtitles.cues.len(), 2);
+        let first = &subtitles.cues[0];
+        assert_eq!(first.start, Duration::from_millis(1000));
+        assert_eq!(first.end, Duration::from_millis(2500));
+        assert_eq!(first.text(), "hello world");
+        assert_eq!(
+            first.spans[0].style,
+            Style {
+                italic: true,
+                background: Some(Color::BLACK),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            first.spans[1].style,
+            Style {
+                bold: true,
+                color: Some(Color {
+                    a: 127,
+                    ..Color::rgb(255, 0, 0)
+                }),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            first.position,
+            Some(Position {
+                anchor: Anchor::TopLeft,
+                x: 10.0,
+                y: 5.0,
+            })
+        );
+        assert_eq!(subtitles.cues[1].position, None);
+
+        assert!(parse("<timedtext><body/></timedtext>").is_err());
+        assert!(parse("WEBVTT\n").is_err());
+    }
+}
@@ -0,0 +1,576 @@
+//! Timed Text Markup Language - https://www.w3.org/TR/ttml1/
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use reytan_extractor_api::anyhow::{bail, Result};
+use roxmltree::Node;
+
+use crate::{
+    escape_xml, format_timestamp, normalize_spans, parse_clock_time, trim_spans, Anchor, Color,
+    Cue, Position, Span, Style, Subtitles,
+};
+
+/// The namespaces differ between the TTML versions and profiles,
+/// so the attributes are matched by the local name
+fn attribute<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.attributes()
+        .find(|a| a.name() == name)
+        .map(|a| a.value())
+}
+
+/// `ttp:frameRate` and `ttp:tickRate`, for the time expressions
+struct Timing {
+    frame_rate: f64,
+    tick_rate: f64,
+}
+
+impl Timing {
+    /// Clock times (`00:00:01.500`, `00:00:01:15` with frames)
+    /// and offset times (`1.5s`, `1500ms`, `45f`, `15000000t`)
+    fn parse(&self, text: &str) -> Option<Duration> {
+        let text = text.trim();
+        if text.contains(':') {
+            let parts = text.split(':').collect::<Vec<_>>();
+            if parts.len() == 4 {
+                let frames = parts[3].parse::<f64>().ok()?;
+                let clock = parse_clock_time(&parts[..3].join(":"))?;
+                return clock
+                    .checked_add(Duration::try_from_secs_f64(frames / self.frame_rate).ok()?);
+            }
+            return parse_clock_time(text);
+        }
+        let unit_start = text.find(|c: char| c.is_ascii_alphabetic())?;
+        let (value, unit) = text.split_at(unit_start);
+        let value = value.parse::<f64>().ok()?;
+        let seconds = match unit {
+            "h" => value * 3600.0,
+            "m" => value * 60.0,
+            "s" => value,
+            "ms" => value / 1000.0,
+            "f" => value / self.frame_rate,
+            "t" => value / self.tick_rate,
+            _ => return None,
+        };
+        Duration::try_from_secs_f64(seconds).ok()
+    }
+}
+
+fn parse_color(text: &str) -> Option<Color> {
+    let text = text.trim();
+    if text.starts_with('#') {
+        return Color::from_hex(text);
+    }
+    if let Some(args) = text
+        .strip_prefix("rgba(")
+        .or_else(|| text.strip_prefix("rgb("))
+        .and_then(|t| t.strip_suffix(')'))
+    {
+        let channels = args
+            .split(',')
+            .map(|c| c.trim().parse::<u8>().ok())
+            .collect::<Option<Vec<_>>>()?;
+        return match channels[..] {
+            [r, g, b] => Some(Color::rgb(r, g, b)),
+            [r, g, b, a] => Some(Color { r, g, b, a }),
+            _ => None,
+        };
+    }
+    Some(match text {
+        "transparent" => Color {
+            a: 0,
+            ..Color::BLACK
+        },
+        "black" => Color::BLACK,
+        "white" => Color::WHITE,
+        "silver" => Color::rgb(192, 192, 192),
+        "gray" => Color::rgb(128, 128, 128),
+        "red" => Color::rgb(255, 0, 0),
+        "lime" => Color::rgb(0, 255, 0),
+        "blue" => Color::rgb(0, 0, 255),
+        "yellow" => Color::rgb(255, 255, 0),
+        "cyan" | "aqua" => Color::rgb(0, 255, 255),
+        "magenta" | "fuchsia" => Color::rgb(255, 0, 255),
+        _ => return None,
+    })
+}
+
+/// `"10% 80%"`, only the percentages are supported
+fn parse_pair(text: &str) -> Option<(f32, f32)> {
+    let mut values = text
+        .split_whitespace()
+        .map(|v| v.strip_suffix('%')?.parse::<f32>().ok());
+    Some((values.next()??, values.next()??))
+}
+
+/// Styles and regions from the head, by `xml:id`
+struct Head<'a, 'input> {
+    styles: HashMap<&'a str, Node<'a, 'input>>,
+    regions: HashMap<&'a str, Position>,
+    timing: Timing,
+}
+
+impl<'a, 'input> Head<'a, 'input> {
+    /// Applies the referenced styles and then the inline ones
+    fn apply_style(&self, node: Node<'a, 'input>, style: &mut Style, depth: u8) {
+        // styles can reference other styles
+        if depth > 8 {
+            return;
+        }
+        for id in attribute(node, "style")
+            .unwrap_or_default()
+            .split_whitespace()
+        {
+            if let Some(referenced) = self.styles.get(id) {
+                self.apply_style(*referenced, style, depth + 1);
+            }
+        }
+        for attr in node.attributes() {
+            let value = attr.value().trim();
+            match attr.name() {
+                "fontWeight" => style.bold = value == "bold",
+                "fontStyle" => style.italic = value == "italic" || value == "oblique",
+                "textDecoration" => {
+                    if value.contains("noUnderline") || value == "none" {
+                        style.underline = false;
+                    } else if value.contains("underline") {
+                        style.underline = true;
+                    }
+                }
+                "color" => style.color = parse_color(value),
+                "backgroundColor" => style.background = parse_color(value),
+                _ => (),
+            }
+        }
+    }
+
+    fn parse_region(node: Node) -> Option<Position> {
+        let mut origin = None;
+        let mut extent = None;
+        let mut horizontal = 1;
+        let mut vertical = 0;
+        for attr in node.attributes() {
+            match attr.name() {
+                "origin" => origin = parse_pair(attr.value()),
+                "extent" => extent = parse_pair(attr.value()),
+                "textAlign" => {
+                    horizontal = match attr.value() {
+                        "left" | "start" => 0,
+                        "right" | "end" => 2,
+                        _ => 1,
+                    }
+                }
+                "displayAlign" => {
+                    vertical = match attr.value() {
+                        "center" => 1,
+                        "after" => 2,
+                        _ => 0,
+                    }
+                }
+                _ => (),
+            }
+        }
+        let (x, y) = origin?;
+        let (width, height) = extent.unwrap_or((100.0 - x, 100.0 - y));
+        Some(Position {
+            anchor: Anchor::from_grid(horizontal, vertical),
+            x: x + width * f32::from(horizontal) / 2.0,
+            y: y + height * f32::from(vertical) / 2.0,
+        })
+    }
+}
+
+/// Collects the text of the paragraph, collapsing the whitespace like in `xml:space="default"`
+fn collect_spans<'a, 'input>(
+    head: &Head<'a, 'input>,
+    node: Node<'a, 'input>,
+    style: &Style,
+    spans: &mut Vec<Span>,
+) {
+    for child in node.children() {
+        if child.is_text() {
+            let text = child.text().unwrap_or_default();
+            let mut collapsed = String::with_capacity(text.len());
+            for c in text.chars() {
+                if c.is_whitespace() {
+                    let previous = spans
+                        .last()
+                        .and_then(|s| s.text.chars().last())
+                        .filter(|_| collapsed.is_empty());
+                    let after_space = match collapsed.chars().last().or(previous) {
+                        Some(last) => last == ' ' || last == '\n',
+                        None => true,
+                    };
+                    if !after_space {
+                        collapsed.push(' ');
+                    }
+                } else {
+                    collapsed.push(c);
+                }
+            }
+            spans.push(Span {
+                text: collapsed,
+                style: style.clone(),
+            });
+        } else if child.has_tag_name("br") {
+            if let Some(last) = spans.last_mut() {
+                last.text.truncate(last.text.trim_end_matches(' ').len());
+            }
+            spans.push(Span {
+                text: "\n".to_string(),
+                style: style.clone(),
+            });
+        } else if child.has_tag_name("span") {
+            let mut span_style = style.clone();
+            head.apply_style(child, &mut span_style, 0);
+            collect_spans(head, child, &span_style, spans);
+        }
+    }
+}
+
+/// Walks the body, `div`s can nest and shift the times of their children
+fn collect_cues<'a, 'input>(
+    head: &Head<'a, 'input>,
+    node: Node<'a, 'input>,
+    offset: Duration,
+    style: &Style,
+    region: Option<&str>,
+    cues: &mut Vec<Cue>,
+) {
+    for child in node.children().filter(|n| n.is_element()) {
+        let mut child_style = style.clone();
+        head.apply_style(child, &mut child_style, 0);
+        let child_region = attribute(child, "region").or(region);
+        let begin = attribute(child, "begin").and_then(|t| head.timing.parse(t));
+        // past the representable time, the element can't be timed
+        let child_offset = match offset.checked_add(begin.unwrap_or_default()) {
+            Some(child_offset) => child_offset,
+            None => continue,
+        };
+        match child.tag_name().name() {
+            "div" => collect_cues(head, child, child_offset, &child_style, child_region, cues),
+            "p" => {
+                let end = match (
+                    attribute(child, "end").and_then(|t| head.timing.parse(t)),
+                    attribute(child, "dur").and_then(|t| head.timing.parse(t)),
+                ) {
+                    (Some(end), _) => offset.checked_add(end),
+                    (None, Some(duration)) => child_offset.checked_add(duration),
+                    // no end, the paragraph can't be timed
+                    (None, None) => None,
+                };
+                let end = match end {
+                    Some(end) => end,
+                    None => continue,
+                };
+                let mut spans = Vec::new();
+                collect_spans(head, child, &child_style, &mut spans);
+                let spans = trim_spans(spans);
+                if spans.is_empty() {
+                    continue;
+                }
+                cues.push(Cue {
+                    start: child_offset,
+                    end,
+                    spans,
+                    position: child_region.and_then(|id| head.regions.get(id).cloned()),
+                });
+            }
+            _ => (),
+        }
+    }
+}
+
+pub fn parse(input: &str) -> Result<Subtitles> {
+    let document = roxmltree::Document::parse(input)?;
+    let root = document.root_element();
+    if root.tag_name().name() != "tt" {
+        bail!("not a TTML file");
+    }
+    let frame_rate = attribute(root, "frameRate")
+        .and_then(|r| r.parse::<f64>().ok())
+        .filter(|r| *r > 0.0)
+        .unwrap_or(30.0);
+    let mut head = Head {
+        styles: HashMap::new(),
+        regions: HashMap::new(),
+        timing: Timing {
+            frame_rate,
+            tick_rate: attribute(root, "tickRate")
+                .and_then(|r| r.parse::<f64>().ok())
+                .filter(|r| *r > 0.0)
+                .unwrap_or(1.0),
+        },
+    };
+    for node in root.descendants().filter(|n| n.is_element()) {
+        let id = match attribute(node, "id") {
+            Some(id) => id,
+            None => continue,
+        };
+        match node.tag_name().name() {
+            "style" => {
+                head.styles.insert(id, node);
+            }
+            "region" => {
+                if let Some(position) = Head::parse_region(node) {
+                    head.regions.insert(id, position);
+                }
+            }
+            _ => (),
+        }
+    }
+    let mut cues = Vec::new();
+    if let Some(body) = root.children().find(|n| n.has_tag_name("body")) {
+        let mut style = Style::default();
+        head.apply_style(body, &mut style, 0);
+        let begin = attribute(body, "begin").and_then(|t| head.timing.parse(t));
+        collect_cues(
+            &head,
+            body,
+            begin.unwrap_or_default(),
+            &style,
+            attribute(body, "region"),
+            &mut cues,
+        );
+    }
+    Ok(Subtitles { cues })
+}
+
+fn style_attributes(style: &Style) -> String {
+    let mut attributes = String::new();
+    if style.bold {
+        attributes.push_str(" tts:fontWeight=\"bold\"");
+    }
+    if style.italic {
+        attributes.push_str(" tts:fontStyle=\"italic\"");
+    }
+    if style.underline {
+        attributes.push_str(" tts:textDecoration=\"underline\"");
+    }
+    for (name, color) in [
+        ("color", style.color),
+        ("backgroundColor", style.background),
+    ] {
+        if let Some(color) = color {
+            attributes.push_str(&format!(
+                " tts:{}=\"rgba({},{},{},{})\"",
+                name, color.r, color.g, color.b, color.a
+            ));
+        }
+    }
+    attributes
+}
+
+/// A region placing the anchor of the cue at its position
+fn region_attributes(position: &Position) -> String {
+    let (horizontal, vertical) = position.anchor.grid();
+    // (origin, extent) on one axis
+    let axis = |value: f32, alignment: u8| match alignment {
+        0 => (value, 100.0 - value),
+        1 => {
+            let half = value.min(100.0 - value);
+            (value - half, half * 2.0)
+        }
+        _ => (0.0, value),
+    };
+    let (x, width) = axis(position.x, horizontal);
+    let (y, height) = axis(position.y, vertical);
+    format!(
+        " tts:origin=\"{}% {}%\" tts:extent=\"{}% {}%\" tts:textAlign=\"{}\" tts:displayAlign=\"{}\"",
+        x,
+        y,
+        width,
+        height,
+        ["start", "center", "end"][usize::from(horizontal)],
+        ["before", "center", "after"][usize::from(vertical)]
+    )
+}
+
+pub fn write(subtitles: &Subtitles) -> String {
+    let mut regions: Vec<&Position> = Vec::new();
+    let mut paragraphs = String::new();
+    for cue in &subtitles.cues {
+        let spans = normalize_spans(cue.spans.clone());
+        if cue.text().trim().is_empty() {
+            continue;
+        }
+        let region = match &cue.position {
+            Some(position) => {
+                let index = match regions.iter().position(|r| *r == position) {
+                    Some(index) => index,
+                    None => {
+                        regions.push(position);
+                        regions.len() - 1
+                    }
+                };
+                format!(" region=\"r{}\"", index)
+            }
+            None => String::new(),
+        };
+        let mut text = String::new();
+        for span in &spans {
+            let content = span
+                .text
+                .split('\n')
+                .map(escape_xml)
+                .collect::<Vec<_>>()
+                .join("<br/>");
+            let attributes = style_attributes(&span.style);
+            if attributes.is_empty() {
+                text.push_str(&content);
+            } else {
+                text.push_str(&format!("<span{}>{}</span>", attributes, content));
+            }
+        }
+        paragraphs.push_str(&format!(
+            "      <p begin=\"{}\" end=\"{}\"{}>{}</p>\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            region,
+            text
+        ));
+    }
+    let mut output = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <tt xmlns=\"http://www.w3.org/ns/ttml\" xmlns:tts=\"http://www.w3.org/ns/ttml#styling\">\n",
+    );
+    if !regions.is_empty() {
+        output.push_str("  <head>\n    <layout>\n");
+        for (index, position) in regions.iter().enumerate() {
+            output.push_str(&format!(
+                "      <region xml:id=\"r{}\"{}/>\n",
+                index,
+                region_attributes(position)
+            ));
+        }
+        output.push_str("    </layout>\n  </head>\n");
+    }
+    output.push_str("  <body>\n    <div>\n");
+    output.push_str(&paragraphs);
+    output.push_str("    </div>\n  </body>\n</tt>\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Anchor, Color, Position, Style};
+
+    use super::{parse, write, Timing};
+
+    #[test]
+    fn test_time_expressions() {
+        let timing = Timing {
+            frame_rate: 25.0,
+            tick_rate: 10_000_000.0,
+        };
+        for (text, expected) in [
+            ("00:00:01.500", Some(1500)),
+            ("00:00:01:05", Some(1200)),
+            ("1.5s", Some(1500)),
+            ("1500ms", Some(1500)),
+            ("2m", Some(120_000)),
+            ("1h", Some(3_600_000)),
+            ("50f", Some(2000)),
+            ("15000000t", Some(1500)),
+            ("15", None),
+            ("1.5x", None),
+            ("-1s", None),
+            ("9999999999999999:00:00:00", None),
+            // u64::MAX seconds, and a second of frames on top
+            ("5124095576030431:00:15:25", None),
+        ] {
+            assert_eq!(
+                timing.parse(text),
+                expected.map(Duration::from_millis),
+                "{:?}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let subtitles = parse(
+            r##"<?xml version="1.0" encoding="UTF-8"?>
+<tt xmlns="http://www.w3.org/ns/ttml" xmlns:tts="http://www.w3.org/ns/ttml#styling" xmlns:ttp="http://www.w3.org/ns/ttml#parameter" ttp:tickRate="1000">
+  <head>
+    <styling>
+      <style xml:id="base" tts:color="yellow"/>
+      <style xml:id="emphasis" style="base" tts:fontStyle="italic"/>
+    </styling>
+    <layout>
+      <region xml:id="top" tts:origin="10% 5%" tts:extent="80% 20%" tts:textAlign="center" tts:displayAlign="before"/>
+    </layout>
+  </head>
+  <body region="top">
+    <div begin="10s">
+      <p begin="1000t" dur="1500t">
+        first   <span style="emphasis">line</span>
+        <br/>
+        <span tts:backgroundColor="#00000080">second</span> line
+      </p>
+      <p begin="3s" end="4s"> </p>
+      <p begin="5s">no end</p>
+    </div>
+    <div begin="1e19s">
+      <div begin="1e19s"><p begin="1s" end="2s">nested past the end</p></div>
+      <p end="1e19s">ending past the end</p>
+      <p dur="1e19s">lasting past the end</p>
+    </div>
+  </body>
+</tt>"##,
+        )
+        .unwrap();
+        assert_eq!(subtitles.cues.len(), 1);
+        let cue = &subtitles.cues[0];
+        assert_eq!(cue.start, Duration::from_millis(11_000));
+        assert_eq!(cue.end, Duration::from_millis(12_500));
+        assert_eq!(cue.text(), "first line\nsecond line");
+        assert_eq!(
+            cue.spans[1].style,
+            Style {
+                italic: true,
+                color: Some(Color::rgb(255, 255, 0)),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            cue.spans[3].style.background,
+            Some(Color {
+                a: 128,
+                ..Color::BLACK
+            })
+        );
+        assert_eq!(
+            cue.position,
+            Some(Position {
+                anchor: Anchor::TopCenter,
+                x: 50.0,
+                y: 5.0,
+            })
+        );
+
+        assert!(parse("<timedtext format=\"3\"/>").is_err());
+        assert!(parse("WEBVTT\n").is_err());
+    }
+
+    #[test]
+    fn test_write_positions() {
+        let mut subtitles = parse(
+            r#"<tt xmlns="http://www.w3.org/ns/ttml"><body><div><p begin="1s" end="2s">text</p></div></body></tt>"#,
+        )
+        .unwrap();
+        for anchor in [Anchor::TopLeft, Anchor::Center, Anchor::BottomRight] {
+            let position = Position {
+                anchor,
+                x: 30.0,
+                y: 80.0,
+            };
+            subtitles.cues[0].position = Some(position.clone());
+            let parsed = parse(&write(&subtitles)).unwrap();
+            assert_eq!(parsed.cues[0].position, Some(position));
+        }
+    }
+}
@@ -0,0 +1,335 @@
+//! WebVTT - https://www.w3.org/TR/webvtt1/
+
+use reytan_extractor_api::anyhow::{anyhow, bail, Result};
+
+use crate::{
+    cue_lines, format_timestamp, normalize_spans, parse_clock_time, Anchor, Cue, Position, Span,
+    Style, Subtitles,
+};
+
+fn decode_entity(entity: &str) -> Option<char> {
+    Some(match entity {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => '\u{a0}',
+        "lrm" => '\u{200e}',
+        "rlm" => '\u{200f}',
+        _ => {
+            let code = entity.strip_prefix('#')?;
+            let code = match code.strip_prefix(['x', 'X']) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => code.parse().ok()?,
+            };
+            char::from_u32(code)?
+        }
+    })
+}
+
+/// Parses the cue text, keeping the bold, italic and underline.
+/// Other tags (voices, classes, timestamps of the words) are dropped
+fn parse_cue_text(text: &str) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut style = Style::default();
+    let mut current = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find(['<', '&']) {
+        current.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with('<') {
+            let end = match rest.find('>') {
+                Some(end) => end,
+                None => break,
+            };
+            let tag = &rest[1..end];
+            rest = &rest[end + 1..];
+            let (closing, name) = match tag.strip_prefix('/') {
+                Some(name) => (true, name),
+                None => (false, tag),
+            };
+            let new_style = match name.split(['.', ' ']).next() {
+                Some("b") => Style {
+                    bold: !closing,
+                    ..style.clone()
+                },
+                Some("i") => Style {
+                    italic: !closing,
+                    ..style.clone()
+                },
+                Some("u") => Style {
+                    underline: !closing,
+                    ..style.clone()
+                },
+                _ => continue,
+            };
+            spans.push(Span {
+                text: std::mem::take(&mut current),
+                style: std::mem::replace(&mut style, new_style),
+            });
+        } else {
+            let entity = rest[1..]
+                .find(';')
+                .and_then(|end| Some((end, decode_entity(&rest[1..end + 1])?)));
+            match entity {
+                Some((end, c)) => {
+                    current.push(c);
+                    rest = &rest[end + 2..];
+                }
+                None => {
+                    current.push('&');
+                    rest = &rest[1..];
+                }
+            }
+        }
+    }
+    current.push_str(rest);
+    spans.push(Span {
+        text: current,
+        style,
+    });
+    normalize_spans(spans)
+}
+
+fn parse_percent(value: &str) -> Option<f32> {
+    value.strip_suffix('%')?.parse().ok()
+}
+
+/// `line:90%,end position:10% align:start`
+fn parse_settings(settings: &str) -> Option<Position> {
+    let mut line = None;
+    let mut position = None;
+    let mut align = None;
+    for setting in settings.split_whitespace() {
+        match setting.split_once(':') {
+            Some(("line", value)) => {
+                let (value, line_align) = value.split_once(',').unwrap_or((value, "start"));
+                // line numbers are not supported
+                line = parse_percent(value).map(|l| (l, line_align));
+            }
+            Some(("position", value)) => {
+                position = parse_percent(value.split(',').next().unwrap_or(value));
+            }
+            Some(("align", value)) => align = Some(value),
+            _ => (),
+        }
+    }
+    if line.is_none() && position.is_none() {
+        return None;
+    }
+    let horizontal = match align {
+        Some("start") | Some("left") => 0,
+        Some("end") | Some("right") => 2,
+        _ => 1,
+    };
+    let vertical = match line {
+        Some((_, "center")) => 1,
+        Some((_, "end")) | None => 2,
+        Some(_) => 0,
+    };
+    Some(Position {
+        anchor: Anchor::from_grid(horizontal, vertical),
+        x: position.unwrap_or(f32::from(horizontal) * 50.0),
+        y: line.map(|(l, _)| l).unwrap_or(100.0),
+    })
+}
+
+pub fn parse(input: &str) -> Result<Subtitles> {
+    let input = input.trim_start_matches('\u{feff}');
+    if !input.starts_with("WEBVTT") {
+        bail!("not a WebVTT file");
+    }
+    let input = input.replace("\r\n", "\n").replace('\r', "\n");
+    let mut cues = Vec::new();
+    // the first block is the header
+    for block in input.split("\n\n").skip(1) {
+        let block = block.trim_matches('\n');
+        if block.starts_with("NOTE") || block.starts_with("STYLE") || block.starts_with("REGION") {
+            continue;
+        }
+        let mut lines = block.lines();
+        // the cue identifier is optional
+        let timing = match lines.find(|l| l.contains("-->")) {
+            Some(timing) => timing,
+            None => continue,
+        };
+        let (start, rest) = timing.split_once("-->").unwrap();
+        let rest = rest.trim_start();
+        let (end, settings) = rest.split_once([' ', '\t']).unwrap_or((rest, ""));
+        let text = lines.collect::<Vec<_>>().join("\n");
+        cues.push(Cue {
+            start: parse_clock_time(start)
+                .ok_or_else(|| anyhow!("invalid cue start: {:?}", start))?,
+            end: parse_clock_time(end).ok_or_else(|| anyhow!("invalid cue end: {:?}", end))?,
+            spans: parse_cue_text(&text),
+            position: parse_settings(settings),
+        });
+    }
+    Ok(Subtitles { cues })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Cue text with the `<b>`, `<i>` and `<u>` tags, used in SRT as well
+/// (which has no escaping, the text is written as is)
+pub(crate) fn write_cue_text(spans: &[Span], escape_text: bool) -> String {
+    let mut text = String::new();
+    for span in spans {
+        let tags = [
+            (span.style.bold, "b"),
+            (span.style.italic, "i"),
+            (span.style.underline, "u"),
+        ];
+        for (_, tag) in tags.iter().filter(|(enabled, _)| *enabled) {
+            text.push_str(&format!("<{}>", tag));
+        }
+        if escape_text {
+            text.push_str(&escape(&span.text));
+        } else {
+            text.push_str(&span.text);
+        }
+        for (_, tag) in tags.iter().rev().filter(|(enabled, _)| *enabled) {
+            text.push_str(&format!("</{}>", tag));
+        }
+    }
+    cue_lines(&text)
+}
+
+fn write_settings(position: &Position) -> String {
+    let (horizontal, vertical) = position.anchor.grid();
+    let alignment = ["start", "center", "end"];
+    format!(
+        " line:{}%,{} position:{}% align:{}",
+        position.y,
+        alignment[usize::from(vertical)],
+        position.x,
+        alignment[usize::from(horizontal)]
+    )
+}
+
+pub fn write(subtitles: &Subtitles) -> String {
+    let mut output = String::from("WEBVTT\n");
+    for cue in &subtitles.cues {
+        let text = write_cue_text(&cue.spans, true);
+        if text.is_empty() {
+            continue;
+        }
+        output.push_str(&format!(
+            "\n{} --> {}{}\n{}\n",
+            format_timestamp(cue.start, '.'),
+            format_timestamp(cue.end, '.'),
+            cue.position
+                .as_ref()
+                .map(write_settings)
+                .unwrap_or_default(),
+            text
+        ));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::{Anchor, Position, Span, Style};
+
+    use super::{parse, parse_cue_text, write};
+
+    #[test]
+    fn test_parse_cue_text() {
+        assert_eq!(
+            parse_cue_text("plain <b>bold <i>both</i></b> &amp; &lt;3&gt; &#x2665;"),
+            vec![
+                Span {
+                    text: "plain ".to_string(),
+                    style: Style::default(),
+                },
+                Span {
+                    text: "bold ".to_string(),
+                    style: Style {
+                        bold: true,
+                        ..Default::default()
+                    },
+                },
+                Span {
+                    text: "both".to_string(),
+                    style: Style {
+                        bold: true,
+                        italic: true,
+                        ..Default::default()
+                    },
+                },
+                Span {
+                    text: " & <3> \u{2665}".to_string(),
+                    style: Style::default(),
+                },
+            ]
+        );
+        // youtube's automatic captions
+        assert_eq!(
+            parse_cue_text("never<00:00:01.200><c> gonna</c><00:00:01.500><c> give</c>"),
+            vec![Span {
+                text: "never gonna give".to_string(),
+                style: Style::default(),
+            }]
+        );
+        assert_eq!(
+            parse_cue_text("<v Rick>you & me &unknown;"),
+            vec![Span {
+                text: "you & me &unknown;".to_string(),
+                style: Style::default(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        let subtitles = parse(
+            "\u{feff}WEBVTT\r\nKind: captions\r\nLanguage: en\r\n\r\nNOTE a comment --> not a cue\r\n\r\nSTYLE\r\n::cue { color: white }\r\n\r\n1\r\n00:01.000 --> 00:00:02.500 align:start position:10% line:5%\r\nfirst\r\nline\r\n\r\n\r\n00:00:03.000 --> 00:00:04.000 line:90%,end\r\nsecond\r\n",
+        )
+        .unwrap();
+        assert_eq!(subtitles.cues.len(), 2);
+        let first = &subtitles.cues[0];
+        assert_eq!(first.start, Duration::from_millis(1000));
+        assert_eq!(first.end, Duration::from_millis(2500));
+        assert_eq!(first.text(), "first\nline");
+        assert_eq!(
+            first.position,
+            Some(Position {
+                anchor: Anchor::TopLeft,
+                x: 10.0,
+                y: 5.0
+            })
+        );
+        assert_eq!(
+            subtitles.cues[1].position,
+            Some(Position {
+                anchor: Anchor::BottomCenter,
+                x: 50.0,
+                y: 90.0
+            })
+        );
+
+        assert!(parse("1\n00:00:01.000 --> 00:00:02.000\ntext\n").is_err());
+        assert!(parse("WEBVTT\n\n00:00:01.000 --> soon\ntext\n").is_err());
+        assert!(parse("WEBVTT\n\n9999999999999999:00:00.000 --> 00:00:02.000\ntext\n").is_err());
+    }
+
+    #[test]
+    fn test_write() {
+        let subtitles = parse(
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.500 align:start position:10% line:5%\n<i>first</i> &amp;\n\nline\n\n00:00:03.000 --> 00:00:04.000\n \n",
+        )
+        .unwrap();
+        assert_eq!(
+            write(&subtitles),
+            "WEBVTT\n\n00:00:01.000 --> 00:00:02.500 line:5%,start position:10% align:start\n<i>first</i> &amp;\n"
+        );
+    }
+}